@@ -1,4 +1,5 @@
 use std::{
+    collections::HashMap,
     fs::File,
     io::{Read, Write},
     path::PathBuf,
@@ -17,39 +18,58 @@ use serde_json::{json, Value};
 
 use crate::{
     auth::AuthState,
-    error::AppError,
+    error::{self, AppError},
     helper,
     logger::{self},
     rate_limiter::RateLimiter,
-    structs::{Item, RivenAttributeInfo, RivenTypeInfo},
+    structs::{AuctionWeaponInfo, Item, RivenAttributeInfo, RivenTypeInfo},
     wfm_client::client::WFMClient,
 };
 
-use super::modules::{item::ItemModule, riven::RivenModule};
+use super::modules::{auction::AuctionModule, item::ItemModule, riven::RivenModule};
+
+// Bump when the layout of the cache changes, an older cache is refreshed but still used while offline
+pub const CACHE_VERSION: i64 = 2;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[allow(dead_code)]
 pub struct CacheDataStruct {
+    pub version: i64,
     pub last_refresh: Option<String>,
     pub item: CacheDataItemStruct,
     pub riven: CacheDataRivenStruct,
+    pub auction: CacheDataAuctionStruct,
 }
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[allow(dead_code)]
 pub struct CacheDataItemStruct {
+    pub last_refresh: Option<String>,
+    pub ttl_hours: i64,
     pub items: Vec<Item>,
+    // Language code -> localized item name -> url name
+    pub localized_names: HashMap<String, HashMap<String, String>>,
 }
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct CacheDataRivenStruct {
+    pub last_refresh: Option<String>,
+    pub ttl_hours: i64,
     pub items: Vec<RivenTypeInfo>,
     pub attributes: Vec<RivenAttributeInfo>,
 }
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CacheDataAuctionStruct {
+    pub last_refresh: Option<String>,
+    pub ttl_hours: i64,
+    pub weapons: Vec<AuctionWeaponInfo>,
+}
 
 #[derive(Clone, Debug)]
 pub struct CacheClient {
     pub log_file: PathBuf,
     pub wfm: Arc<Mutex<WFMClient>>,
     pub cache_data: Arc<Mutex<CacheDataStruct>>,
+    // Set when the cache could not be refreshed and the app runs from the last saved cache
+    pub offline: Arc<Mutex<bool>>,
 }
 
 impl CacheClient {
@@ -58,13 +78,27 @@ impl CacheClient {
             log_file: PathBuf::from("cache"),
             wfm,
             cache_data: Arc::new(Mutex::new(CacheDataStruct {
+                version: CACHE_VERSION,
                 last_refresh: None,
-                item: CacheDataItemStruct { items: vec![] },
+                item: CacheDataItemStruct {
+                    last_refresh: None,
+                    ttl_hours: 24,
+                    items: vec![],
+                    localized_names: HashMap::new(),
+                },
                 riven: CacheDataRivenStruct {
+                    last_refresh: None,
+                    ttl_hours: 168,
                     items: vec![],
                     attributes: vec![],
                 },
+                auction: CacheDataAuctionStruct {
+                    last_refresh: None,
+                    ttl_hours: 168,
+                    weapons: vec![],
+                },
             })),
+            offline: Arc::new(Mutex::new(false)),
         }
     }
     fn get_file_path() -> PathBuf {
@@ -73,57 +107,193 @@ impl CacheClient {
         settings_path
     }
 
+    // Loads the last saved cache and refreshes the stale sections in the background.
+    // The first refresh is awaited when there is no cache yet, if it fails the app starts offline.
     pub async fn load(&self) -> Result<CacheDataStruct, AppError> {
-        let path_ref = Self::get_file_path();
-
-        if path_ref.exists() {
-            let (se, vaild) = Self::read_from_file()?;
-            if vaild {
-                let last_refresh = se.last_refresh.clone();
-                match last_refresh {
-                    Some(last_refresh) => {
-                        let last_refresh = chrono::DateTime::parse_from_rfc3339(&last_refresh)
-                            .map_err(|e| AppError::new("Cache", eyre!(e.to_string())))?;
-                        let now = chrono::Utc::now();
-                        let diff = now.signed_duration_since(last_refresh);
-                        if diff.num_hours() < 24 {
-                            let arced_mutex = Arc::clone(&self.cache_data);
-                            let mut my_lock = arced_mutex.lock()?;
-                            my_lock.last_refresh = Some(last_refresh.to_string());
-                            my_lock.item = se.item;
-                            my_lock.riven = se.riven;
-                            return Ok(my_lock.clone());
-                        } else {
-                            let data = self.refresh().await?;
-                            self.save_to_file()?;
-                            return Ok(data);
-                        }
-                    }
-                    None => {
-                        let data = self.refresh().await?;
-                        self.save_to_file()?;
-                        return Ok(data);
-                    }
+        let mut has_cache = false;
+        if Self::get_file_path().exists() {
+            match Self::read_from_file() {
+                Ok((data, _)) => {
+                    *self.cache_data.lock()? = data;
+                    has_cache = true;
                 }
-            } else {
-                let data = self.refresh().await?;
-                self.save_to_file()?;
-                return Ok(data);
+                Err(e) => error::create_log_file("cache.log".to_string(), &e),
             }
-        } else {
-            let data = self.refresh().await?;
-            self.save_to_file()?;
-            return Ok(data);
         }
+
+        if !has_cache {
+            match self.refresh().await {
+                Ok(_) => {
+                    self.save_to_file()?;
+                    // The ducat values take a request per prime set, they are fetched after the app started
+                    self.start_background_refresh();
+                }
+                Err(e) => {
+                    error::create_log_file("cache.log".to_string(), &e);
+                    self.set_offline(true)?;
+                }
+            }
+        } else if !self.get_stale_sections()?.is_empty() {
+            self.start_background_refresh();
+        }
+        let cache_data = self.cache_data.lock()?.clone();
+        Ok(cache_data)
     }
 
     pub async fn refresh(&self) -> Result<CacheDataStruct, AppError> {
-        self.items().refresh().await?;
-        self.riven().refresh().await?;
+        for section in ["item", "riven", "auction"] {
+            self.refresh_section(section).await?;
+        }
+        self.cache_data.lock()?.version = CACHE_VERSION;
         self.set_last_refresh(chrono::Utc::now().to_rfc3339())?;
+        self.set_offline(false)?;
         let cache_data = self.cache_data.lock()?.clone();
         Ok(cache_data)
     }
+
+    async fn refresh_section(&self, section: &str) -> Result<(), AppError> {
+        match section {
+            "item" => self.items().refresh().await?,
+            "riven" => self.riven().refresh().await?,
+            "auction" => {
+                // The weapons are only needed for lich and sister auctions, a failure keeps the old list
+                // and leaves the section stale so it is tried again on the next refresh.
+                if let Err(e) = self.auction().refresh().await {
+                    error::create_log_file("cache.log".to_string(), &e);
+                    logger::warning_con(
+                        "Cache",
+                        "Could not refresh the lich and sister weapons, keeping the old list",
+                    );
+                    return Ok(());
+                }
+            }
+            _ => {
+                return Err(AppError::new(
+                    "Cache",
+                    eyre!("Unknown section: {}", section),
+                ))
+            }
+        }
+        let now = Some(chrono::Utc::now().to_rfc3339());
+        let mut cache_data = self.cache_data.lock()?;
+        match section {
+            "item" => cache_data.item.last_refresh = now,
+            "riven" => cache_data.riven.last_refresh = now,
+            _ => cache_data.auction.last_refresh = now,
+        }
+        Ok(())
+    }
+
+    fn is_stale(last_refresh: &Option<String>, ttl_hours: i64) -> bool {
+        match last_refresh {
+            Some(last_refresh) => match chrono::DateTime::parse_from_rfc3339(last_refresh) {
+                Ok(last_refresh) => {
+                    let diff = chrono::Utc::now().signed_duration_since(last_refresh);
+                    diff.num_hours() >= ttl_hours
+                }
+                Err(_) => true,
+            },
+            None => true,
+        }
+    }
+
+    // Returns the sections older than their TTL, every section is stale when the cache version changed.
+    pub fn get_stale_sections(&self) -> Result<Vec<String>, AppError> {
+        let cache_data = self.cache_data.lock()?.clone();
+        let all = cache_data.version != CACHE_VERSION;
+        let mut sections = vec![];
+        if all || Self::is_stale(&cache_data.item.last_refresh, cache_data.item.ttl_hours) {
+            sections.push("item".to_string());
+        }
+        if all || Self::is_stale(&cache_data.riven.last_refresh, cache_data.riven.ttl_hours) {
+            sections.push("riven".to_string());
+        }
+        if all
+            || Self::is_stale(
+                &cache_data.auction.last_refresh,
+                cache_data.auction.ttl_hours,
+            )
+        {
+            sections.push("auction".to_string());
+        }
+        Ok(sections)
+    }
+
+    // Refreshes the stale sections only, the cache is saved after every section so a failure keeps the rest.
+    pub async fn refresh_stale(&self) -> Result<Vec<String>, AppError> {
+        let sections = self.get_stale_sections()?;
+        for section in sections.iter() {
+            if let Err(e) = self.refresh_section(section).await {
+                self.set_offline(true)?;
+                return Err(e);
+            }
+            self.save_to_file()?;
+        }
+        self.cache_data.lock()?.version = CACHE_VERSION;
+        self.set_last_refresh(chrono::Utc::now().to_rfc3339())?;
+        self.set_offline(false)?;
+        self.save_to_file()?;
+        Ok(sections)
+    }
+
+    // Fetches the ducat values of the prime parts that have none yet, new parts come in with an item refresh.
+    pub async fn refresh_ducats(&self) -> Result<i64, AppError> {
+        let updated = self.items().refresh_ducats().await?;
+        if updated > 0 {
+            self.save_to_file()?;
+        }
+        Ok(updated)
+    }
+
+    // Refreshes the stale sections and then the missing ducat values.
+    pub fn start_background_refresh(&self) {
+        let cache = self.clone();
+        tauri::async_runtime::spawn(async move {
+            match cache.refresh_stale().await {
+                Ok(sections) => {
+                    if !sections.is_empty() {
+                        logger::info_con(
+                            "Cache",
+                            format!("Refreshed cache sections: {}", sections.join(", ")).as_str(),
+                        );
+                    }
+                    if let Err(e) = cache.refresh_ducats().await {
+                        error::create_log_file("cache.log".to_string(), &e);
+                        logger::warning_con("Cache", "Could not refresh the ducat values");
+                    }
+                }
+                Err(e) => {
+                    error::create_log_file("cache.log".to_string(), &e);
+                    logger::warning_con("Cache", "Could not refresh the cache, running offline");
+                }
+            }
+            match cache.get_frontend_data() {
+                Ok(data) => helper::send_message_to_window("Cache:OnUpdate", Some(data)),
+                Err(e) => error::create_log_file("cache.log".to_string(), &e),
+            }
+        });
+    }
+
+    // The cache data the frontend keeps, sent at startup and after a background refresh.
+    pub fn get_frontend_data(&self) -> Result<Value, AppError> {
+        Ok(json!({
+            "offline": self.is_offline()?,
+            "items": self.items().get_types()?,
+            "riven_items": self.riven().get_types()?,
+            "riven_attributes": self.riven().get_attributes()?,
+            "auction_weapons": self.auction().get_weapons()?,
+        }))
+    }
+
+    pub fn is_offline(&self) -> Result<bool, AppError> {
+        Ok(*self.offline.lock()?)
+    }
+
+    pub fn set_offline(&self, offline: bool) -> Result<(), AppError> {
+        *self.offline.lock()? = offline;
+        Ok(())
+    }
+
     pub fn items(&self) -> ItemModule {
         ItemModule { client: self }
     }
@@ -139,6 +309,10 @@ impl CacheClient {
         RivenModule { client: self }
     }
 
+    pub fn auction(&self) -> AuctionModule<'_> {
+        AuctionModule { client: self }
+    }
+
     pub fn save_to_file(&self) -> Result<(), AppError> {
         let chache_data = self.cache_data.clone();
         let json = serde_json::to_string_pretty(&chache_data)
@@ -169,6 +343,12 @@ impl CacheClient {
         let mut json_value: Value = serde_json::from_str(json_str)
             .map_err(|e| AppError::new("Cache", eyre!(e.to_string())))?;
 
+        // Files from before the cache had a version are version 0
+        if json_value.get("version").is_none() {
+            json_value["version"] = json!(0);
+            is_valid = false;
+        }
+
         if json_value.get("last_refresh").is_none() {
             let now = chrono::Utc::now();
             // Set the 'last_refresh' property to None
@@ -182,6 +362,18 @@ impl CacheClient {
                 item_data["items"] = json!([]);
                 is_valid = false;
             }
+            if item_data.get("last_refresh").is_none() {
+                item_data["last_refresh"] = json!(null);
+                is_valid = false;
+            }
+            if item_data.get("ttl_hours").is_none() {
+                item_data["ttl_hours"] = json!(24);
+                is_valid = false;
+            }
+            if item_data.get("localized_names").is_none() {
+                item_data["localized_names"] = json!({});
+                is_valid = false;
+            }
         }
 
         // Check for nested properties within 'riven'
@@ -194,6 +386,37 @@ impl CacheClient {
                 riven_data["attributes"] = json!([]);
                 is_valid = false;
             }
+            if riven_data.get("last_refresh").is_none() {
+                riven_data["last_refresh"] = json!(null);
+                is_valid = false;
+            }
+            if riven_data.get("ttl_hours").is_none() {
+                riven_data["ttl_hours"] = json!(168);
+                is_valid = false;
+            }
+        }
+
+        // Check for nested properties within 'auction'
+        match json_value.get_mut("auction") {
+            Some(auction_data) => {
+                if auction_data.get("weapons").is_none() {
+                    auction_data["weapons"] = json!([]);
+                    is_valid = false;
+                }
+                if auction_data.get("last_refresh").is_none() {
+                    auction_data["last_refresh"] = json!(null);
+                    is_valid = false;
+                }
+                if auction_data.get("ttl_hours").is_none() {
+                    auction_data["ttl_hours"] = json!(168);
+                    is_valid = false;
+                }
+            }
+            None => {
+                json_value["auction"] =
+                    json!({ "last_refresh": null, "ttl_hours": 168, "weapons": [] });
+                is_valid = false;
+            }
         }
 
         // Deserialize the updated JSON object into a SettingsState struct
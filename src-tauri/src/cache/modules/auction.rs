@@ -0,0 +1,55 @@
+use std::sync::Arc;
+
+use serde_json::json;
+
+use crate::{
+    cache::client::CacheClient, error::AppError, helper, logger, structs::AuctionWeaponInfo,
+};
+pub struct AuctionModule<'a> {
+    pub client: &'a CacheClient,
+}
+
+impl<'a> AuctionModule<'a> {
+    // Refrece
+    pub async fn refresh(&self) -> Result<(), AppError> {
+        self.refresh_weapons().await?;
+        Ok(())
+    }
+    pub async fn refresh_weapons(&self) -> Result<Vec<AuctionWeaponInfo>, AppError> {
+        let wfm = self.client.wfm.lock()?.clone();
+        helper::send_message_to_window(
+            "set_initializstatus",
+            Some(json!({"status": "Downloading Lich and Sister Data from Warframe.Market..."})),
+        );
+        let mut weapons = wfm.auction().get_all_weapon_types("lich").await?;
+        weapons.append(&mut wfm.auction().get_all_weapon_types("sister").await?);
+
+        let arced_mutex = Arc::clone(&self.client.cache_data);
+        let mut my_lock = arced_mutex.lock()?;
+        my_lock.auction.weapons = weapons.clone();
+        Ok(weapons)
+    }
+    pub fn get_weapons(&self) -> Result<Vec<AuctionWeaponInfo>, AppError> {
+        let weapons = self.client.cache_data.lock()?.clone().auction.weapons;
+        Ok(weapons.clone())
+    }
+
+    pub fn find_weapon(
+        &self,
+        auction_type: &str,
+        url_name: &str,
+    ) -> Result<Option<AuctionWeaponInfo>, AppError> {
+        let weapons = self.client.cache_data.lock()?.clone().auction.weapons;
+        let weapon = weapons
+            .iter()
+            .find(|&x| x.url_name == url_name && x.auction_type.as_deref() == Some(auction_type))
+            .cloned();
+        if weapon.is_none() {
+            logger::warning_con(
+                "CacheAuctions",
+                format!("Weapon Type: {} {} not found", auction_type, url_name).as_str(),
+            );
+        }
+        Ok(weapon)
+    }
+}
@@ -21,6 +21,16 @@ impl<'a> ItemModule<'a> {
     // Refrece
     pub async fn refresh(&self) -> Result<(), AppError> {
         self.refresh_types().await?;
+        // Trade logs of non English clients use the item names of that language
+        let language = helper::get_warframe_language_code();
+        if language != "en" {
+            if let Err(e) = self.refresh_localized_names(&language).await {
+                logger::warning_con(
+                    "CacheItems",
+                    format!("Could not get {} item names: {:?}", language, e).as_str(),
+                );
+            }
+        }
         Ok(())
     }
 
@@ -50,6 +60,9 @@ impl<'a> ItemModule<'a> {
                 .await
                 .map_err(|e| AppError::new("CacheItems", eyre!(e.to_string())))?;
 
+        // Ducat values and part quantities come from the item details and are kept between refreshes
+        let old_items = self.client.cache_data.lock()?.clone().item.items;
+
         helper::emit_undate_initializ_status("Storing Looping through Item Data...", None);
         let mut items: Vec<Item> = Vec::new();
         for item in wfm_items.clone() {
@@ -85,6 +98,9 @@ impl<'a> ItemModule<'a> {
                 new.trade_tax = Some(helper::calculate_trade_tax(tags, mod_max_rank));
                 new.mr_requirement = mr_requirement;
                 new.wikia_url = wikia_url;
+                let old_item = old_items.iter().find(|x| x.id == item.id);
+                new.ducats = old_item.and_then(|x| x.ducats);
+                new.quantity_for_set = old_item.and_then(|x| x.quantity_for_set);
                 // Only send for every 10th item
                 if items.len() % 100 == 0 {
                     helper::emit_undate_initializ_status(
@@ -109,6 +125,134 @@ impl<'a> ItemModule<'a> {
         Ok(items)
     }
 
+    // Fetches the ducat values of prime parts and the part quantities of every set from the item details of the sets.
+    // Only sets with parts that have no part quantity, or for prime sets no ducat value, yet are fetched.
+    pub async fn refresh_ducats(&self) -> Result<i64, AppError> {
+        let wfm = self.client.wfm.lock()?.clone();
+        let items = self.get_types()?;
+
+        let sets = items
+            .iter()
+            .filter(|i| {
+                i.tags
+                    .clone()
+                    .unwrap_or_default()
+                    .contains(&"set".to_string())
+            })
+            .filter(|set| {
+                let prime = set
+                    .tags
+                    .clone()
+                    .unwrap_or_default()
+                    .contains(&"prime".to_string());
+                set.set_items.clone().unwrap_or_default().iter().any(|p| {
+                    items.iter().any(|i| {
+                        (i.id == *p || i.url_name == *p)
+                            && i.url_name != set.url_name
+                            && (i.quantity_for_set.is_none() || (prime && i.ducats.is_none()))
+                    })
+                })
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let total = sets.len();
+        let mut updated = 0;
+        for (index, set) in sets.iter().enumerate() {
+            helper::emit_progress(
+                "Cache:Ducats:Progress",
+                "cache.ducats.progress",
+                Some(json!({ "name": set.url_name, "count": index + 1, "total": total})),
+                false,
+            );
+            let details = match wfm.items().get_item(set.url_name.clone()).await {
+                Ok(details) => details,
+                Err(e) => {
+                    logger::warning_con(
+                        "CacheItems",
+                        format!("Could not get ducats for {}: {:?}", set.url_name, e).as_str(),
+                    );
+                    continue;
+                }
+            };
+            let arced_mutex = Arc::clone(&self.client.cache_data);
+            let mut my_lock = arced_mutex.lock()?;
+            for part in details
+                .items_in_set
+                .iter()
+                .filter(|p| !p.set_root.unwrap_or(false))
+            {
+                if let Some(item) = my_lock.item.items.iter_mut().find(|i| i.id == part.id) {
+                    item.quantity_for_set = Some(part.quantity_for_set.unwrap_or(1));
+                    if part.ducats.is_some() {
+                        item.ducats = part.ducats;
+                    }
+                    updated += 1;
+                }
+            }
+        }
+        helper::emit_progress(
+            "Cache:Ducats:Progress",
+            "cache.ducats.completed",
+            Some(json!({ "count": updated })),
+            true,
+        );
+        Ok(updated)
+    }
+
+    // Stores the item names of a language with their url name.
+    pub async fn refresh_localized_names(&self, language: &str) -> Result<usize, AppError> {
+        let wfm = self.client.wfm.lock()?.clone();
+        helper::emit_undate_initializ_status(
+            format!(
+                "Downloading {} Item Names from Warframe.Market...",
+                language
+            )
+            .as_str(),
+            None,
+        );
+        let names = wfm
+            .items()
+            .get_all_items_in_language(language)
+            .await?
+            .into_iter()
+            .map(|item| (item.item_name, item.url_name))
+            .collect::<HashMap<String, String>>();
+        let total = names.len();
+
+        let arced_mutex = Arc::clone(&self.client.cache_data);
+        let mut my_lock = arced_mutex.lock()?;
+        my_lock
+            .item
+            .localized_names
+            .insert(language.to_string(), names);
+        Ok(total)
+    }
+
+    pub fn find_by_localized_name(
+        &self,
+        language: &str,
+        item_name: &str,
+    ) -> Result<Option<Item>, AppError> {
+        let cache_data = self.client.cache_data.lock()?;
+        let url_name = match cache_data
+            .item
+            .localized_names
+            .get(language)
+            .and_then(|names| names.get(item_name))
+        {
+            Some(url_name) => url_name.clone(),
+            None => return Ok(None),
+        };
+        let item = cache_data
+            .item
+            .items
+            .iter()
+            .find(|x| x.url_name == url_name)
+            .cloned();
+        Ok(item)
+    }
+
     pub fn get_types(&self) -> Result<Vec<Item>, AppError> {
         let items = self.client.cache_data.lock()?.clone().item.items;
         Ok(items)
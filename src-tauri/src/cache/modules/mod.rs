@@ -1,2 +1,3 @@
+pub mod auction;
 pub mod item;
 pub mod riven;
@@ -0,0 +1,87 @@
+use std::sync::{Arc, Mutex};
+
+use once_cell::sync::Lazy;
+
+use crate::{
+    cache::client::CacheClient,
+    error::{self, AppError},
+    helper,
+    live_scraper::client::LiveScraperClient,
+};
+
+// Create a static variable to store the log file name
+static LOG_FILE: Lazy<Mutex<String>> =
+    Lazy::new(|| Mutex::new("command_analytics.log".to_string()));
+
+#[tauri::command]
+pub async fn get_set_arbitrage(
+    live_scraper: tauri::State<'_, Arc<Mutex<LiveScraperClient>>>,
+) -> Result<serde_json::Value, AppError> {
+    let live_scraper = live_scraper.lock()?.clone();
+    match live_scraper.set().get_set_arbitrage().await {
+        Ok(df) => helper::dataframe_to_json(&df),
+        Err(e) => {
+            error::create_log_file(LOG_FILE.lock().unwrap().to_owned(), &e);
+            Err(e)
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn get_ducat_ranking(
+    live_scraper: tauri::State<'_, Arc<Mutex<LiveScraperClient>>>,
+) -> Result<serde_json::Value, AppError> {
+    let live_scraper = live_scraper.lock()?.clone();
+    match live_scraper.ducat().get_ducat_ranking().await {
+        Ok(df) => helper::dataframe_to_json(&df),
+        Err(e) => {
+            error::create_log_file(LOG_FILE.lock().unwrap().to_owned(), &e);
+            Err(e)
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn refresh_ducat_values(
+    cache: tauri::State<'_, Arc<Mutex<CacheClient>>>,
+) -> Result<i64, AppError> {
+    let cache = cache.lock()?.clone();
+    let updated = match cache.items().refresh_ducats().await {
+        Ok(updated) => updated,
+        Err(e) => {
+            error::create_log_file(LOG_FILE.lock().unwrap().to_owned(), &e);
+            return Err(e);
+        }
+    };
+    cache.save_to_file()?;
+    cache.items().emit();
+    Ok(updated)
+}
+
+#[tauri::command]
+pub async fn get_rank_up_profit(
+    live_scraper: tauri::State<'_, Arc<Mutex<LiveScraperClient>>>,
+) -> Result<serde_json::Value, AppError> {
+    let live_scraper = live_scraper.lock()?.clone();
+    match live_scraper.rank_up().get_rank_up_profit().await {
+        Ok(df) => helper::dataframe_to_json(&df),
+        Err(e) => {
+            error::create_log_file(LOG_FILE.lock().unwrap().to_owned(), &e);
+            Err(e)
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn get_market_indicators(
+    live_scraper: tauri::State<'_, Arc<Mutex<LiveScraperClient>>>,
+) -> Result<serde_json::Value, AppError> {
+    let live_scraper = live_scraper.lock()?.clone();
+    match live_scraper.indicator().get_indicators().await {
+        Ok(df) => helper::dataframe_to_json(&df),
+        Err(e) => {
+            error::create_log_file(LOG_FILE.lock().unwrap().to_owned(), &e);
+            Err(e)
+        }
+    }
+}
@@ -52,7 +52,6 @@ pub async fn init(
     let mut response = json!({
         "settings": &settings.clone(),
         "user": &auth.clone(),
-        "price_scraper_last_run": price_scraper.get_status(),
     });
 
     helper::emit_undate_initializ_status("Loading Database...", None);
@@ -63,14 +62,16 @@ pub async fn init(
             return Err(e);
         }
     }
+    response["price_scraper_last_run"] = json!(price_scraper.get_status().await?);
 
-    // Load Cache
+    // Load Cache, if it can not be refreshed the app starts offline from the last saved cache
     helper::emit_undate_initializ_status("Loading Cache...", None);
     match cache.load().await {
         Ok(_) => {
             response["items"] = json!(cache.items().get_types()?);
             response["riven_items"] = json!(cache.riven().get_types()?);
             response["riven_attributes"] = json!(cache.riven().get_attributes()?);
+            response["auction_weapons"] = json!(cache.auction().get_weapons()?);
         }
         Err(e) => {
             error::create_log_file(LOG_FILE.lock().unwrap().to_owned(), &e);
@@ -78,7 +79,7 @@ pub async fn init(
         }
     }
 
-    // Validate Auth
+    // Validate Auth, when warframe.market can not be reached the app continues offline
     helper::emit_undate_initializ_status("Validating Credentials...", None);
     let is_validate = match wfm.auth().validate().await {
         Ok(is_validate) => {
@@ -87,9 +88,13 @@ pub async fn init(
         }
         Err(e) => {
             error::create_log_file(LOG_FILE.lock().unwrap().to_owned(), &e);
-            return Err(e);
+            cache.set_offline(true)?;
+            response["valid"] = json!(false);
+            false
         }
     };
+    let offline = cache.is_offline()?;
+    response["offline"] = json!(offline);
 
     // Load Stock Items, Rivens
     helper::emit_undate_initializ_status("Loading Stock...", None);
@@ -113,6 +118,58 @@ pub async fn init(
             return Err(e);
         }
     };
+    // Load Stock Sets
+    match db.stock_set().get_sets().await {
+        Ok(items) => {
+            response["stock_sets"] = json!(items);
+        }
+        Err(e) => {
+            error::create_log_file(LOG_FILE.lock().unwrap().to_owned(), &e);
+            return Err(e);
+        }
+    };
+    // Load Stock Auctions
+    match db.stock_auction().get_auctions().await {
+        Ok(items) => {
+            response["stock_auctions"] = json!(items);
+        }
+        Err(e) => {
+            error::create_log_file(LOG_FILE.lock().unwrap().to_owned(), &e);
+            return Err(e);
+        }
+    };
+    // Load Watchlist
+    match db.watchlist().get_items().await {
+        Ok(items) => {
+            response["watchlist"] = json!(items);
+        }
+        Err(e) => {
+            error::create_log_file(LOG_FILE.lock().unwrap().to_owned(), &e);
+            return Err(e);
+        }
+    };
+
+    // Load Log Events
+    match db.log_event().get_items().await {
+        Ok(items) => {
+            response["log_events"] = json!(items);
+        }
+        Err(e) => {
+            error::create_log_file(LOG_FILE.lock().unwrap().to_owned(), &e);
+            return Err(e);
+        }
+    };
+
+    // Load Trade Partners
+    match db.trade_partner().get_items().await {
+        Ok(items) => {
+            response["trade_partners"] = json!(items);
+        }
+        Err(e) => {
+            error::create_log_file(LOG_FILE.lock().unwrap().to_owned(), &e);
+            return Err(e);
+        }
+    };
 
     // Load Transactions
     helper::emit_undate_initializ_status("Loading Transactions...", None);
@@ -126,7 +183,7 @@ pub async fn init(
         }
     };
 
-    if is_validate {
+    if is_validate && !offline {
         helper::emit_undate_initializ_status("Loading Your Orders...", None);
         let mut ordres_vec = match wfm.orders().get_my_orders().await {
             Ok(ordres_vec) => ordres_vec,
@@ -161,13 +218,19 @@ pub async fn init(
     }
 
     // Check for updates
-    helper::emit_undate_initializ_status("Checking for updates...", None);
-    response["app_info"] = helper::get_app_info().await?;
+    if !offline {
+        helper::emit_undate_initializ_status("Checking for updates...", None);
+        match helper::get_app_info().await {
+            Ok(app_info) => response["app_info"] = app_info,
+            Err(e) => error::create_log_file(LOG_FILE.lock().unwrap().to_owned(), &e),
+        }
+    }
 
     // Start EE Log Parser
     if !ee_log.is_running() {
         ee_log.start_loop();
     }
+    response["ee_log"] = ee_log.get_status();
 
     Ok(response)
 }
@@ -176,6 +239,7 @@ pub async fn init(
 pub async fn update_settings(
     settings: SettingsState,
     settings_state: tauri::State<'_, Arc<std::sync::Mutex<SettingsState>>>,
+    ee_log: tauri::State<'_, Arc<std::sync::Mutex<EELogParser>>>,
 ) -> Result<(), AppError> {
     let arced_mutex = Arc::clone(&settings_state);
     let mut my_lock = arced_mutex.lock()?;
@@ -186,10 +250,26 @@ pub async fn update_settings(
     // Set Live Scraper Settings
     my_lock.live_scraper = settings.live_scraper;
 
+    // Set Price Scraper Settings
+    my_lock.price_scraper = settings.price_scraper;
+
+    // Set Watchlist Settings
+    my_lock.watchlist = settings.watchlist;
+
+    // Set Scheduler Settings
+    my_lock.scheduler = settings.scheduler;
+
+    // Set EE Log Settings, the parser picks up a new path on its next check
+    my_lock.ee_log = settings.ee_log;
+
     // Set Whisper Scraper Settings
     my_lock.notifications = settings.notifications;
 
     my_lock.save_to_file().expect("Could not save settings");
+    // The parser keeps a copy of the settings it uses on every line
+    drop(my_lock);
+    let ee_log = ee_log.lock()?.clone();
+    ee_log.on_settings_change()?;
     Ok(())
 }
 
@@ -0,0 +1,161 @@
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use once_cell::sync::Lazy;
+use serde_json::json;
+
+use crate::{
+    database::{client::DBClient, modules::transaction::TransactionStruct},
+    error::{self, AppError},
+    structs::TradeClassification,
+    wf_ee_log_parser::{client::EELogParser, events::on_new_trading::PlayerTradeStruct},
+};
+
+// Create a static variable to store the log file name
+static LOG_FILE: Lazy<Mutex<String>> = Lazy::new(|| Mutex::new("command_ee_log.log".to_string()));
+
+// A stored transaction this close to the trade is the same trade, it was recorded live or by an earlier replay.
+// Live transactions are created when the stock is sold, which can be a while after the trade.
+const SAME_TRADE_MINUTES: i64 = 10;
+
+// Returns true when the trade is already stored as a transaction of the same item and type.
+// A stored transaction with a player only matches trades with that player.
+fn is_stored(
+    transactions: &[TransactionStruct],
+    trade: &PlayerTradeStruct,
+    url_name: &str,
+    transaction_type: &str,
+) -> bool {
+    let trade_time = match chrono::DateTime::parse_from_rfc3339(&trade.crated_at) {
+        Ok(time) => time,
+        Err(_) => return false,
+    };
+    transactions.iter().any(|t| {
+        let user_name = t
+            .properties
+            .as_ref()
+            .and_then(|p| p.0.as_ref())
+            .and_then(|p| p.get("user_name"))
+            .and_then(|u| u.as_str());
+        t.url == url_name
+            && t.transaction_type == transaction_type
+            && (user_name.is_none() || user_name == Some(trade.user_name.as_str()))
+            && chrono::DateTime::parse_from_rfc3339(&t.created).is_ok_and(|created| {
+                (created - trade_time).num_minutes().abs() <= SAME_TRADE_MINUTES
+            })
+    })
+}
+
+// Replays an EE.log file without notifications and returns the trades and conversations in it.
+// With import the sales and purchases of a single item or riven are stored as transactions.
+#[tauri::command]
+pub async fn replay_ee_log(
+    path: String,
+    import: bool,
+    ee_log: tauri::State<'_, Arc<Mutex<EELogParser>>>,
+    db: tauri::State<'_, Arc<Mutex<DBClient>>>,
+) -> Result<serde_json::Value, AppError> {
+    let ee_log = ee_log.lock()?.clone();
+    let db = db.lock()?.clone();
+    let (trades, conversations) = match ee_log.replay(PathBuf::from(path)) {
+        Ok(result) => result,
+        Err(e) => {
+            error::create_log_file(LOG_FILE.lock().unwrap().to_owned(), &e);
+            return Err(e);
+        }
+    };
+
+    let mut imported = vec![];
+    if import {
+        let transactions = db.transaction().get_items().await?;
+        for trade in trades.iter() {
+            // Every trade counts for the partner, like a live trade. The partner history skips the trades
+            // counted before, live or by an earlier replay
+            if !trade.user_name.is_empty() && !trade.crated_at.is_empty() {
+                let (platinum_in, platinum_out) = trade.get_platinum();
+                if let Err(e) = db
+                    .trade_partner()
+                    .record_trade(
+                        &trade.user_name,
+                        platinum_in,
+                        platinum_out,
+                        &trade.crated_at,
+                    )
+                    .await
+                {
+                    error::create_log_file(LOG_FILE.lock().unwrap().to_owned(), &e);
+                }
+            }
+            let (transaction_type, items) = match trade.trade_type {
+                TradeClassification::Sale => ("sell", &trade.offerings),
+                TradeClassification::Purchase => ("buy", &trade.receiving),
+                _ => continue,
+            };
+            // Only a single item traded for platinum can be stored as a transaction
+            let items = items
+                .iter()
+                .filter(|i| i.name != "plat")
+                .collect::<Vec<_>>();
+            if items.len() != 1 {
+                continue;
+            }
+            let item = items[0];
+            let url_name = match &item.wfm_url_name {
+                Some(url_name) => url_name,
+                None => continue,
+            };
+            // The url of a traded riven is the url of its weapon
+            let item_type = if item.riven.is_some() {
+                "riven"
+            } else {
+                "item"
+            };
+            // A trade without a log time can not be told apart from the ones already stored
+            if trade.crated_at.is_empty()
+                || is_stored(&transactions, trade, url_name, transaction_type)
+            {
+                continue;
+            }
+            match db
+                .transaction()
+                .create_at(
+                    url_name,
+                    item_type,
+                    transaction_type,
+                    item.quantity,
+                    trade.total_platinum,
+                    item.rank.max(0),
+                    Some(json!({ "user_name": trade.user_name, "source": "ee_log" })),
+                    trade.crated_at.clone(),
+                )
+                .await
+            {
+                Ok(transaction) => imported.push(transaction),
+                Err(e) => error::create_log_file(LOG_FILE.lock().unwrap().to_owned(), &e),
+            }
+        }
+    }
+    Ok(json!({
+        "trades": trades,
+        "conversations": conversations,
+        "imported": imported,
+    }))
+}
+
+// Replays an EE.log file and returns how long reading, the prefilter and the events took per line.
+#[tauri::command]
+pub fn benchmark_ee_log(
+    path: String,
+    ee_log: tauri::State<'_, Arc<Mutex<EELogParser>>>,
+) -> Result<serde_json::Value, AppError> {
+    let ee_log = ee_log.lock()?.clone();
+    match ee_log.benchmark(PathBuf::from(path)) {
+        Ok(result) => Ok(result),
+        Err(e) => {
+            error::create_log_file(LOG_FILE.lock().unwrap().to_owned(), &e);
+            Err(e)
+        }
+    }
+}
@@ -2,7 +2,14 @@ use std::sync::{Arc, Mutex};
 
 use once_cell::sync::Lazy;
 
-use crate::{error, live_scraper::client::LiveScraperClient, logger::error};
+use eyre::eyre;
+
+use crate::{
+    cache::client::CacheClient,
+    error::{self, AppError},
+    helper,
+    live_scraper::client::LiveScraperClient,
+};
 
 // Create a static variable to store the log file name
 static LOG_FILE: Lazy<Mutex<String>> = Lazy::new(|| Mutex::new("command_live_scraper.log".to_string()));
@@ -10,10 +17,17 @@ static LOG_FILE: Lazy<Mutex<String>> = Lazy::new(|| Mutex::new("command_live_scr
 #[tauri::command]
 pub fn toggle_live_scraper(
     live_scraper: tauri::State<'_, Arc<std::sync::Mutex<LiveScraperClient>>>,
+    cache: tauri::State<'_, Arc<std::sync::Mutex<CacheClient>>>,
 ) {
+    let offline = cache.lock().unwrap().is_offline().unwrap_or(false);
     let mut live_scraper = live_scraper.lock().unwrap();
     if live_scraper.is_running() {
         live_scraper.stop_loop();
+    } else if offline {
+        // The live scraper needs warframe.market, it can not run while offline
+        let e = AppError::new("LiveScraper", eyre!("Warframe.market can not be reached"));
+        error::create_log_file(LOG_FILE.lock().unwrap().to_owned(), &e);
+        helper::send_message_to_window("LiveScraper:Error", Some(e.to_json()));
     } else {
         match live_scraper.start_loop() {
             Ok(_) => {}
@@ -1,10 +1,15 @@
+pub mod analytics;
 pub mod auctions;
 pub mod auth;
 pub mod base;
 pub mod debug;
+pub mod ee_log;
 pub mod live_scraper;
 pub mod orders;
 pub mod price_scraper;
+pub mod scheduler;
 pub mod stock;
 pub mod chat;
+pub mod trade_partner;
 pub mod transaction;
+pub mod watchlist;
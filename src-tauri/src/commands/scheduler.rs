@@ -0,0 +1,42 @@
+use std::sync::{Arc, Mutex};
+
+use once_cell::sync::Lazy;
+use serde_json::json;
+
+use crate::{
+    error::{self, AppError},
+    scheduler::Scheduler,
+};
+
+// Create a static variable to store the log file name
+static LOG_FILE: Lazy<Mutex<String>> =
+    Lazy::new(|| Mutex::new("command_scheduler.log".to_string()));
+
+#[tauri::command]
+pub fn get_scheduler_jobs(
+    scheduler: tauri::State<'_, Arc<Mutex<Scheduler>>>,
+) -> Result<serde_json::Value, AppError> {
+    let scheduler = scheduler.lock()?.clone();
+    match scheduler.get_jobs() {
+        Ok(jobs) => Ok(json!(jobs)),
+        Err(e) => {
+            error::create_log_file(LOG_FILE.lock().unwrap().to_owned(), &e);
+            Err(e)
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn run_scheduler_job(
+    id: String,
+    scheduler: tauri::State<'_, Arc<Mutex<Scheduler>>>,
+) -> Result<serde_json::Value, AppError> {
+    let scheduler = scheduler.lock()?.clone();
+    match scheduler.run_job(&id).await {
+        Ok(_) => Ok(json!(scheduler.get_jobs()?)),
+        Err(e) => {
+            error::create_log_file(LOG_FILE.lock().unwrap().to_owned(), &e);
+            Err(e)
+        }
+    }
+}
@@ -8,7 +8,7 @@ static LOG_FILE: Lazy<Mutex<String>> = Lazy::new(|| Mutex::new("command_stock.lo
 use crate::{
     database::{
         client::DBClient,
-        modules::{stock_item, stock_riven::MatchRivenStruct},
+        modules::{stock_auction::MatchAuctionStruct, stock_riven::MatchRivenStruct},
     },
     enums::{LogLevel, OrderType},
     error::{self, AppError},
@@ -408,6 +408,44 @@ pub async fn import_auction(
     }
     let auction = auction.unwrap().clone();
 
+    // Lich and Sister weapons go into the auction stock
+    if auction.item.item_type == "lich" || auction.item.item_type == "sister" {
+        let stock = match db
+            .stock_auction()
+            .import_auction(auction.clone(), price)
+            .await
+        {
+            Ok(stock) => stock,
+            Err(e) => {
+                error::create_log_file(LOG_FILE.lock().unwrap().to_owned(), &e);
+                return Err(e);
+            }
+        };
+        if stock.price <= 0.0 {
+            return Ok(json!(stock.clone()));
+        }
+        match db
+            .transaction()
+            .create(
+                &stock.weapon_url,
+                &stock.auction_type,
+                "buy",
+                1,
+                price as i32,
+                0,
+                Some(json!(stock.clone())),
+            )
+            .await
+        {
+            Ok(_) => {}
+            Err(e) => {
+                error::create_log_file(LOG_FILE.lock().unwrap().to_owned(), &e);
+                return Err(e);
+            }
+        }
+        return Ok(json!(stock.clone()));
+    }
+
     // Import Auction into Stock DB
     let riven_item = match db
         .stock_riven()
@@ -541,8 +579,11 @@ pub async fn sell_riven_stock(
     }
     let stock = stock.unwrap().clone();
 
-    // Delete Riven from Stock
-    db.stock_riven().delete(id).await?;
+    // Delete Riven from Stock and create the Transaction
+    db.stock_riven()
+        .sell_riven(id, price)
+        .await
+        .inspect_err(|e| error::create_log_file(LOG_FILE.lock().unwrap().to_owned(), e))?;
 
     let json_stock = serde_json::to_value(&stock).unwrap();
 
@@ -560,25 +601,177 @@ pub async fn sell_riven_stock(
             }
         };
     }
+    Ok(json_stock)
+}
+
+// -----------------------------------------------------------------------------------------------
+
+// Auction Stock Commands (Lich/Sister weapons)
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn create_auction_stock(
+    auction_type: String,
+    url_name: String,
+    name: Option<String>,
+    element: String,
+    damage: i32,
+    having_ephemera: bool,
+    quirk: Option<String>,
+    match_auction: Option<MatchAuctionStruct>,
+    price: f64,
+    minium_price: Option<i32>,
+    db: tauri::State<'_, Arc<Mutex<DBClient>>>,
+) -> Result<serde_json::Value, AppError> {
+    let db = db.lock()?.clone();
+
+    // Create Auction in Stock DB
+    let stock = match db
+        .stock_auction()
+        .create(
+            None,
+            &auction_type,
+            &url_name,
+            name,
+            &element,
+            damage,
+            having_ephemera,
+            quirk,
+            match_auction,
+            price,
+            minium_price,
+        )
+        .await
+    {
+        Ok(stock) => stock,
+        Err(e) => {
+            error::create_log_file(LOG_FILE.lock().unwrap().to_owned(), &e);
+            return Err(e);
+        }
+    };
+
+    // If price is less than 0, return
+    if price <= 0.0 {
+        return Ok(json!(stock.clone()));
+    }
 
     // Create Transaction
-    db.transaction()
+    match db
+        .transaction()
         .create(
             &stock.weapon_url,
-            "riven",
-            "sell",
+            &stock.auction_type,
+            "buy",
             1,
             price as i32,
-            stock.rank,
+            0,
+            Some(json!(stock.clone())),
+        )
+        .await
+    {
+        Ok(_) => Ok(json!(stock.clone())),
+        Err(e) => {
+            error::create_log_file(LOG_FILE.lock().unwrap().to_owned(), &e);
+            Err(e)
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn update_auction_stock(
+    id: i64,
+    match_auction: Option<MatchAuctionStruct>,
+    minium_price: Option<i32>,
+    private: Option<bool>,
+    db: tauri::State<'_, Arc<Mutex<DBClient>>>,
+) -> Result<serde_json::Value, AppError> {
+    let db = db.lock()?.clone();
+    let stock = db.stock_auction().get_by_id(id).await?;
+    if stock.is_none() {
+        return Err(AppError::new("Command", eyre!("Auction {} not found", id)));
+    }
+
+    let stock = db
+        .stock_auction()
+        .update_by_id(
+            id,
+            None,
+            None,
+            None,
+            match_auction,
+            minium_price,
+            None,
+            private,
+        )
+        .await?;
+    Ok(json!(stock.clone()))
+}
+
+#[tauri::command]
+pub async fn delete_auction_stock(
+    id: i64,
+    db: tauri::State<'_, Arc<Mutex<DBClient>>>,
+    wfm: tauri::State<'_, Arc<Mutex<WFMClient>>>,
+) -> Result<serde_json::Value, AppError> {
+    let db = db.lock()?.clone();
+    let wfm = wfm.lock()?.clone();
+
+    let stock = db.stock_auction().delete(id).await?;
+    let json_stock = serde_json::to_value(&stock).unwrap();
+
+    // Delete Auction from Warframe Market
+    if let Some(order_id) = stock.order_id {
+        match wfm.auction().delete(order_id.as_str()).await {
+            Ok(_) => {}
+            Err(e) => {
+                error::create_log_file(LOG_FILE.lock().unwrap().to_owned(), &e);
+                logger::info_con(
+                    "CommandStock",
+                    format!(
+                        "Error deleting Auction from Warframe Market: {:?}",
+                        order_id
+                    )
+                    .as_str(),
+                );
+            }
+        };
+    }
+
+    Ok(json_stock)
+}
+
+#[tauri::command]
+pub async fn sell_auction_stock(
+    id: i64,
+    price: i32,
+    db: tauri::State<'_, Arc<Mutex<DBClient>>>,
+    wfm: tauri::State<'_, Arc<Mutex<WFMClient>>>,
+) -> Result<serde_json::Value, AppError> {
+    let db_state = db.lock()?.clone();
+    let stock = match db_state.stock_auction().get_by_id(id).await? {
+        Some(stock) => stock,
+        None => return Err(AppError::new("Command", eyre!("Auction {} not found", id))),
+    };
+
+    let json_stock = delete_auction_stock(id, db, wfm).await?;
+
+    // Create Transaction
+    db_state
+        .transaction()
+        .create(
+            &stock.weapon_url,
+            &stock.auction_type,
+            "sell",
+            1,
+            price,
+            0,
             Some(json!({
-                "type": "riven",
+                "type": stock.auction_type,
                 "weapon_url_name": stock.weapon_url,
-                "re_rolls": stock.re_rolls,
-                "polarity": stock.polarity,
-                "name": stock.mod_name,
-                "mod_rank": stock.rank,
-                "mastery_level": stock.mastery_rank,
-                "attributes": stock.attributes,
+                "name": stock.name,
+                "element": stock.element,
+                "damage": stock.damage,
+                "having_ephemera": stock.having_ephemera,
+                "quirk": stock.quirk,
             })),
         )
         .await
@@ -589,4 +782,94 @@ pub async fn sell_riven_stock(
     Ok(json_stock)
 }
 
-// -----------------------------------------------------------------------------------------------
+// Set Stock Commands
+#[tauri::command]
+pub async fn delete_set_stock(
+    id: i64,
+    db: tauri::State<'_, Arc<Mutex<DBClient>>>,
+    wfm: tauri::State<'_, Arc<Mutex<WFMClient>>>,
+) -> Result<serde_json::Value, AppError> {
+    let db = db.lock()?.clone();
+    let wfm = wfm.lock()?.clone();
+
+    let stock = db.stock_set().delete(id).await?;
+
+    // Delete the linked orders from Warframe Market
+    let mut order_ids: Vec<String> = stock
+        .parts
+        .0
+        .iter()
+        .filter_map(|p| p.order_id.clone())
+        .collect();
+    if stock.set_order_id.is_some() {
+        order_ids.push(stock.set_order_id.clone().unwrap());
+    }
+    for order_id in order_ids {
+        match wfm.orders().delete(&order_id).await {
+            Ok(_) => {}
+            Err(e) => {
+                error::create_log_file(LOG_FILE.lock().unwrap().to_owned(), &e);
+                logger::info_con(
+                    "CommandStock",
+                    format!(
+                        "Error deleting Set order from Warframe Market: {:?}",
+                        order_id
+                    )
+                    .as_str(),
+                );
+            }
+        };
+    }
+    Ok(json!(stock))
+}
+
+#[tauri::command]
+pub async fn sell_set_stock(
+    id: i64,
+    price: i32,
+    db: tauri::State<'_, Arc<Mutex<DBClient>>>,
+    wfm: tauri::State<'_, Arc<Mutex<WFMClient>>>,
+) -> Result<serde_json::Value, AppError> {
+    let db_state = db.lock()?.clone();
+    let stock = match db_state.stock_set().get_by_id(id).await? {
+        Some(stock) => stock,
+        None => return Err(AppError::new("Command", eyre!("Set {} not found", id))),
+    };
+
+    // The parts were combined into the set, remove them from the stock
+    for part in stock.parts.0.iter() {
+        let stock_item = db_state
+            .stock_item()
+            .get_item_by_url_name(&part.url)
+            .await?;
+        if stock_item.is_none() {
+            continue;
+        }
+        let stock_item = stock_item.unwrap();
+        let invantory = db_state
+            .stock_item()
+            .sell_item(stock_item.id, part.quantity as i32)
+            .await?;
+        if invantory.owned <= 0 {
+            db_state
+                .stock_item()
+                .emit("DELETE", json!(invantory.clone()));
+        } else {
+            db_state
+                .stock_item()
+                .emit("CREATE_OR_UPDATE", json!(invantory.clone()));
+        }
+    }
+
+    let json_stock = delete_set_stock(id, db, wfm).await?;
+
+    // Create Transaction
+    db_state
+        .transaction()
+        .create(&stock.url, "item", "sell", 1, price, 0, None)
+        .await
+        .inspect_err(|e| {
+            error::create_log_file(LOG_FILE.lock().unwrap().to_owned(), e);
+        })?;
+    Ok(json_stock)
+}
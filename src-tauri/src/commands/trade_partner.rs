@@ -0,0 +1,49 @@
+use std::sync::{Arc, Mutex};
+
+use once_cell::sync::Lazy;
+use serde_json::json;
+
+use crate::{
+    database::client::DBClient,
+    error::{self, AppError},
+};
+
+// Create a static variable to store the log file name
+static LOG_FILE: Lazy<Mutex<String>> =
+    Lazy::new(|| Mutex::new("command_trade_partner.log".to_string()));
+
+#[tauri::command]
+pub async fn get_trade_partners(
+    db: tauri::State<'_, Arc<Mutex<DBClient>>>,
+) -> Result<serde_json::Value, AppError> {
+    let db = db.lock()?.clone();
+    match db.trade_partner().get_items().await {
+        Ok(partners) => Ok(json!(partners)),
+        Err(e) => {
+            error::create_log_file(LOG_FILE.lock().unwrap().to_owned(), &e);
+            Err(e)
+        }
+    }
+}
+
+// Sets the notes and the blocklist flag of a player, the player is added when not known yet.
+#[tauri::command]
+pub async fn update_trade_partner(
+    user_name: String,
+    notes: Option<String>,
+    blocked: Option<bool>,
+    db: tauri::State<'_, Arc<Mutex<DBClient>>>,
+) -> Result<serde_json::Value, AppError> {
+    let db = db.lock()?.clone();
+    match db
+        .trade_partner()
+        .update_by_name(&user_name, notes, blocked)
+        .await
+    {
+        Ok(partner) => Ok(json!(partner)),
+        Err(e) => {
+            error::create_log_file(LOG_FILE.lock().unwrap().to_owned(), &e);
+            Err(e)
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use std::sync::{Arc, Mutex};
+
+use once_cell::sync::Lazy;
+use serde_json::json;
+
+use crate::{
+    database::{client::DBClient, modules::watchlist::WatchRuleStruct},
+    error::{self, AppError},
+};
+
+// Create a static variable to store the log file name
+static LOG_FILE: Lazy<Mutex<String>> =
+    Lazy::new(|| Mutex::new("command_watchlist.log".to_string()));
+
+#[tauri::command]
+pub async fn create_watchlist_item(
+    url_name: String,
+    mod_rank: Option<i64>,
+    rules: Vec<WatchRuleStruct>,
+    db: tauri::State<'_, Arc<Mutex<DBClient>>>,
+) -> Result<serde_json::Value, AppError> {
+    let db = db.lock()?.clone();
+    match db.watchlist().create(&url_name, mod_rank, rules).await {
+        Ok(watch) => Ok(json!(watch)),
+        Err(e) => {
+            error::create_log_file(LOG_FILE.lock().unwrap().to_owned(), &e);
+            Err(e)
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn update_watchlist_item(
+    id: i64,
+    mod_rank: Option<i64>,
+    rules: Option<Vec<WatchRuleStruct>>,
+    db: tauri::State<'_, Arc<Mutex<DBClient>>>,
+) -> Result<serde_json::Value, AppError> {
+    let db = db.lock()?.clone();
+    match db.watchlist().update_by_id(id, mod_rank, rules).await {
+        Ok(watch) => Ok(json!(watch)),
+        Err(e) => {
+            error::create_log_file(LOG_FILE.lock().unwrap().to_owned(), &e);
+            Err(e)
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn delete_watchlist_item(
+    id: i64,
+    db: tauri::State<'_, Arc<Mutex<DBClient>>>,
+) -> Result<serde_json::Value, AppError> {
+    let db = db.lock()?.clone();
+    match db.watchlist().delete(id).await {
+        Ok(watch) => Ok(json!(watch)),
+        Err(e) => {
+            error::create_log_file(LOG_FILE.lock().unwrap().to_owned(), &e);
+            Err(e)
+        }
+    }
+}
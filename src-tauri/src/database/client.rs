@@ -19,7 +19,12 @@ use crate::{
     wfm_client::client::WFMClient,
 };
 
-use super::modules::{ transaction::TransactionModule, stock_item::StockItemModule, stock_riven::StockRivenModule};
+use super::modules::{
+    log_event::LogEventModule, price_history::PriceHistoryModule,
+    stock_auction::StockAuctionModule, stock_item::StockItemModule, stock_riven::StockRivenModule,
+    stock_set::StockSetModule, trade_partner::TradePartnerModule, transaction::TransactionModule,
+    watchlist::WatchlistModule,
+};
 #[derive(Clone, Debug)]
 pub struct DBClient {
     pub log_file: String,
@@ -61,12 +66,28 @@ impl DBClient {
     pub async fn initialize(&self) -> Result<bool, AppError> {
         self.stock_item().initialize().await?;
         self.stock_riven().initialize().await?;
+        self.stock_auction().initialize().await?;
+        self.stock_set().initialize().await?;
         self.transaction().initialize().await?;
+        self.price_history().initialize().await?;
+        self.watchlist().initialize().await?;
+        self.log_event().initialize().await?;
+        self.trade_partner().initialize().await?;
         Ok(true)
     }
     pub fn get_connection(&self) -> Arc<Mutex<Pool<Sqlite>>> {
         self.connection.clone()
     }
+    // Writes a consistent copy of the database to `path`, the file must not exist yet.
+    pub async fn backup(&self, path: &std::path::Path) -> Result<(), AppError> {
+        let connection = self.connection.lock().unwrap().clone();
+        sqlx::query("VACUUM INTO ?")
+            .bind(path.to_string_lossy().to_string())
+            .execute(&connection)
+            .await
+            .map_err(|e| AppError::new("Database", eyre!(e.to_string())))?;
+        Ok(())
+    }
 
     pub fn transaction(&self) -> TransactionModule {
         TransactionModule { client: self }
@@ -79,4 +100,28 @@ impl DBClient {
     pub fn stock_riven(&self) -> StockRivenModule {
         StockRivenModule { client: self }
     }
+
+    pub fn stock_auction(&self) -> StockAuctionModule<'_> {
+        StockAuctionModule { client: self }
+    }
+
+    pub fn stock_set(&self) -> StockSetModule<'_> {
+        StockSetModule { client: self }
+    }
+
+    pub fn price_history(&self) -> PriceHistoryModule<'_> {
+        PriceHistoryModule { client: self }
+    }
+
+    pub fn watchlist(&self) -> WatchlistModule<'_> {
+        WatchlistModule { client: self }
+    }
+
+    pub fn log_event(&self) -> LogEventModule<'_> {
+        LogEventModule { client: self }
+    }
+
+    pub fn trade_partner(&self) -> TradePartnerModule<'_> {
+        TradePartnerModule { client: self }
+    }
 }
@@ -0,0 +1,132 @@
+use crate::{database::client::DBClient, error::AppError, helper};
+use eyre::eyre;
+use sea_query::{ColumnDef, Iden, InsertStatement, Query, SqliteQueryBuilder, Table};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Iden)]
+pub enum LogEvent {
+    Table,
+    Id,
+    Rule,
+    Line,
+    Captures,
+    Created,
+}
+
+// A line of EE.log matched by a user defined log rule with the database action.
+#[derive(sqlx::FromRow, Serialize, Deserialize, Clone, Debug)]
+#[allow(dead_code)]
+pub struct LogEventStruct {
+    pub id: i64,
+    pub rule: String,
+    pub line: String,
+    pub captures: sqlx::types::Json<serde_json::Value>,
+    pub created: String,
+}
+
+pub struct LogEventModule<'a> {
+    pub client: &'a DBClient,
+}
+
+impl<'a> LogEventModule<'a> {
+    // Methods sea-query
+
+    // Initialize the database
+    pub async fn initialize(&self) -> Result<bool, AppError> {
+        let connection = self.client.connection.lock().unwrap().clone();
+        let sql = Table::create()
+            .table(LogEvent::Table)
+            .if_not_exists()
+            .col(
+                ColumnDef::new(LogEvent::Id)
+                    .integer()
+                    .not_null()
+                    .auto_increment()
+                    .primary_key(),
+            )
+            .col(ColumnDef::new(LogEvent::Rule).string().not_null())
+            .col(ColumnDef::new(LogEvent::Line).string().not_null())
+            .col(
+                ColumnDef::new(LogEvent::Captures)
+                    .json()
+                    .not_null()
+                    .default(json!({})),
+            )
+            .col(ColumnDef::new(LogEvent::Created).date_time().not_null())
+            .build(SqliteQueryBuilder);
+
+        sqlx::query(&sql)
+            .execute(&connection)
+            .await
+            .map_err(|e| AppError::new("Database", eyre!(e.to_string())))?;
+        Ok(true)
+    }
+
+    pub async fn get_items(&self) -> Result<Vec<LogEventStruct>, AppError> {
+        let connection = self.client.connection.lock().unwrap().clone();
+        // Read
+        let sql = Query::select()
+            .columns([
+                LogEvent::Id,
+                LogEvent::Rule,
+                LogEvent::Line,
+                LogEvent::Captures,
+                LogEvent::Created,
+            ])
+            .from(LogEvent::Table)
+            .to_string(SqliteQueryBuilder);
+        let rows = sqlx::query_as::<_, LogEventStruct>(&sql)
+            .fetch_all(&connection)
+            .await
+            .map_err(|e| AppError::new("Database", eyre!(e.to_string())))?;
+        Ok(rows)
+    }
+
+    pub async fn create(
+        &self,
+        rule: &str,
+        line: &str,
+        captures: serde_json::Value,
+    ) -> Result<LogEventStruct, AppError> {
+        let connection = self.client.connection.lock().unwrap().clone();
+        let mut event = LogEventStruct {
+            id: 0,
+            rule: rule.to_string(),
+            line: line.to_string(),
+            captures: sqlx::types::Json(captures),
+            created: chrono::Local::now().naive_local().to_string(),
+        };
+
+        let sql = InsertStatement::default()
+            .into_table(LogEvent::Table)
+            .columns([
+                LogEvent::Rule,
+                LogEvent::Line,
+                LogEvent::Captures,
+                LogEvent::Created,
+            ])
+            .values_panic([
+                event.rule.clone().into(),
+                event.line.clone().into(),
+                event.captures.0.clone().into(),
+                event.created.clone().into(),
+            ])
+            .to_string(SqliteQueryBuilder);
+        let row = sqlx::query(&sql.replace("\\", ""))
+            .execute(&connection)
+            .await
+            .map_err(|e| AppError::new("Database", eyre!(e.to_string())))?;
+        event.id = row.last_insert_rowid();
+
+        self.emit(
+            "CREATE_OR_UPDATE",
+            serde_json::to_value(event.clone()).unwrap(),
+        );
+        Ok(event)
+    }
+
+    pub fn emit(&self, operation: &str, data: serde_json::Value) {
+        helper::emit_update("LogEvent", operation, Some(data));
+    }
+}
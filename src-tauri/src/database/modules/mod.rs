@@ -1,3 +1,9 @@
+pub mod log_event;
+pub mod price_history;
+pub mod stock_auction;
 pub mod stock_item;
 pub mod stock_riven;
+pub mod stock_set;
+pub mod trade_partner;
 pub mod transaction;
+pub mod watchlist;
@@ -0,0 +1,465 @@
+use crate::{database::client::DBClient, error::AppError, helper};
+use eyre::eyre;
+use polars::prelude::*;
+use sea_query::{
+    ColumnDef, Expr, Func, Iden, Index, InsertStatement, OnConflict, Order, Query,
+    SqliteQueryBuilder, Table,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+
+#[derive(Iden)]
+pub enum PriceHistory {
+    Table,
+    Day,
+    Name,
+    ItemId,
+    OrderType,
+    ModRank,
+    SubType,
+    Datetime,
+    Volume,
+    MinPrice,
+    MaxPrice,
+    AvgPrice,
+    Median,
+    Range,
+    Updated,
+    Source,
+}
+
+// One row of the daily price statistics from relics.run or warframe.market.
+// Items without ranks are stored with a mod_rank of -1 and items without subtypes with an empty sub_type,
+// so the (day, name, order_type, mod_rank, sub_type) key stays unique.
+// `source` is "relics_run" or "warframe_market", only relics.run days hold every item.
+#[derive(sqlx::FromRow, Serialize, Deserialize, Clone, Debug)]
+#[allow(dead_code)]
+pub struct PriceHistoryStruct {
+    pub day: String,
+    pub name: String,
+    pub item_id: String,
+    pub order_type: String,
+    pub mod_rank: i64,
+    pub sub_type: String,
+    pub datetime: String,
+    pub volume: i64,
+    pub min_price: f64,
+    pub max_price: f64,
+    pub avg_price: f64,
+    pub median: f64,
+    pub range: f64,
+    pub updated: i64,
+    pub source: String,
+}
+
+pub struct PriceHistoryModule<'a> {
+    pub client: &'a DBClient,
+}
+
+impl<'a> PriceHistoryModule<'a> {
+    // Methods sea-query
+
+    // Initialize the database
+    pub async fn initialize(&self) -> Result<bool, AppError> {
+        let connection = self.client.connection.lock().unwrap().clone();
+        let sql = Table::create()
+            .table(PriceHistory::Table)
+            .if_not_exists()
+            .col(ColumnDef::new(PriceHistory::Day).string().not_null())
+            .col(ColumnDef::new(PriceHistory::Name).string().not_null())
+            .col(ColumnDef::new(PriceHistory::ItemId).string().not_null())
+            .col(ColumnDef::new(PriceHistory::OrderType).string().not_null())
+            .col(
+                ColumnDef::new(PriceHistory::ModRank)
+                    .integer()
+                    .not_null()
+                    .default(-1),
+            )
+            .col(
+                ColumnDef::new(PriceHistory::SubType)
+                    .string()
+                    .not_null()
+                    .default(""),
+            )
+            .col(ColumnDef::new(PriceHistory::Datetime).string().not_null())
+            .col(
+                ColumnDef::new(PriceHistory::Volume)
+                    .integer()
+                    .not_null()
+                    .default(0),
+            )
+            .col(
+                ColumnDef::new(PriceHistory::MinPrice)
+                    .float()
+                    .not_null()
+                    .default(0),
+            )
+            .col(
+                ColumnDef::new(PriceHistory::MaxPrice)
+                    .float()
+                    .not_null()
+                    .default(0),
+            )
+            .col(
+                ColumnDef::new(PriceHistory::AvgPrice)
+                    .float()
+                    .not_null()
+                    .default(0),
+            )
+            .col(
+                ColumnDef::new(PriceHistory::Median)
+                    .float()
+                    .not_null()
+                    .default(0),
+            )
+            .col(
+                ColumnDef::new(PriceHistory::Range)
+                    .float()
+                    .not_null()
+                    .default(0),
+            )
+            .col(
+                ColumnDef::new(PriceHistory::Updated)
+                    .big_integer()
+                    .not_null()
+                    .default(0),
+            )
+            .col(
+                ColumnDef::new(PriceHistory::Source)
+                    .string()
+                    .not_null()
+                    .default("relics_run"),
+            )
+            .build(SqliteQueryBuilder);
+        sqlx::query(&sql)
+            .execute(&connection)
+            .await
+            .map_err(|e| AppError::new("Database", eyre!(e.to_string())))?;
+
+        // Add the sub type column to tables created before it existed
+        let alter_sql = Table::alter()
+            .table(PriceHistory::Table)
+            .add_column(
+                ColumnDef::new(PriceHistory::SubType)
+                    .string()
+                    .not_null()
+                    .default(""),
+            )
+            .to_string(SqliteQueryBuilder);
+        helper::alter_table(connection.clone(), &alter_sql).await?;
+
+        // Add the source column to tables created before it existed
+        let alter_sql = Table::alter()
+            .table(PriceHistory::Table)
+            .add_column(
+                ColumnDef::new(PriceHistory::Source)
+                    .string()
+                    .not_null()
+                    .default("relics_run"),
+            )
+            .to_string(SqliteQueryBuilder);
+        helper::alter_table(connection.clone(), &alter_sql).await?;
+
+        // The old key did not include the sub type
+        let sql = Index::drop()
+            .if_exists()
+            .name("idx_price_history_key")
+            .table(PriceHistory::Table)
+            .to_string(SqliteQueryBuilder);
+        sqlx::query(&sql)
+            .execute(&connection)
+            .await
+            .map_err(|e| AppError::new("Database", eyre!(e.to_string())))?;
+
+        // The key of a row, also used by the window queries that filter on day first
+        let sql = Index::create()
+            .if_not_exists()
+            .name("idx_price_history_row")
+            .table(PriceHistory::Table)
+            .col(PriceHistory::Day)
+            .col(PriceHistory::Name)
+            .col(PriceHistory::OrderType)
+            .col(PriceHistory::ModRank)
+            .col(PriceHistory::SubType)
+            .unique()
+            .build(SqliteQueryBuilder);
+        sqlx::query(&sql)
+            .execute(&connection)
+            .await
+            .map_err(|e| AppError::new("Database", eyre!(e.to_string())))?;
+
+        // Lookups of a single item over a window
+        let sql = Index::create()
+            .if_not_exists()
+            .name("idx_price_history_name")
+            .table(PriceHistory::Table)
+            .col(PriceHistory::Name)
+            .col(PriceHistory::Day)
+            .build(SqliteQueryBuilder);
+        sqlx::query(&sql)
+            .execute(&connection)
+            .await
+            .map_err(|e| AppError::new("Database", eyre!(e.to_string())))?;
+        Ok(true)
+    }
+
+    // Returns all days that are stored, newest first.
+    // With a source only the days that have rows from that source are returned.
+    pub async fn get_days(&self, source: Option<&str>) -> Result<Vec<String>, AppError> {
+        let connection = self.client.connection.lock().unwrap().clone();
+        let mut query = Query::select();
+        query
+            .distinct()
+            .column(PriceHistory::Day)
+            .from(PriceHistory::Table)
+            .order_by(PriceHistory::Day, Order::Desc);
+        if let Some(source) = source {
+            query.and_where(Expr::col(PriceHistory::Source).eq(source));
+        }
+        let sql = query.to_string(SqliteQueryBuilder);
+        let rows = sqlx::query(&sql)
+            .fetch_all(&connection)
+            .await
+            .map_err(|e| AppError::new("Database", eyre!(e.to_string())))?;
+        Ok(rows.iter().map(|r| r.get::<String, _>(0)).collect())
+    }
+
+    // Returns the unix time in milliseconds of the last insert, None when the table is empty.
+    pub async fn get_last_update(&self) -> Result<Option<i64>, AppError> {
+        let connection = self.client.connection.lock().unwrap().clone();
+        let sql = Query::select()
+            .expr(Func::max(Expr::col(PriceHistory::Updated)))
+            .from(PriceHistory::Table)
+            .to_string(SqliteQueryBuilder);
+        let row = sqlx::query(&sql)
+            .fetch_one(&connection)
+            .await
+            .map_err(|e| AppError::new("Database", eyre!(e.to_string())))?;
+        Ok(row.get::<Option<i64>, _>(0))
+    }
+
+    // Returns the rows of the given window, optionally limited to a list of url names.
+    // `from_day` and `to_day` are inclusive and in the format "YYYY-MM-DD".
+    pub async fn get_window(
+        &self,
+        from_day: &str,
+        to_day: &str,
+        names: Option<Vec<String>>,
+    ) -> Result<Vec<PriceHistoryStruct>, AppError> {
+        let connection = self.client.connection.lock().unwrap().clone();
+        let mut query = Query::select();
+        query
+            .columns([
+                PriceHistory::Day,
+                PriceHistory::Name,
+                PriceHistory::ItemId,
+                PriceHistory::OrderType,
+                PriceHistory::ModRank,
+                PriceHistory::SubType,
+                PriceHistory::Datetime,
+                PriceHistory::Volume,
+                PriceHistory::MinPrice,
+                PriceHistory::MaxPrice,
+                PriceHistory::AvgPrice,
+                PriceHistory::Median,
+                PriceHistory::Range,
+                PriceHistory::Updated,
+                PriceHistory::Source,
+            ])
+            .from(PriceHistory::Table)
+            .and_where(Expr::col(PriceHistory::Day).gte(from_day))
+            .and_where(Expr::col(PriceHistory::Day).lte(to_day))
+            .order_by(PriceHistory::Name, Order::Asc)
+            .order_by(PriceHistory::Day, Order::Asc);
+        if let Some(names) = names {
+            query.and_where(Expr::col(PriceHistory::Name).is_in(names));
+        }
+        let sql = query.to_string(SqliteQueryBuilder);
+        let rows = sqlx::query_as::<_, PriceHistoryStruct>(&sql)
+            .fetch_all(&connection)
+            .await
+            .map_err(|e| AppError::new("Database", eyre!(e.to_string())))?;
+        Ok(rows)
+    }
+
+    // Same as `get_window` but returns the rows in the layout of the old price_data.csv,
+    // mod_rank is null for items without ranks and sub_type is empty for items without subtypes.
+    // The extra "source" column tells the relics.run rows from the warframe.market rows.
+    pub async fn get_window_df(
+        &self,
+        from_day: &str,
+        to_day: &str,
+        names: Option<Vec<String>>,
+    ) -> Result<DataFrame, AppError> {
+        let rows = self.get_window(from_day, to_day, names).await?;
+        DataFrame::new(vec![
+            Series::new(
+                "name",
+                rows.iter().map(|r| r.name.clone()).collect::<Vec<_>>(),
+            ),
+            Series::new(
+                "datetime",
+                rows.iter().map(|r| r.datetime.clone()).collect::<Vec<_>>(),
+            ),
+            Series::new(
+                "order_type",
+                rows.iter()
+                    .map(|r| r.order_type.clone())
+                    .collect::<Vec<_>>(),
+            ),
+            Series::new("volume", rows.iter().map(|r| r.volume).collect::<Vec<_>>()),
+            Series::new(
+                "min_price",
+                rows.iter().map(|r| r.min_price).collect::<Vec<_>>(),
+            ),
+            Series::new(
+                "max_price",
+                rows.iter().map(|r| r.max_price).collect::<Vec<_>>(),
+            ),
+            Series::new(
+                "avg_price",
+                rows.iter().map(|r| r.avg_price).collect::<Vec<_>>(),
+            ),
+            Series::new(
+                "mod_rank",
+                rows.iter()
+                    .map(|r| {
+                        if r.mod_rank < 0 {
+                            None
+                        } else {
+                            Some(r.mod_rank as f64)
+                        }
+                    })
+                    .collect::<Vec<_>>(),
+            ),
+            Series::new(
+                "sub_type",
+                rows.iter().map(|r| r.sub_type.clone()).collect::<Vec<_>>(),
+            ),
+            Series::new("median", rows.iter().map(|r| r.median).collect::<Vec<_>>()),
+            Series::new(
+                "item_id",
+                rows.iter().map(|r| r.item_id.clone()).collect::<Vec<_>>(),
+            ),
+            Series::new("range", rows.iter().map(|r| r.range).collect::<Vec<_>>()),
+            Series::new(
+                "source",
+                rows.iter().map(|r| r.source.clone()).collect::<Vec<_>>(),
+            ),
+        ])
+        .map_err(|e| AppError::new("Database", eyre!(e.to_string())))
+    }
+
+    // Inserts or replaces the rows in one transaction, returns the number of rows written.
+    pub async fn upsert(&self, rows: Vec<PriceHistoryStruct>) -> Result<i64, AppError> {
+        let connection = self.client.connection.lock().unwrap().clone();
+        let mut tx = connection
+            .begin()
+            .await
+            .map_err(|e| AppError::new("Database", eyre!(e.to_string())))?;
+        let updated = chrono::Local::now().timestamp_millis();
+
+        // Keep the statements well below the SQLite variable limit
+        for chunk in rows.chunks(500) {
+            let mut insert = InsertStatement::default();
+            insert
+                .into_table(PriceHistory::Table)
+                .columns([
+                    PriceHistory::Day,
+                    PriceHistory::Name,
+                    PriceHistory::ItemId,
+                    PriceHistory::OrderType,
+                    PriceHistory::ModRank,
+                    PriceHistory::SubType,
+                    PriceHistory::Datetime,
+                    PriceHistory::Volume,
+                    PriceHistory::MinPrice,
+                    PriceHistory::MaxPrice,
+                    PriceHistory::AvgPrice,
+                    PriceHistory::Median,
+                    PriceHistory::Range,
+                    PriceHistory::Updated,
+                    PriceHistory::Source,
+                ])
+                .on_conflict(
+                    OnConflict::columns([
+                        PriceHistory::Day,
+                        PriceHistory::Name,
+                        PriceHistory::OrderType,
+                        PriceHistory::ModRank,
+                        PriceHistory::SubType,
+                    ])
+                    .update_columns([
+                        PriceHistory::ItemId,
+                        PriceHistory::Datetime,
+                        PriceHistory::Volume,
+                        PriceHistory::MinPrice,
+                        PriceHistory::MaxPrice,
+                        PriceHistory::AvgPrice,
+                        PriceHistory::Median,
+                        PriceHistory::Range,
+                        PriceHistory::Updated,
+                        PriceHistory::Source,
+                    ])
+                    .to_owned(),
+                );
+            for row in chunk {
+                insert.values_panic([
+                    row.day.clone().into(),
+                    row.name.clone().into(),
+                    row.item_id.clone().into(),
+                    row.order_type.clone().into(),
+                    row.mod_rank.into(),
+                    row.sub_type.clone().into(),
+                    row.datetime.clone().into(),
+                    row.volume.into(),
+                    row.min_price.into(),
+                    row.max_price.into(),
+                    row.avg_price.into(),
+                    row.median.into(),
+                    row.range.into(),
+                    updated.into(),
+                    row.source.clone().into(),
+                ]);
+            }
+            let sql = insert.to_string(SqliteQueryBuilder);
+            sqlx::query(&sql.replace("\\", ""))
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| AppError::new("Database", eyre!(e.to_string())))?;
+        }
+        tx.commit()
+            .await
+            .map_err(|e| AppError::new("Database", eyre!(e.to_string())))?;
+        Ok(rows.len() as i64)
+    }
+
+    // Removes the rows of a day that came from the given source, returns the number of rows removed.
+    pub async fn delete_day(&self, day: &str, source: &str) -> Result<u64, AppError> {
+        let connection = self.client.connection.lock().unwrap().clone();
+        let sql = Query::delete()
+            .from_table(PriceHistory::Table)
+            .and_where(Expr::col(PriceHistory::Day).eq(day))
+            .and_where(Expr::col(PriceHistory::Source).eq(source))
+            .to_string(SqliteQueryBuilder);
+        let result = sqlx::query(&sql)
+            .execute(&connection)
+            .await
+            .map_err(|e| AppError::new("Database", eyre!(e.to_string())))?;
+        Ok(result.rows_affected())
+    }
+
+    // Removes every day before `day`, returns the number of rows removed.
+    pub async fn delete_before(&self, day: &str) -> Result<u64, AppError> {
+        let connection = self.client.connection.lock().unwrap().clone();
+        let sql = Query::delete()
+            .from_table(PriceHistory::Table)
+            .and_where(Expr::col(PriceHistory::Day).lt(day))
+            .to_string(SqliteQueryBuilder);
+        let result = sqlx::query(&sql)
+            .execute(&connection)
+            .await
+            .map_err(|e| AppError::new("Database", eyre!(e.to_string())))?;
+        Ok(result.rows_affected())
+    }
+}
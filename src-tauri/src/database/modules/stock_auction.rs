@@ -0,0 +1,478 @@
+use crate::{
+    database::client::DBClient, enums::LogLevel, error::AppError, helper, structs::Auction,
+};
+use eyre::eyre;
+use sea_query::{ColumnDef, Expr, Iden, InsertStatement, Query, SqliteQueryBuilder, Table, Value};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use super::stock_riven::MinMaxStruct;
+
+#[derive(Iden)]
+pub enum StockAuction {
+    Table,
+    Id,
+    OrderId,
+    AuctionType,
+    WeaponId,
+    WeaponUrl,
+    WeaponName,
+    Name,
+    Element,
+    Damage,
+    HavingEphemera,
+    Quirk,
+    MatchAuction,
+    Price,
+    MiniumPrice,
+    ListedPrice,
+    Private,
+    Status,
+    Created,
+}
+
+#[derive(sqlx::FromRow, Serialize, Deserialize, Clone, Debug)]
+#[allow(dead_code)]
+pub struct StockAuctionStruct {
+    pub id: i64,
+    pub order_id: Option<String>,
+    pub auction_type: String,
+    pub weapon_id: String,
+    pub weapon_url: String,
+    pub weapon_name: String,
+    pub name: Option<String>,
+    pub element: String,
+    pub damage: i32,
+    pub having_ephemera: bool,
+    pub quirk: Option<String>,
+    pub match_auction: sqlx::types::Json<MatchAuctionStruct>,
+    pub price: f64,
+    pub minium_price: Option<i32>,
+    pub listed_price: Option<i32>,
+    pub private: bool,
+    pub status: String,
+    pub created: String,
+}
+
+// What a live auction has to match to be compared against a stocked weapon.
+// A field left as None is not used in the search.
+#[derive(sqlx::FromRow, Serialize, Deserialize, Clone, Debug)]
+#[allow(dead_code)]
+pub struct MatchAuctionStruct {
+    pub element: Option<String>,
+    pub damage: Option<MinMaxStruct>,
+    pub having_ephemera: Option<bool>,
+}
+
+pub struct StockAuctionModule<'a> {
+    pub client: &'a DBClient,
+}
+
+impl<'a> StockAuctionModule<'a> {
+    // Methods sea-query
+
+    // Initialize the database
+    pub async fn initialize(&self) -> Result<bool, AppError> {
+        let connection = self.client.connection.lock().unwrap().clone();
+        let sql = Table::create()
+            .table(StockAuction::Table)
+            .if_not_exists()
+            .col(
+                ColumnDef::new(StockAuction::Id)
+                    .integer()
+                    .not_null()
+                    .auto_increment()
+                    .primary_key(),
+            )
+            .col(ColumnDef::new(StockAuction::OrderId).uuid())
+            .col(
+                ColumnDef::new(StockAuction::AuctionType)
+                    .string()
+                    .not_null(),
+            )
+            .col(ColumnDef::new(StockAuction::WeaponId).uuid().not_null())
+            .col(ColumnDef::new(StockAuction::WeaponUrl).string().not_null())
+            .col(ColumnDef::new(StockAuction::WeaponName).string().not_null())
+            .col(ColumnDef::new(StockAuction::Name).string())
+            .col(ColumnDef::new(StockAuction::Element).string().not_null())
+            .col(
+                ColumnDef::new(StockAuction::Damage)
+                    .integer()
+                    .not_null()
+                    .default(Value::Int(Some(0))),
+            )
+            .col(
+                ColumnDef::new(StockAuction::HavingEphemera)
+                    .boolean()
+                    .not_null()
+                    .default(Value::Bool(Some(false))),
+            )
+            .col(ColumnDef::new(StockAuction::Quirk).string())
+            .col(
+                ColumnDef::new(StockAuction::MatchAuction)
+                    .json()
+                    .not_null()
+                    .default(json!({})),
+            )
+            .col(
+                ColumnDef::new(StockAuction::Price)
+                    .float()
+                    .not_null()
+                    .default(Value::Int(Some(0))),
+            )
+            .col(
+                ColumnDef::new(StockAuction::MiniumPrice)
+                    .integer()
+                    .default(Value::Int(None)),
+            )
+            .col(
+                ColumnDef::new(StockAuction::ListedPrice)
+                    .integer()
+                    .default(Value::Int(None)),
+            )
+            .col(
+                ColumnDef::new(StockAuction::Private)
+                    .boolean()
+                    .default(Value::Bool(Some(false))),
+            )
+            .col(
+                ColumnDef::new(StockAuction::Status)
+                    .string()
+                    .not_null()
+                    .default("pending"),
+            )
+            .col(ColumnDef::new(StockAuction::Created).date_time().not_null())
+            .build(SqliteQueryBuilder);
+
+        sqlx::query(&sql)
+            .execute(&connection)
+            .await
+            .map_err(|e| AppError::new("Database", eyre!(e.to_string())))?;
+        Ok(true)
+    }
+
+    pub async fn get_auctions(&self) -> Result<Vec<StockAuctionStruct>, AppError> {
+        let connection = self.client.connection.lock().unwrap().clone();
+        // Read
+        let sql = Query::select()
+            .columns([
+                StockAuction::Id,
+                StockAuction::OrderId,
+                StockAuction::AuctionType,
+                StockAuction::WeaponId,
+                StockAuction::WeaponUrl,
+                StockAuction::WeaponName,
+                StockAuction::Name,
+                StockAuction::Element,
+                StockAuction::Damage,
+                StockAuction::HavingEphemera,
+                StockAuction::Quirk,
+                StockAuction::MatchAuction,
+                StockAuction::Price,
+                StockAuction::MiniumPrice,
+                StockAuction::ListedPrice,
+                StockAuction::Private,
+                StockAuction::Status,
+                StockAuction::Created,
+            ])
+            .from(StockAuction::Table)
+            .to_string(SqliteQueryBuilder);
+        let rows = sqlx::query_as::<_, StockAuctionStruct>(&sql)
+            .fetch_all(&connection)
+            .await
+            .map_err(|e| AppError::new("Database", eyre!(e.to_string())))?;
+        Ok(rows)
+    }
+    pub async fn get_by_id(&self, id: i64) -> Result<Option<StockAuctionStruct>, AppError> {
+        let stock = self.get_auctions().await?;
+        let stock_auction = stock.iter().find(|t| t.id == id);
+        Ok(stock_auction.cloned())
+    }
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create(
+        &self,
+        order_id: Option<String>,
+        auction_type: &str,
+        url_name: &str,
+        name: Option<String>,
+        element: &str,
+        damage: i32,
+        having_ephemera: bool,
+        quirk: Option<String>,
+        match_auction: Option<MatchAuctionStruct>,
+        price: f64,
+        minium_price: Option<i32>,
+    ) -> Result<StockAuctionStruct, AppError> {
+        let connection = self.client.connection.lock().unwrap().clone();
+        let cache = self.client.cache.lock().unwrap().clone();
+
+        let weapon = match cache.auction().find_weapon(auction_type, url_name)? {
+            Some(weapon) => weapon,
+            None => {
+                return Err(AppError::new_with_level(
+                    "Database",
+                    eyre!(
+                        "Could not find {} weapon in cache: {}",
+                        auction_type,
+                        url_name
+                    ),
+                    LogLevel::Critical,
+                ))
+            }
+        };
+
+        let match_auction = match match_auction {
+            Some(m) => m,
+            None => MatchAuctionStruct {
+                element: None,
+                damage: None,
+                having_ephemera: None,
+            },
+        };
+
+        let mut inventory = StockAuctionStruct {
+            id: 0,
+            order_id: order_id.clone(),
+            auction_type: auction_type.to_string(),
+            weapon_id: weapon.id,
+            weapon_url: url_name.to_string(),
+            weapon_name: weapon.item_name,
+            name,
+            element: element.to_string(),
+            damage,
+            having_ephemera,
+            quirk,
+            match_auction: sqlx::types::Json(match_auction.clone()),
+            price,
+            minium_price,
+            listed_price: None,
+            private: false,
+            status: "pending".to_string(),
+            created: chrono::Local::now().naive_local().to_string(),
+        };
+
+        let sql = InsertStatement::default()
+            .into_table(StockAuction::Table)
+            .columns([
+                StockAuction::OrderId,
+                StockAuction::AuctionType,
+                StockAuction::WeaponId,
+                StockAuction::WeaponUrl,
+                StockAuction::WeaponName,
+                StockAuction::Name,
+                StockAuction::Element,
+                StockAuction::Damage,
+                StockAuction::HavingEphemera,
+                StockAuction::Quirk,
+                StockAuction::MatchAuction,
+                StockAuction::Price,
+                StockAuction::MiniumPrice,
+                StockAuction::Status,
+                StockAuction::Created,
+            ])
+            .values_panic([
+                inventory.order_id.clone().into(),
+                inventory.auction_type.clone().into(),
+                inventory.weapon_id.clone().into(),
+                inventory.weapon_url.clone().into(),
+                inventory.weapon_name.clone().into(),
+                inventory.name.clone().into(),
+                inventory.element.clone().into(),
+                inventory.damage.into(),
+                inventory.having_ephemera.into(),
+                inventory.quirk.clone().into(),
+                serde_json::to_value(&inventory.match_auction)
+                    .unwrap()
+                    .into(),
+                inventory.price.into(),
+                inventory.minium_price.into(),
+                inventory.status.clone().into(),
+                inventory.created.clone().into(),
+            ])
+            .to_string(SqliteQueryBuilder);
+        let row = sqlx::query(&sql.replace("\\", ""))
+            .execute(&connection)
+            .await
+            .map_err(|e| AppError::new("Database", eyre!(e.to_string())))?;
+        inventory.id = row.last_insert_rowid();
+
+        // Update UI
+        self.emit(
+            "CREATE_OR_UPDATE",
+            serde_json::to_value(inventory.clone()).unwrap(),
+        );
+        Ok(inventory)
+    }
+
+    pub async fn import_auction(
+        &self,
+        auction: Auction<String>,
+        price: i32,
+    ) -> Result<StockAuctionStruct, AppError> {
+        let item = auction.item.clone();
+        let weapon_url = match item.weapon_url_name.clone() {
+            Some(weapon_url) => weapon_url,
+            None => {
+                return Err(AppError::new_with_level(
+                    "Database",
+                    eyre!("Auction {} has no weapon", auction.id),
+                    LogLevel::Error,
+                ))
+            }
+        };
+        let stock = self
+            .create(
+                Some(auction.id.clone()),
+                &item.item_type,
+                &weapon_url,
+                item.name.clone(),
+                &item.element.clone().unwrap_or("".to_string()),
+                item.damage.unwrap_or(0) as i32,
+                item.having_ephemera.unwrap_or(false),
+                item.quirk.clone(),
+                None,
+                price as f64,
+                None,
+            )
+            .await?;
+        Ok(stock)
+    }
+
+    pub async fn reset_listed_price(&self) -> Result<(), AppError> {
+        let connection = self.client.connection.lock().unwrap().clone();
+        let sql = Query::update()
+            .table(StockAuction::Table)
+            .values([
+                (StockAuction::ListedPrice, Value::Int(None)),
+                (StockAuction::Status, "pending".into()),
+            ])
+            .to_string(SqliteQueryBuilder);
+        sqlx::query(&sql.replace("\\", ""))
+            .execute(&connection)
+            .await
+            .map_err(|e| AppError::new("Database", eyre!(e.to_string())))?;
+
+        self.emit("SET", json!(self.get_auctions().await?));
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn update_by_id(
+        &self,
+        id: i64,
+        order_id: Option<String>,
+        price: Option<f64>,
+        listed_price: Option<i32>,
+        match_auction: Option<MatchAuctionStruct>,
+        minium_price: Option<i32>,
+        status: Option<String>,
+        private: Option<bool>,
+    ) -> Result<StockAuctionStruct, AppError> {
+        let connection = self.client.connection.lock().unwrap().clone();
+        let stock_auction = self.get_by_id(id).await?;
+        if stock_auction.is_none() {
+            return Err(AppError::new_with_level(
+                "Database",
+                eyre!("Auction not found in database"),
+                LogLevel::Error,
+            ));
+        }
+        let mut stock_auction = stock_auction.unwrap();
+        let mut values = vec![(StockAuction::ListedPrice, listed_price.into())];
+
+        if let Some(order_id) = order_id {
+            if order_id.is_empty() || order_id == "null" {
+                stock_auction.order_id = None;
+            } else {
+                stock_auction.order_id = Some(order_id);
+            }
+            values.push((StockAuction::OrderId, stock_auction.order_id.clone().into()));
+        }
+
+        if let Some(price) = price {
+            stock_auction.price = price;
+            values.push((StockAuction::Price, price.into()));
+        }
+
+        if let Some(minium_price) = minium_price {
+            // If minium_price is -1, set it to None
+            let minium_price = if minium_price == -1 {
+                None
+            } else {
+                Some(minium_price)
+            };
+            stock_auction.minium_price = minium_price;
+            values.push((StockAuction::MiniumPrice, minium_price.into()));
+        }
+
+        if listed_price.is_some() && listed_price.unwrap() > -1 {
+            stock_auction.listed_price = listed_price;
+        }
+
+        if let Some(status) = status {
+            stock_auction.status = status;
+            values.push((StockAuction::Status, stock_auction.status.clone().into()));
+        }
+
+        if let Some(private) = private {
+            stock_auction.private = private;
+            values.push((StockAuction::Private, stock_auction.private.into()));
+        }
+
+        if let Some(match_auction) = match_auction {
+            stock_auction.match_auction = sqlx::types::Json(match_auction);
+            values.push((
+                StockAuction::MatchAuction,
+                serde_json::to_value(&stock_auction.match_auction)
+                    .unwrap()
+                    .into(),
+            ));
+        }
+
+        let sql = Query::update()
+            .table(StockAuction::Table)
+            .values(values)
+            .and_where(Expr::col(StockAuction::Id).eq(id))
+            .to_string(SqliteQueryBuilder);
+        sqlx::query(&sql.replace("\\", ""))
+            .execute(&connection)
+            .await
+            .map_err(|e| AppError::new("Database", eyre!(e.to_string())))?;
+
+        self.emit(
+            "CREATE_OR_UPDATE",
+            serde_json::to_value(stock_auction.clone()).unwrap(),
+        );
+        Ok(stock_auction)
+    }
+
+    pub async fn delete(&self, id: i64) -> Result<StockAuctionStruct, AppError> {
+        let connection = self.client.connection.lock().unwrap().clone();
+        let stock_auction = self.get_by_id(id).await?;
+        if stock_auction.is_none() {
+            return Err(AppError::new_with_level(
+                "Database",
+                eyre!("Stock Auction not found in database"),
+                LogLevel::Error,
+            ));
+        }
+        let sql = Query::delete()
+            .from_table(StockAuction::Table)
+            .and_where(Expr::col(StockAuction::Id).eq(id))
+            .to_string(SqliteQueryBuilder);
+        sqlx::query(&sql)
+            .execute(&connection)
+            .await
+            .map_err(|e| AppError::new("Database", eyre!(e.to_string())))?;
+        let stock_auction = stock_auction.unwrap();
+        self.emit(
+            "DELETE",
+            serde_json::to_value(stock_auction.clone()).unwrap(),
+        );
+        Ok(stock_auction)
+    }
+
+    pub fn emit(&self, operation: &str, data: serde_json::Value) {
+        helper::emit_update("StockAuctions", operation, Some(data));
+    }
+}
@@ -197,6 +197,22 @@ impl<'a> StockItemModule<'a> {
         let item = items.iter().find(|t| t.url == url_name);
         Ok(item.cloned())
     }
+    // Same item with another sub type (e.g. relic refinement) or rank is its own stock entry,
+    // a rank of None matches any rank
+    pub async fn get_item_by_key(
+        &self,
+        url_name: &str,
+        sub_type: Option<&str>,
+        rank: Option<i64>,
+    ) -> Result<Option<StockItemStruct>, AppError> {
+        let items = self.get_items().await?;
+        let item = items.iter().find(|t| {
+            t.url == url_name
+                && t.sub_type.as_deref() == sub_type
+                && rank.is_none_or(|rank| t.rank as i64 == rank)
+        });
+        Ok(item.cloned())
+    }
     pub async fn get_by_id(&self, id: i64) -> Result<Option<StockItemStruct>, AppError> {
         let stock = self.get_items().await?;
         let stock_item = stock.iter().find(|t| t.id == id);
@@ -211,7 +227,9 @@ impl<'a> StockItemModule<'a> {
         rank: i32,
         sub_type: Option<&str>,
     ) -> Result<StockItemStruct, AppError> {
-        let inventorys = self.get_item_by_url_name(url_name).await?;
+        let inventorys = self
+            .get_item_by_key(url_name, sub_type, Some(rank as i64))
+            .await?;
         let connection = self.client.connection.lock().unwrap().clone();
 
         if quantity <= 0 {
@@ -418,17 +436,19 @@ impl<'a> StockItemModule<'a> {
         self.emit("SET", json!(self.get_items().await?));
         Ok(())
     }
+    #[allow(clippy::too_many_arguments)]
     pub async fn update_by_url(
         &self,
         id: &str,
+        sub_type: Option<&str>,
+        rank: Option<i64>,
         owned: Option<i32>,
         price: Option<f64>,
         listed_price: Option<i32>,
         status: Option<String>,
         hidden: Option<bool>,
     ) -> Result<StockItemStruct, AppError> {
-        let items = self.get_items().await?;
-        let item = items.iter().find(|t| t.url == id);
+        let item = self.get_item_by_key(id, sub_type, rank).await?;
         if item.is_none() {
             return Err(AppError::new_with_level(
                 "Database",
@@ -525,6 +545,17 @@ impl<'a> StockItemModule<'a> {
         Ok(names)
     }
 
+    // Same as get_items_names but keeps the sub type and rank of every item
+    pub async fn get_items_keys(&self) -> Result<Vec<(String, Option<String>, i64)>, AppError> {
+        let inventorys = self.get_items().await?;
+        let keys = inventorys
+            .iter()
+            .filter(|t| !t.hidden && t.owned > 0)
+            .map(|t| (t.url.clone(), t.sub_type.clone(), t.rank as i64))
+            .collect::<Vec<_>>();
+        Ok(keys)
+    }
+
     pub fn emit(&self, operation: &str, data: serde_json::Value) {
         helper::emit_update("StockItems", operation, Some(data));
     }
@@ -543,6 +574,12 @@ impl<'a> StockItemModule<'a> {
                 "item_url",
                 item.iter().map(|i| i.url.clone()).collect::<Vec<_>>(),
             ),
+            Series::new(
+                "sub_type",
+                item.iter()
+                    .map(|i| i.sub_type.clone().unwrap_or("".to_string()))
+                    .collect::<Vec<_>>(),
+            ),
             Series::new(
                 "item_name",
                 item.iter().map(|i| i.name.clone()).collect::<Vec<_>>(),
@@ -544,6 +544,32 @@ impl<'a> StockRivenModule<'a> {
         );
         Ok(stock_item.unwrap().clone())
     }
+    // Removes a sold riven from the stock and stores the sale as a transaction.
+    pub async fn sell_riven(&self, id: i64, price: i32) -> Result<StockRivenStruct, AppError> {
+        let stock = self.delete(id).await?;
+        self.client
+            .transaction()
+            .create(
+                &stock.weapon_url,
+                "riven",
+                "sell",
+                1,
+                price,
+                stock.rank,
+                Some(json!({
+                    "type": "riven",
+                    "weapon_url_name": stock.weapon_url,
+                    "re_rolls": stock.re_rolls,
+                    "polarity": stock.polarity,
+                    "name": stock.mod_name,
+                    "mod_rank": stock.rank,
+                    "mastery_level": stock.mastery_rank,
+                    "attributes": stock.attributes,
+                })),
+            )
+            .await?;
+        Ok(stock)
+    }
     pub fn emit(&self, operation: &str, data: serde_json::Value) {
         helper::emit_update("StockRivens", operation, Some(data));
     }
@@ -0,0 +1,330 @@
+use crate::{database::client::DBClient, enums::LogLevel, error::AppError, helper};
+use eyre::eyre;
+use sea_query::{ColumnDef, Expr, Iden, InsertStatement, Query, SqliteQueryBuilder, Table, Value};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Iden)]
+pub enum StockSet {
+    Table,
+    Id,
+    WFMId,
+    Url,
+    Name,
+    Parts,
+    SetOrderId,
+    PartsCost,
+    Price,
+    ListedPrice,
+    Status,
+    Created,
+}
+
+// A set bought part by part and sold as a whole.
+// The part buy orders and the set sell order are linked through this row.
+#[derive(sqlx::FromRow, Serialize, Deserialize, Clone, Debug)]
+#[allow(dead_code)]
+pub struct StockSetStruct {
+    pub id: i64,
+    pub wfm_id: String,
+    pub url: String,
+    pub name: String,
+    pub parts: sqlx::types::Json<Vec<SetPartStruct>>,
+    pub set_order_id: Option<String>,
+    pub parts_cost: f64,
+    pub price: f64,
+    pub listed_price: Option<i32>,
+    pub status: String,
+    pub created: String,
+}
+
+#[derive(sqlx::FromRow, Serialize, Deserialize, Clone, Debug)]
+#[allow(dead_code)]
+pub struct SetPartStruct {
+    pub wfm_id: String,
+    pub url: String,
+    pub quantity: i64,
+    pub order_id: Option<String>,
+    pub listed_price: Option<i64>,
+}
+
+pub struct StockSetModule<'a> {
+    pub client: &'a DBClient,
+}
+
+impl<'a> StockSetModule<'a> {
+    // Methods sea-query
+
+    // Initialize the database
+    pub async fn initialize(&self) -> Result<bool, AppError> {
+        let connection = self.client.connection.lock().unwrap().clone();
+        let sql = Table::create()
+            .table(StockSet::Table)
+            .if_not_exists()
+            .col(
+                ColumnDef::new(StockSet::Id)
+                    .integer()
+                    .not_null()
+                    .auto_increment()
+                    .primary_key(),
+            )
+            .col(ColumnDef::new(StockSet::WFMId).uuid().not_null())
+            .col(ColumnDef::new(StockSet::Url).string().not_null())
+            .col(ColumnDef::new(StockSet::Name).string().not_null())
+            .col(
+                ColumnDef::new(StockSet::Parts)
+                    .json()
+                    .not_null()
+                    .default(json!([])),
+            )
+            .col(ColumnDef::new(StockSet::SetOrderId).uuid())
+            .col(
+                ColumnDef::new(StockSet::PartsCost)
+                    .float()
+                    .not_null()
+                    .default(Value::Int(Some(0))),
+            )
+            .col(
+                ColumnDef::new(StockSet::Price)
+                    .float()
+                    .not_null()
+                    .default(Value::Int(Some(0))),
+            )
+            .col(
+                ColumnDef::new(StockSet::ListedPrice)
+                    .integer()
+                    .default(Value::Int(None)),
+            )
+            .col(
+                ColumnDef::new(StockSet::Status)
+                    .string()
+                    .not_null()
+                    .default("buying"),
+            )
+            .col(ColumnDef::new(StockSet::Created).date_time().not_null())
+            .build(SqliteQueryBuilder);
+
+        sqlx::query(&sql)
+            .execute(&connection)
+            .await
+            .map_err(|e| AppError::new("Database", eyre!(e.to_string())))?;
+        Ok(true)
+    }
+
+    pub async fn get_sets(&self) -> Result<Vec<StockSetStruct>, AppError> {
+        let connection = self.client.connection.lock().unwrap().clone();
+        // Read
+        let sql = Query::select()
+            .columns([
+                StockSet::Id,
+                StockSet::WFMId,
+                StockSet::Url,
+                StockSet::Name,
+                StockSet::Parts,
+                StockSet::SetOrderId,
+                StockSet::PartsCost,
+                StockSet::Price,
+                StockSet::ListedPrice,
+                StockSet::Status,
+                StockSet::Created,
+            ])
+            .from(StockSet::Table)
+            .to_string(SqliteQueryBuilder);
+        let rows = sqlx::query_as::<_, StockSetStruct>(&sql)
+            .fetch_all(&connection)
+            .await
+            .map_err(|e| AppError::new("Database", eyre!(e.to_string())))?;
+        Ok(rows)
+    }
+
+    pub async fn get_by_id(&self, id: i64) -> Result<Option<StockSetStruct>, AppError> {
+        let sets = self.get_sets().await?;
+        let set = sets.iter().find(|t| t.id == id);
+        Ok(set.cloned())
+    }
+
+    // Returns the url names of all parts that are held back for a set.
+    pub async fn get_reserved_parts(&self) -> Result<Vec<String>, AppError> {
+        let sets = self.get_sets().await?;
+        let parts = sets
+            .iter()
+            .flat_map(|s| s.parts.0.iter().map(|p| p.url.clone()))
+            .collect::<Vec<_>>();
+        Ok(parts)
+    }
+
+    pub async fn create(
+        &self,
+        url_name: &str,
+        parts: Vec<SetPartStruct>,
+        parts_cost: f64,
+        price: f64,
+    ) -> Result<StockSetStruct, AppError> {
+        let connection = self.client.connection.lock().unwrap().clone();
+        let item = match self.client.cache.lock()?.items().find_type(url_name)? {
+            Some(item) => item,
+            None => {
+                return Err(AppError::new_with_level(
+                    "Database",
+                    eyre!("Could not find set in cache: {}", url_name),
+                    LogLevel::Critical,
+                ))
+            }
+        };
+
+        let mut set = StockSetStruct {
+            id: 0,
+            wfm_id: item.id.clone(),
+            url: url_name.to_string(),
+            name: item.item_name.replace("\'", ""),
+            parts: sqlx::types::Json(parts),
+            set_order_id: None,
+            parts_cost,
+            price,
+            listed_price: None,
+            status: "buying".to_string(),
+            created: chrono::Local::now().naive_local().to_string(),
+        };
+
+        let sql = InsertStatement::default()
+            .into_table(StockSet::Table)
+            .columns([
+                StockSet::WFMId,
+                StockSet::Url,
+                StockSet::Name,
+                StockSet::Parts,
+                StockSet::PartsCost,
+                StockSet::Price,
+                StockSet::Status,
+                StockSet::Created,
+            ])
+            .values_panic([
+                set.wfm_id.clone().into(),
+                set.url.clone().into(),
+                set.name.clone().into(),
+                serde_json::to_value(&set.parts).unwrap().into(),
+                set.parts_cost.into(),
+                set.price.into(),
+                set.status.clone().into(),
+                set.created.clone().into(),
+            ])
+            .to_string(SqliteQueryBuilder);
+        let row = sqlx::query(&sql.replace("\\", ""))
+            .execute(&connection)
+            .await
+            .map_err(|e| AppError::new("Database", eyre!(e.to_string())))?;
+        set.id = row.last_insert_rowid();
+
+        self.emit(
+            "CREATE_OR_UPDATE",
+            serde_json::to_value(set.clone()).unwrap(),
+        );
+        Ok(set)
+    }
+
+    pub async fn update_by_id(
+        &self,
+        id: i64,
+        parts: Option<Vec<SetPartStruct>>,
+        set_order_id: Option<String>,
+        price: Option<f64>,
+        listed_price: Option<i32>,
+        status: Option<String>,
+    ) -> Result<StockSetStruct, AppError> {
+        let connection = self.client.connection.lock().unwrap().clone();
+        let set = self.get_by_id(id).await?;
+        if set.is_none() {
+            return Err(AppError::new_with_level(
+                "Database",
+                eyre!("Set not found in database"),
+                LogLevel::Error,
+            ));
+        }
+        let mut set = set.unwrap();
+        let mut values = vec![];
+
+        if let Some(parts) = parts {
+            set.parts = sqlx::types::Json(parts);
+            values.push((
+                StockSet::Parts,
+                serde_json::to_value(&set.parts).unwrap().into(),
+            ));
+        }
+
+        if let Some(set_order_id) = set_order_id {
+            if set_order_id.is_empty() {
+                set.set_order_id = None;
+            } else {
+                set.set_order_id = Some(set_order_id);
+            }
+            values.push((StockSet::SetOrderId, set.set_order_id.clone().into()));
+        }
+
+        if let Some(price) = price {
+            set.price = price;
+            values.push((StockSet::Price, price.into()));
+        }
+
+        if let Some(listed_price) = listed_price {
+            // If listed_price is -1, set it to None
+            set.listed_price = if listed_price == -1 {
+                None
+            } else {
+                Some(listed_price)
+            };
+            values.push((StockSet::ListedPrice, set.listed_price.into()));
+        }
+
+        if let Some(status) = status {
+            set.status = status;
+            values.push((StockSet::Status, set.status.clone().into()));
+        }
+
+        if values.is_empty() {
+            return Ok(set);
+        }
+
+        let sql = Query::update()
+            .table(StockSet::Table)
+            .values(values)
+            .and_where(Expr::col(StockSet::Id).eq(id))
+            .to_string(SqliteQueryBuilder);
+        sqlx::query(&sql.replace("\\", ""))
+            .execute(&connection)
+            .await
+            .map_err(|e| AppError::new("Database", eyre!(e.to_string())))?;
+
+        self.emit(
+            "CREATE_OR_UPDATE",
+            serde_json::to_value(set.clone()).unwrap(),
+        );
+        Ok(set)
+    }
+
+    pub async fn delete(&self, id: i64) -> Result<StockSetStruct, AppError> {
+        let connection = self.client.connection.lock().unwrap().clone();
+        let set = self.get_by_id(id).await?;
+        if set.is_none() {
+            return Err(AppError::new_with_level(
+                "Database",
+                eyre!("Stock Set not found in database"),
+                LogLevel::Error,
+            ));
+        }
+        let sql = Query::delete()
+            .from_table(StockSet::Table)
+            .and_where(Expr::col(StockSet::Id).eq(id))
+            .to_string(SqliteQueryBuilder);
+        sqlx::query(&sql)
+            .execute(&connection)
+            .await
+            .map_err(|e| AppError::new("Database", eyre!(e.to_string())))?;
+        let set = set.unwrap();
+        self.emit("DELETE", serde_json::to_value(set.clone()).unwrap());
+        Ok(set)
+    }
+
+    pub fn emit(&self, operation: &str, data: serde_json::Value) {
+        helper::emit_update("StockSets", operation, Some(data));
+    }
+}
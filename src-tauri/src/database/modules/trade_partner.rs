@@ -0,0 +1,373 @@
+use crate::{database::client::DBClient, error::AppError, helper};
+use eyre::eyre;
+use sea_query::{
+    ColumnDef, Expr, Iden, Index, InsertStatement, OnConflict, Query, SqliteQueryBuilder, Table,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Iden)]
+pub enum TradePartner {
+    Table,
+    Id,
+    UserName,
+    Trades,
+    PlatinumIn,
+    PlatinumOut,
+    LastSeen,
+    Notes,
+    Blocked,
+    Created,
+}
+
+// The log times of the trades counted for a player, so a replayed log does not count them again.
+#[derive(Iden)]
+pub enum TradePartnerTrade {
+    Table,
+    UserName,
+    TradedAt,
+}
+
+// A player we traded with, the totals are added up from every trade found in EE.log.
+#[derive(sqlx::FromRow, Serialize, Deserialize, Clone, Debug)]
+pub struct TradePartnerStruct {
+    pub id: i64,
+    pub user_name: String,
+    pub trades: i64,
+    // Platinum received from the player
+    pub platinum_in: i64,
+    // Platinum paid to the player
+    pub platinum_out: i64,
+    pub last_seen: String,
+    pub notes: String,
+    // The orders and auctions of a blocked player are left out of the live scraper prices
+    pub blocked: bool,
+    pub created: String,
+}
+
+pub struct TradePartnerModule<'a> {
+    pub client: &'a DBClient,
+}
+
+impl<'a> TradePartnerModule<'a> {
+    // Methods sea-query
+
+    // Initialize the database
+    pub async fn initialize(&self) -> Result<bool, AppError> {
+        let connection = self.client.connection.lock().unwrap().clone();
+        let sql = Table::create()
+            .table(TradePartner::Table)
+            .if_not_exists()
+            .col(
+                ColumnDef::new(TradePartner::Id)
+                    .integer()
+                    .not_null()
+                    .auto_increment()
+                    .primary_key(),
+            )
+            .col(
+                ColumnDef::new(TradePartner::UserName)
+                    .string()
+                    .not_null()
+                    .unique_key(),
+            )
+            .col(
+                ColumnDef::new(TradePartner::Trades)
+                    .integer()
+                    .not_null()
+                    .default(0),
+            )
+            .col(
+                ColumnDef::new(TradePartner::PlatinumIn)
+                    .integer()
+                    .not_null()
+                    .default(0),
+            )
+            .col(
+                ColumnDef::new(TradePartner::PlatinumOut)
+                    .integer()
+                    .not_null()
+                    .default(0),
+            )
+            .col(ColumnDef::new(TradePartner::LastSeen).string().not_null())
+            .col(
+                ColumnDef::new(TradePartner::Notes)
+                    .string()
+                    .not_null()
+                    .default(""),
+            )
+            .col(
+                ColumnDef::new(TradePartner::Blocked)
+                    .boolean()
+                    .not_null()
+                    .default(false),
+            )
+            .col(ColumnDef::new(TradePartner::Created).date_time().not_null())
+            .build(SqliteQueryBuilder);
+
+        sqlx::query(&sql)
+            .execute(&connection)
+            .await
+            .map_err(|e| AppError::new("Database", eyre!(e.to_string())))?;
+
+        let sql = Table::create()
+            .table(TradePartnerTrade::Table)
+            .if_not_exists()
+            .col(
+                ColumnDef::new(TradePartnerTrade::UserName)
+                    .string()
+                    .not_null(),
+            )
+            .col(
+                ColumnDef::new(TradePartnerTrade::TradedAt)
+                    .string()
+                    .not_null(),
+            )
+            .build(SqliteQueryBuilder);
+        sqlx::query(&sql)
+            .execute(&connection)
+            .await
+            .map_err(|e| AppError::new("Database", eyre!(e.to_string())))?;
+
+        let sql = Index::create()
+            .if_not_exists()
+            .name("idx_trade_partner_trade_row")
+            .table(TradePartnerTrade::Table)
+            .col(TradePartnerTrade::UserName)
+            .col(TradePartnerTrade::TradedAt)
+            .unique()
+            .build(SqliteQueryBuilder);
+        sqlx::query(&sql)
+            .execute(&connection)
+            .await
+            .map_err(|e| AppError::new("Database", eyre!(e.to_string())))?;
+        Ok(true)
+    }
+
+    pub async fn get_items(&self) -> Result<Vec<TradePartnerStruct>, AppError> {
+        let connection = self.client.connection.lock().unwrap().clone();
+        // Read
+        let sql = Query::select()
+            .columns([
+                TradePartner::Id,
+                TradePartner::UserName,
+                TradePartner::Trades,
+                TradePartner::PlatinumIn,
+                TradePartner::PlatinumOut,
+                TradePartner::LastSeen,
+                TradePartner::Notes,
+                TradePartner::Blocked,
+                TradePartner::Created,
+            ])
+            .from(TradePartner::Table)
+            .to_string(SqliteQueryBuilder);
+        let rows = sqlx::query_as::<_, TradePartnerStruct>(&sql)
+            .fetch_all(&connection)
+            .await
+            .map_err(|e| AppError::new("Database", eyre!(e.to_string())))?;
+        Ok(rows)
+    }
+
+    pub async fn get_by_name(
+        &self,
+        user_name: &str,
+    ) -> Result<Option<TradePartnerStruct>, AppError> {
+        let partners = self.get_items().await?;
+        Ok(partners.into_iter().find(|p| p.user_name == user_name))
+    }
+
+    pub async fn get_blocked_names(&self) -> Result<Vec<String>, AppError> {
+        let partners = self.get_items().await?;
+        Ok(partners
+            .into_iter()
+            .filter(|p| p.blocked)
+            .map(|p| p.user_name)
+            .collect())
+    }
+
+    // Creates a player, `last_seen` is the time of their first trade or empty when we did not trade yet.
+    pub async fn create(
+        &self,
+        user_name: &str,
+        last_seen: &str,
+    ) -> Result<TradePartnerStruct, AppError> {
+        let connection = self.client.connection.lock().unwrap().clone();
+        let mut partner = TradePartnerStruct {
+            id: 0,
+            user_name: user_name.to_string(),
+            trades: 0,
+            platinum_in: 0,
+            platinum_out: 0,
+            last_seen: last_seen.to_string(),
+            notes: "".to_string(),
+            blocked: false,
+            created: chrono::Local::now().to_rfc3339(),
+        };
+
+        let sql = InsertStatement::default()
+            .into_table(TradePartner::Table)
+            .columns([
+                TradePartner::UserName,
+                TradePartner::LastSeen,
+                TradePartner::Created,
+            ])
+            .values_panic([
+                partner.user_name.clone().into(),
+                partner.last_seen.clone().into(),
+                partner.created.clone().into(),
+            ])
+            .to_string(SqliteQueryBuilder);
+        let row = sqlx::query(&sql.replace("\\", ""))
+            .execute(&connection)
+            .await
+            .map_err(|e| AppError::new("Database", eyre!(e.to_string())))?;
+        partner.id = row.last_insert_rowid();
+
+        self.emit(
+            "CREATE_OR_UPDATE",
+            serde_json::to_value(partner.clone()).unwrap(),
+        );
+        Ok(partner)
+    }
+
+    // Adds a trade to the totals of the player, the player is created on the first trade.
+    // A trade with a log time is counted once, None is returned when it was counted before.
+    pub async fn record_trade(
+        &self,
+        user_name: &str,
+        platinum_in: i64,
+        platinum_out: i64,
+        seen: &str,
+    ) -> Result<Option<TradePartnerStruct>, AppError> {
+        let connection = self.client.connection.lock().unwrap().clone();
+        let mut tx = connection
+            .begin()
+            .await
+            .map_err(|e| AppError::new("Database", eyre!(e.to_string())))?;
+
+        if !seen.is_empty() {
+            let sql = InsertStatement::default()
+                .into_table(TradePartnerTrade::Table)
+                .columns([TradePartnerTrade::UserName, TradePartnerTrade::TradedAt])
+                .values_panic([user_name.into(), seen.into()])
+                .on_conflict(
+                    OnConflict::columns([TradePartnerTrade::UserName, TradePartnerTrade::TradedAt])
+                        .do_nothing()
+                        .to_owned(),
+                )
+                .to_string(SqliteQueryBuilder);
+            let result = sqlx::query(&sql.replace("\\", ""))
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| AppError::new("Database", eyre!(e.to_string())))?;
+            if result.rows_affected() == 0 {
+                return Ok(None);
+            }
+        }
+
+        // A replayed log can hold trades older than the last one we know of
+        let sql = InsertStatement::default()
+            .into_table(TradePartner::Table)
+            .columns([
+                TradePartner::UserName,
+                TradePartner::Trades,
+                TradePartner::PlatinumIn,
+                TradePartner::PlatinumOut,
+                TradePartner::LastSeen,
+                TradePartner::Created,
+            ])
+            .values_panic([
+                user_name.into(),
+                1.into(),
+                platinum_in.into(),
+                platinum_out.into(),
+                seen.into(),
+                chrono::Local::now().to_rfc3339().into(),
+            ])
+            .on_conflict(
+                OnConflict::column(TradePartner::UserName)
+                    .values([
+                        (TradePartner::Trades, Expr::col(TradePartner::Trades).add(1)),
+                        (
+                            TradePartner::PlatinumIn,
+                            Expr::col(TradePartner::PlatinumIn).add(platinum_in),
+                        ),
+                        (
+                            TradePartner::PlatinumOut,
+                            Expr::col(TradePartner::PlatinumOut).add(platinum_out),
+                        ),
+                        (
+                            TradePartner::LastSeen,
+                            Expr::cust("MAX(last_seen, excluded.last_seen)"),
+                        ),
+                    ])
+                    .to_owned(),
+            )
+            .to_string(SqliteQueryBuilder);
+        sqlx::query(&sql.replace("\\", ""))
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::new("Database", eyre!(e.to_string())))?;
+        tx.commit()
+            .await
+            .map_err(|e| AppError::new("Database", eyre!(e.to_string())))?;
+
+        let partner = self.get_by_name(user_name).await?.ok_or(AppError::new(
+            "Database",
+            eyre!("Trade partner {} not found", user_name),
+        ))?;
+        self.emit(
+            "CREATE_OR_UPDATE",
+            serde_json::to_value(partner.clone()).unwrap(),
+        );
+        Ok(Some(partner))
+    }
+
+    // Sets the notes and the blocklist flag, a player can be blocked before trading with them.
+    pub async fn update_by_name(
+        &self,
+        user_name: &str,
+        notes: Option<String>,
+        blocked: Option<bool>,
+    ) -> Result<TradePartnerStruct, AppError> {
+        let connection = self.client.connection.lock().unwrap().clone();
+        let mut partner = match self.get_by_name(user_name).await? {
+            Some(partner) => partner,
+            None => self.create(user_name, "").await?,
+        };
+        let mut values = vec![];
+
+        if let Some(notes) = notes {
+            partner.notes = notes;
+            values.push((TradePartner::Notes, partner.notes.clone().into()));
+        }
+
+        if let Some(blocked) = blocked {
+            partner.blocked = blocked;
+            values.push((TradePartner::Blocked, partner.blocked.into()));
+        }
+
+        if values.is_empty() {
+            return Ok(partner);
+        }
+
+        let sql = Query::update()
+            .table(TradePartner::Table)
+            .values(values)
+            .and_where(Expr::col(TradePartner::Id).eq(partner.id))
+            .to_string(SqliteQueryBuilder);
+        sqlx::query(&sql.replace("\\", ""))
+            .execute(&connection)
+            .await
+            .map_err(|e| AppError::new("Database", eyre!(e.to_string())))?;
+
+        self.emit(
+            "CREATE_OR_UPDATE",
+            serde_json::to_value(partner.clone()).unwrap(),
+        );
+        Ok(partner)
+    }
+
+    pub fn emit(&self, operation: &str, data: serde_json::Value) {
+        helper::emit_update("TradePartners", operation, Some(data));
+    }
+}
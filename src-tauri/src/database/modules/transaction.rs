@@ -22,6 +22,7 @@ pub enum Transaction {
     Quantity,
     Created,
     Properties,
+    Credits,
 }
 
 #[derive(sqlx::FromRow, Serialize, Deserialize, Clone, Debug)]
@@ -39,6 +40,7 @@ pub struct TransactionStruct {
     pub price: i32,
     pub created: String,
     pub properties: Option<sqlx::types::Json<Option<serde_json::Value>>>,
+    pub credits: i64,
 }
 impl<'a> TransactionModule<'a> {
     pub async fn initialize(&self) -> Result<bool, AppError> {
@@ -82,6 +84,12 @@ impl<'a> TransactionModule<'a> {
                     .default(Value::Int(Some(0))),
             )
             .col(ColumnDef::new(Transaction::Properties).json())
+            .col(
+                ColumnDef::new(Transaction::Credits)
+                    .integer()
+                    .not_null()
+                    .default(Value::Int(Some(0))),
+            )
             .col(ColumnDef::new(Transaction::Created).date_time().not_null())
             .build(SqliteQueryBuilder);
 
@@ -89,9 +97,34 @@ impl<'a> TransactionModule<'a> {
             .execute(&connection)
             .await
             .map_err(|e| AppError::new("Database", eyre!(e.to_string())))?;
+
+        let table = Table::alter()
+            .table(Transaction::Table)
+            .add_column(
+                ColumnDef::new(Transaction::Credits)
+                    .integer()
+                    .not_null()
+                    .default(Value::Int(Some(0))),
+            )
+            .to_string(SqliteQueryBuilder);
+        helper::alter_table(connection.clone(), &table).await?;
         Ok(true)
     }
 
+    // The trade tax is paid by the player giving the item, so only sold items cost credits.
+    fn get_trade_tax(&self, transaction: &TransactionStruct) -> i64 {
+        if transaction.transaction_type != "sell" || transaction.item_type != "item" {
+            return 0;
+        }
+        let tags = transaction
+            .tags
+            .split(",")
+            .map(|t| t.to_string())
+            .collect::<Vec<_>>();
+        helper::calculate_trade_tax(tags, Some(transaction.rank as i64))
+            * transaction.quantity as i64
+    }
+
     pub async fn get_items(&self) -> Result<Vec<TransactionStruct>, AppError> {
         let connection = self.client.connection.lock().unwrap().clone();
         // Read
@@ -108,6 +141,7 @@ impl<'a> TransactionModule<'a> {
                 Transaction::Price,
                 Transaction::Quantity,
                 Transaction::Properties,
+                Transaction::Credits,
                 Transaction::Created,
             ])
             .from(Transaction::Table)
@@ -133,6 +167,31 @@ impl<'a> TransactionModule<'a> {
         price: i32,
         rank: i32,
         properties: Option<serde_json::Value>,
+    ) -> Result<TransactionStruct, AppError> {
+        self.create_at(
+            url_name,
+            item_type,
+            transaction_type,
+            quantity,
+            price,
+            rank,
+            properties,
+            chrono::Utc::now().to_rfc3339(),
+        )
+        .await
+    }
+    // Creates a transaction that happened at the given time, used when importing older trades.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create_at(
+        &self,
+        url_name: &str,
+        item_type: &str,
+        transaction_type: &str,
+        quantity: i32,
+        price: i32,
+        rank: i32,
+        properties: Option<serde_json::Value>,
+        created: String,
     ) -> Result<TransactionStruct, AppError> {
         let connection = self.client.connection.lock().unwrap().clone();
         let mut transaction = TransactionStruct {
@@ -147,33 +206,72 @@ impl<'a> TransactionModule<'a> {
             price,
             transaction_type: transaction_type.to_string(),
             quantity,
-            created: chrono::Utc::now().to_rfc3339(),
+            credits: 0,
+            created,
         };
         if item_type == "riven" {
-            let item = self
-                .client
-                .cache
-                .lock()?
-                .riven()
-                .find_type(&url_name)?
-                .unwrap();
+            let item = match self.client.cache.lock()?.riven().find_type(url_name)? {
+                Some(t) => t,
+                None => {
+                    return Err(AppError::new_with_level(
+                        "Database",
+                        eyre!("Riven {} not found in cache", url_name),
+                        LogLevel::Error,
+                    ));
+                }
+            };
             transaction.wfm_id = item.id.clone();
             transaction.url = item.url_name.clone();
             transaction.name = item.item_name.clone();
             transaction.tags = item.riven_type.clone().unwrap_or("Unknown".to_string());
         } else if item_type == "item" {
-            let item = self
-                .client
-                .cache
-                .lock()?
-                .items()
-                .find_type(&url_name)?
-                .unwrap();
+            let item = match self.client.cache.lock()?.items().find_type(url_name)? {
+                Some(t) => t,
+                None => {
+                    return Err(AppError::new_with_level(
+                        "Database",
+                        eyre!("Item {} not found in cache", url_name),
+                        LogLevel::Error,
+                    ));
+                }
+            };
+            let tags = match item.tags {
+                Some(tags) => tags,
+                None => {
+                    return Err(AppError::new_with_level(
+                        "Database",
+                        eyre!("Item {} has no tags in cache", url_name),
+                        LogLevel::Error,
+                    ));
+                }
+            };
             transaction.wfm_id = item.id.clone();
             transaction.url = item.url_name.clone();
             transaction.name = item.item_name.replace("\'", "").clone();
-            transaction.tags = item.tags.unwrap().join(",");
+            transaction.tags = tags.join(",");
+        } else if item_type == "lich" || item_type == "sister" {
+            let weapon = match self
+                .client
+                .cache
+                .lock()?
+                .auction()
+                .find_weapon(item_type, url_name)?
+            {
+                Some(t) => t,
+                None => {
+                    return Err(AppError::new_with_level(
+                        "Database",
+                        eyre!("Weapon {} {} not found in cache", item_type, url_name),
+                        LogLevel::Error,
+                    ));
+                }
+            };
+            transaction.wfm_id = weapon.id.clone();
+            transaction.url = weapon.url_name.clone();
+            transaction.name = weapon.item_name.clone();
+            transaction.tags = item_type.to_string();
         }
+        transaction.credits = self.get_trade_tax(&transaction);
 
         logger::info_con(
             "Database",
@@ -201,6 +299,7 @@ impl<'a> TransactionModule<'a> {
                 Transaction::Price,
                 Transaction::TransactionType,
                 Transaction::Quantity,
+                Transaction::Credits,
                 Transaction::Created,
             ])
             .values_panic([
@@ -214,6 +313,7 @@ impl<'a> TransactionModule<'a> {
                 transaction.price.into(),
                 transaction.transaction_type.clone().into(),
                 transaction.quantity.into(),
+                transaction.credits.into(),
                 transaction.created.clone().into(),
             ])
             .to_string(SqliteQueryBuilder);
@@ -269,6 +369,10 @@ impl<'a> TransactionModule<'a> {
             transaction.rank = rank.unwrap() as i32;
             values.push((Transaction::Rank, rank.into()));
         }
+
+        // Quantity, rank and type all change the trade tax
+        transaction.credits = self.get_trade_tax(&transaction);
+        values.push((Transaction::Credits, transaction.credits.into()));
         logger::info_con("Database", format!("Updating Transaction: {} {} {} {}", transaction.price, transaction.transaction_type, transaction.quantity, transaction.rank).as_str());
         let sql = Query::update()
             .table(Transaction::Table)
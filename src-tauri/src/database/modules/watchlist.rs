@@ -0,0 +1,257 @@
+use crate::{database::client::DBClient, enums::LogLevel, error::AppError, helper};
+use eyre::eyre;
+use sea_query::{ColumnDef, Expr, Iden, InsertStatement, Query, SqliteQueryBuilder, Table, Value};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Iden)]
+pub enum Watchlist {
+    Table,
+    Id,
+    WFMId,
+    Url,
+    Name,
+    ModRank,
+    Rules,
+    Created,
+}
+
+// An item that is watched without being in stock, every rule can fire a notification.
+#[derive(sqlx::FromRow, Serialize, Deserialize, Clone, Debug)]
+#[allow(dead_code)]
+pub struct WatchlistStruct {
+    pub id: i64,
+    pub wfm_id: String,
+    pub url: String,
+    pub name: String,
+    pub mod_rank: Option<i64>,
+    pub rules: sqlx::types::Json<Vec<WatchRuleStruct>>,
+    pub created: String,
+}
+
+// rule_type is one of
+//  "sell_below"     lowest ingame sell order is below value
+//  "buy_above"      highest ingame buy order is above value
+//  "median_change"  closed median moved more than value percent over the last day
+#[derive(sqlx::FromRow, Serialize, Deserialize, Clone, Debug)]
+#[allow(dead_code)]
+pub struct WatchRuleStruct {
+    pub rule_type: String,
+    pub value: f64,
+    // Minutes before the rule can fire again
+    pub cooldown: i64,
+    pub last_triggered: Option<String>,
+}
+
+pub struct WatchlistModule<'a> {
+    pub client: &'a DBClient,
+}
+
+impl<'a> WatchlistModule<'a> {
+    // Methods sea-query
+
+    // Initialize the database
+    pub async fn initialize(&self) -> Result<bool, AppError> {
+        let connection = self.client.connection.lock().unwrap().clone();
+        let sql = Table::create()
+            .table(Watchlist::Table)
+            .if_not_exists()
+            .col(
+                ColumnDef::new(Watchlist::Id)
+                    .integer()
+                    .not_null()
+                    .auto_increment()
+                    .primary_key(),
+            )
+            .col(ColumnDef::new(Watchlist::WFMId).uuid().not_null())
+            .col(ColumnDef::new(Watchlist::Url).string().not_null())
+            .col(ColumnDef::new(Watchlist::Name).string().not_null())
+            .col(
+                ColumnDef::new(Watchlist::ModRank)
+                    .integer()
+                    .default(Value::Int(None)),
+            )
+            .col(
+                ColumnDef::new(Watchlist::Rules)
+                    .json()
+                    .not_null()
+                    .default(json!([])),
+            )
+            .col(ColumnDef::new(Watchlist::Created).date_time().not_null())
+            .build(SqliteQueryBuilder);
+
+        sqlx::query(&sql)
+            .execute(&connection)
+            .await
+            .map_err(|e| AppError::new("Database", eyre!(e.to_string())))?;
+        Ok(true)
+    }
+
+    pub async fn get_items(&self) -> Result<Vec<WatchlistStruct>, AppError> {
+        let connection = self.client.connection.lock().unwrap().clone();
+        // Read
+        let sql = Query::select()
+            .columns([
+                Watchlist::Id,
+                Watchlist::WFMId,
+                Watchlist::Url,
+                Watchlist::Name,
+                Watchlist::ModRank,
+                Watchlist::Rules,
+                Watchlist::Created,
+            ])
+            .from(Watchlist::Table)
+            .to_string(SqliteQueryBuilder);
+        let rows = sqlx::query_as::<_, WatchlistStruct>(&sql)
+            .fetch_all(&connection)
+            .await
+            .map_err(|e| AppError::new("Database", eyre!(e.to_string())))?;
+        Ok(rows)
+    }
+
+    pub async fn get_by_id(&self, id: i64) -> Result<Option<WatchlistStruct>, AppError> {
+        let items = self.get_items().await?;
+        let item = items.iter().find(|t| t.id == id);
+        Ok(item.cloned())
+    }
+
+    pub async fn create(
+        &self,
+        url_name: &str,
+        mod_rank: Option<i64>,
+        rules: Vec<WatchRuleStruct>,
+    ) -> Result<WatchlistStruct, AppError> {
+        let connection = self.client.connection.lock().unwrap().clone();
+        let item = match self.client.cache.lock()?.items().find_type(url_name)? {
+            Some(item) => item,
+            None => {
+                return Err(AppError::new_with_level(
+                    "Database",
+                    eyre!("Could not find item in cache: {}", url_name),
+                    LogLevel::Critical,
+                ))
+            }
+        };
+
+        let mut watch = WatchlistStruct {
+            id: 0,
+            wfm_id: item.id.clone(),
+            url: url_name.to_string(),
+            name: item.item_name.replace("\'", ""),
+            mod_rank,
+            rules: sqlx::types::Json(rules),
+            created: chrono::Local::now().naive_local().to_string(),
+        };
+
+        let sql = InsertStatement::default()
+            .into_table(Watchlist::Table)
+            .columns([
+                Watchlist::WFMId,
+                Watchlist::Url,
+                Watchlist::Name,
+                Watchlist::ModRank,
+                Watchlist::Rules,
+                Watchlist::Created,
+            ])
+            .values_panic([
+                watch.wfm_id.clone().into(),
+                watch.url.clone().into(),
+                watch.name.clone().into(),
+                watch.mod_rank.into(),
+                serde_json::to_value(&watch.rules).unwrap().into(),
+                watch.created.clone().into(),
+            ])
+            .to_string(SqliteQueryBuilder);
+        let row = sqlx::query(&sql.replace("\\", ""))
+            .execute(&connection)
+            .await
+            .map_err(|e| AppError::new("Database", eyre!(e.to_string())))?;
+        watch.id = row.last_insert_rowid();
+
+        self.emit(
+            "CREATE_OR_UPDATE",
+            serde_json::to_value(watch.clone()).unwrap(),
+        );
+        Ok(watch)
+    }
+
+    pub async fn update_by_id(
+        &self,
+        id: i64,
+        mod_rank: Option<i64>,
+        rules: Option<Vec<WatchRuleStruct>>,
+    ) -> Result<WatchlistStruct, AppError> {
+        let connection = self.client.connection.lock().unwrap().clone();
+        let watch = self.get_by_id(id).await?;
+        if watch.is_none() {
+            return Err(AppError::new_with_level(
+                "Database",
+                eyre!("Watchlist item not found in database"),
+                LogLevel::Error,
+            ));
+        }
+        let mut watch = watch.unwrap();
+        let mut values = vec![];
+
+        if let Some(mod_rank) = mod_rank {
+            // If mod_rank is -1, set it to None
+            watch.mod_rank = if mod_rank == -1 { None } else { Some(mod_rank) };
+            values.push((Watchlist::ModRank, watch.mod_rank.into()));
+        }
+
+        if let Some(rules) = rules {
+            watch.rules = sqlx::types::Json(rules);
+            values.push((
+                Watchlist::Rules,
+                serde_json::to_value(&watch.rules).unwrap().into(),
+            ));
+        }
+
+        if values.is_empty() {
+            return Ok(watch);
+        }
+
+        let sql = Query::update()
+            .table(Watchlist::Table)
+            .values(values)
+            .and_where(Expr::col(Watchlist::Id).eq(id))
+            .to_string(SqliteQueryBuilder);
+        sqlx::query(&sql.replace("\\", ""))
+            .execute(&connection)
+            .await
+            .map_err(|e| AppError::new("Database", eyre!(e.to_string())))?;
+
+        self.emit(
+            "CREATE_OR_UPDATE",
+            serde_json::to_value(watch.clone()).unwrap(),
+        );
+        Ok(watch)
+    }
+
+    pub async fn delete(&self, id: i64) -> Result<WatchlistStruct, AppError> {
+        let connection = self.client.connection.lock().unwrap().clone();
+        let watch = self.get_by_id(id).await?;
+        if watch.is_none() {
+            return Err(AppError::new_with_level(
+                "Database",
+                eyre!("Watchlist item not found in database"),
+                LogLevel::Error,
+            ));
+        }
+        let sql = Query::delete()
+            .from_table(Watchlist::Table)
+            .and_where(Expr::col(Watchlist::Id).eq(id))
+            .to_string(SqliteQueryBuilder);
+        sqlx::query(&sql)
+            .execute(&connection)
+            .await
+            .map_err(|e| AppError::new("Database", eyre!(e.to_string())))?;
+        let watch = watch.unwrap();
+        self.emit("DELETE", serde_json::to_value(watch.clone()).unwrap());
+        Ok(watch)
+    }
+
+    pub fn emit(&self, operation: &str, data: serde_json::Value) {
+        helper::emit_update("Watchlist", operation, Some(data));
+    }
+}
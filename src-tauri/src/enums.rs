@@ -178,6 +178,109 @@ impl<'de> Deserialize<'de> for StockMode {
         })
     }
 }
+
+// Where the price scraper gets its price history from.
+// Auto uses relics.run and falls back to warframe.market when relics.run is down or lagging.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PriceSource {
+    RelicsRun,
+    WarframeMarket,
+    Auto,
+    Unknown(String),
+}
+impl PriceSource {
+    pub fn as_str(&self) -> &str {
+        match *self {
+            PriceSource::RelicsRun => "relics_run",
+            PriceSource::WarframeMarket => "warframe_market",
+            PriceSource::Auto => "auto",
+            PriceSource::Unknown(ref i) => i,
+        }
+    }
+}
+impl Serialize for PriceSource {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let value = match self {
+            PriceSource::RelicsRun => "relics_run",
+            PriceSource::WarframeMarket => "warframe_market",
+            PriceSource::Auto => "auto",
+            PriceSource::Unknown(i) => {
+                logger::critical_file(
+                    "PriceSource",
+                    format!("Unknown PriceSource: {}", i).as_str(),
+                    Some("enums.log"),
+                );
+                "unknown"
+            }
+        };
+        serializer.serialize_str(value)
+    }
+}
+
+impl<'de> Deserialize<'de> for PriceSource {
+    fn deserialize<D>(deserializer: D) -> Result<PriceSource, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s: String = String::deserialize(deserializer)?;
+        Ok(match s.as_str() {
+            "relics_run" => PriceSource::RelicsRun,
+            "warframe_market" => PriceSource::WarframeMarket,
+            "auto" => PriceSource::Auto,
+            s => PriceSource::Unknown(s.to_string()),
+        })
+    }
+}
+// What a user defined EE.log rule does when its pattern matches.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LogRuleAction {
+    SystemNotify,
+    Discord,
+    Frontend,
+    Database,
+    Unknown(String),
+}
+impl Serialize for LogRuleAction {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let value = match self {
+            LogRuleAction::SystemNotify => "system_notify",
+            LogRuleAction::Discord => "discord",
+            LogRuleAction::Frontend => "frontend",
+            LogRuleAction::Database => "database",
+            LogRuleAction::Unknown(i) => {
+                logger::critical_file(
+                    "LogRuleAction",
+                    format!("Unknown LogRuleAction: {}", i).as_str(),
+                    Some("enums.log"),
+                );
+                "unknown"
+            }
+        };
+        serializer.serialize_str(value)
+    }
+}
+
+impl<'de> Deserialize<'de> for LogRuleAction {
+    fn deserialize<D>(deserializer: D) -> Result<LogRuleAction, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s: String = String::deserialize(deserializer)?;
+        Ok(match s.as_str() {
+            "system_notify" => LogRuleAction::SystemNotify,
+            "discord" => LogRuleAction::Discord,
+            "frontend" => LogRuleAction::Frontend,
+            "database" => LogRuleAction::Database,
+            s => LogRuleAction::Unknown(s.to_string()),
+        })
+    }
+}
 #[derive(PartialEq, Debug, Clone)]
 pub enum LogLevel {
     Info,
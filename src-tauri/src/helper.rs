@@ -4,7 +4,7 @@ use eyre::eyre;
 use once_cell::sync::Lazy;
 use polars::{
     lazy::dsl::col,
-    prelude::{DataFrame, Expr, IntoLazy, SortOptions},
+    prelude::{AnyValue, DataFrame, Expr, IntoLazy, SortOptions},
     series::Series,
 };
 use serde_json::{json, Value};
@@ -20,10 +20,11 @@ use zip::{write::FileOptions, CompressionMethod, ZipWriter};
 use crate::{
     error::AppError,
     logger::{self},
-    structs::WarframeLanguage,
     PACKAGEINFO,
 };
 pub static WINDOW: Lazy<Mutex<Option<Window>>> = Lazy::new(|| Mutex::new(None));
+// The folder EE.log and Launcher.log are in, set by the EE log parser
+pub static WARFRAME_FOLDER: Lazy<Mutex<Option<PathBuf>>> = Lazy::new(|| Mutex::new(None));
 
 #[derive(Debug)]
 pub enum ColumnType {
@@ -516,6 +517,30 @@ pub fn merge_dataframes(frames: Vec<DataFrame>) -> Result<DataFrame, AppError> {
     Ok(DataFrame::new(combined_series)
         .map_err(|e| AppError::new("Helper", eyre!(e.to_string())))?)
 }
+/// Converts a DataFrame into a JSON array with one object per row, used to send analysis results to the frontend.
+pub fn dataframe_to_json(df: &DataFrame) -> Result<Value, AppError> {
+    let mut rows: Vec<Value> = vec![json!({}); df.height()];
+    for series in df.get_columns() {
+        for (i, row) in rows.iter_mut().enumerate() {
+            let value = series
+                .get(i)
+                .map_err(|e| AppError::new("Helper", eyre!(e.to_string())))?;
+            row[series.name()] = match value {
+                AnyValue::Null => Value::Null,
+                AnyValue::Boolean(v) => json!(v),
+                AnyValue::Utf8(v) => json!(v),
+                AnyValue::Int32(v) => json!(v),
+                AnyValue::Int64(v) => json!(v),
+                AnyValue::UInt32(v) => json!(v),
+                AnyValue::UInt64(v) => json!(v),
+                AnyValue::Float32(v) => json!(v),
+                AnyValue::Float64(v) => json!(v),
+                v => json!(v.to_string()),
+            };
+        }
+    }
+    Ok(json!(rows))
+}
 /// Returns a vector of strings representing the dates of the last `x` days, including today.
 /// The dates are formatted as "YYYY-MM-DD".
 pub fn last_x_days(x: i64) -> Vec<String> {
@@ -530,6 +555,48 @@ pub fn last_x_days(x: i64) -> Vec<String> {
         .rev()
         .collect()
 }
+// 64 bit FNV-1a hash as hex, used to validate files written by the app itself.
+pub fn fnv1a_hash(data: &[u8]) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in data {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{:016x}", hash)
+}
+// Number of single character edits needed to turn `a` into `b`.
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b_chars: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b_chars.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b_chars.len() + 1];
+        for (j, b_char) in b_chars.iter().enumerate() {
+            let cost = if a_char == *b_char { 0 } else { 1 };
+            current[j + 1] = (previous[j] + cost)
+                .min(previous[j + 1] + 1)
+                .min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b_chars.len()]
+}
+/// Returns up to `limit` candidates closest to `name`, ignoring case.
+/// Candidates that need more edits than a third of the name are left out.
+pub fn fuzzy_suggestions(name: &str, candidates: &[String], limit: usize) -> Vec<String> {
+    let name = name.to_lowercase();
+    let max_distance = (name.chars().count() / 3).max(2);
+    let mut scored = candidates
+        .iter()
+        .map(|c| (levenshtein(&name, &c.to_lowercase()), c))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .collect::<Vec<_>>();
+    scored.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.cmp(b.1)));
+    scored
+        .into_iter()
+        .take(limit)
+        .map(|(_, c)| c.clone())
+        .collect()
+}
 pub fn send_message_to_discord(
     webhook: String,
     title: String,
@@ -648,7 +715,7 @@ pub fn calculate_trade_tax(item_tags: Vec<String>, rank: Option<i64>) -> i64 {
         } else if item_tags.contains(&"legendary".to_string()) {
             let rank_tax = Vec::from([100000, 300000, 600000, 1000000, 1500000, 2100000]);
             let rank = rank.unwrap_or(0);
-            if rank > 0 && rank < 7 {
+            if rank > 0 && rank < 6 {
                 return rank_tax[rank as usize];
             } else {
                 return rank_tax[0];
@@ -671,13 +738,96 @@ pub fn calculate_trade_tax(item_tags: Vec<String>, rank: Option<i64>) -> i64 {
     2000
 }
 
-pub fn get_warframe_language() -> WarframeLanguage {
-    let path = get_app_local_path().join("Warframe").join("Launcher.log");
+/// Converts a trade tax in credits into platinum using the configured credits per platinum.
+pub fn trade_tax_to_plat(credits: i64, credits_per_plat: i64) -> f64 {
+    if credits_per_plat <= 0 {
+        return 0.0;
+    }
+    credits as f64 / credits_per_plat as f64
+}
+
+/// DataFrames store a missing sub type as an empty string, this turns it back into an Option.
+pub fn sub_type_from_str(sub_type: &str) -> Option<String> {
+    if sub_type.is_empty() {
+        None
+    } else {
+        Some(sub_type.to_string())
+    }
+}
+
+// Returns the folders Warframe may write its logs to, the native Windows folder first.
+// On Linux the game runs through Proton or Wine, so the folder is inside a prefix.
+pub fn get_warframe_folder_candidates() -> Vec<PathBuf> {
+    let mut candidates = vec![get_app_local_path().join("Warframe")];
+    let home = match BaseDirs::new() {
+        Some(base_dirs) => base_dirs.home_dir().to_path_buf(),
+        None => return candidates,
+    };
+
+    // Steam Proton prefixes, 230410 is the Steam app id of Warframe
+    let mut prefixes = vec![];
+    for steam in [
+        ".steam/steam",
+        ".local/share/Steam",
+        ".var/app/com.valvesoftware.Steam/.local/share/Steam",
+    ] {
+        prefixes.push(home.join(steam).join("steamapps/compatdata/230410/pfx"));
+    }
+    // Wine and Lutris prefixes
+    prefixes.push(home.join(".wine"));
+    prefixes.push(home.join("Games/warframe"));
+    prefixes.push(home.join("Games/warframe/prefix"));
+
+    // The user folder in a prefix is named after the user, so every user is checked
+    for prefix in prefixes {
+        let users = match fs::read_dir(prefix.join("drive_c/users")) {
+            Ok(users) => users,
+            Err(_) => continue,
+        };
+        for user in users.flatten() {
+            candidates.push(user.path().join("AppData/Local/Warframe"));
+        }
+    }
+    candidates
+}
+
+// Finds the Warframe log folder.
+// A path from the settings is used as is and may point at EE.log or its folder, otherwise the first candidate with an EE.log is used.
+pub fn find_warframe_folder(custom_path: &str) -> PathBuf {
+    if !custom_path.is_empty() {
+        let path = PathBuf::from(custom_path);
+        if path.is_file() || path.extension().is_some_and(|e| e == "log") {
+            return path.parent().map(|p| p.to_path_buf()).unwrap_or(path);
+        }
+        return path;
+    }
+    let candidates = get_warframe_folder_candidates();
+    candidates
+        .iter()
+        .find(|folder| folder.join("EE.log").exists())
+        .unwrap_or(&candidates[0])
+        .clone()
+}
+
+pub fn get_warframe_folder() -> PathBuf {
+    match WARFRAME_FOLDER.lock().unwrap().clone() {
+        Some(folder) => folder,
+        None => get_app_local_path().join("Warframe"),
+    }
+}
+
+pub fn set_warframe_folder(folder: PathBuf) {
+    *WARFRAME_FOLDER.lock().unwrap() = Some(folder);
+}
+
+// Returns the two letter language code Warframe was last started with, "en" when it can not be found.
+pub fn get_warframe_language_code() -> String {
+    let path = get_warframe_folder().join("Launcher.log");
 
     let log_file = "get_warframe_language.log";
 
     if !path.exists() {
-        return WarframeLanguage::English;
+        return "en".to_string();
     }
 
     let file_result = fs::File::open(&path);
@@ -690,7 +840,7 @@ pub fn get_warframe_language() -> WarframeLanguage {
 
                 // Ensure lang_code is exactly two characters
                 if lang_code.len() == 2 {
-                    return WarframeLanguage::from_str(lang_code);
+                    return lang_code.to_string();
                 } else {
                     logger::info_con(
                         "Helper",
@@ -733,7 +883,7 @@ pub fn get_warframe_language() -> WarframeLanguage {
     }
 
     // Default to English in case of any error
-    WarframeLanguage::English
+    "en".to_string()
 }
 
 pub fn validate_json(json: &Value, required: &Value, path: &str) -> (Value, Vec<String>) {
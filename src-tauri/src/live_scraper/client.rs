@@ -1,4 +1,6 @@
 use std::{
+    collections::HashMap,
+    path::PathBuf,
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc, Mutex,
@@ -6,6 +8,7 @@ use std::{
     time::Duration,
 };
 
+use eyre::eyre;
 use serde_json::json;
 
 use crate::{
@@ -21,7 +24,10 @@ use crate::{
     wfm_client::client::WFMClient,
 };
 
-use super::modules::{item::ItemModule, riven::RivenModule};
+use super::modules::{
+    auction::AuctionModule, ducat::DucatModule, indicator::IndicatorModule, item::ItemModule,
+    rank_up::RankUpModule, riven::RivenModule, set::SetModule, watchlist::WatchlistModule,
+};
 
 #[derive(Clone)]
 pub struct LiveScraperClient {
@@ -33,6 +39,14 @@ pub struct LiveScraperClient {
     pub auth: Arc<Mutex<AuthState>>,
     pub db: Arc<Mutex<DBClient>>,
     pub mh: Arc<Mutex<MonitorHandler>>,
+    // Items the ducat module has buy orders for
+    pub ducat_orders: Arc<Mutex<Vec<String>>>,
+    // Items the rank up module has unranked buy orders for
+    pub rank_up_orders: Arc<Mutex<Vec<String>>>,
+    // Watchlist id -> unix time of the last check
+    pub watchlist_checked: Arc<Mutex<HashMap<i64, i64>>>,
+    // Orders hidden while the game is closed, the item modules leave the orders alone until they are shown again
+    pub hidden_orders: Arc<Mutex<Vec<String>>>,
 }
 
 impl LiveScraperClient {
@@ -53,8 +67,48 @@ impl LiveScraperClient {
             auth,
             db,
             mh,
+            ducat_orders: Arc::new(Mutex::new(DucatModule::load_orders())),
+            rank_up_orders: Arc::new(Mutex::new(RankUpModule::load_orders())),
+            watchlist_checked: Arc::new(Mutex::new(HashMap::new())),
+            hidden_orders: Arc::new(Mutex::new(Self::load_hidden_orders())),
+        }
+    }
+    fn get_hidden_orders_path() -> PathBuf {
+        helper::get_app_roaming_path().join("hidden_orders.json")
+    }
+    // The ids of the orders hidden while the game is closed, kept in a file so they are shown again after a restart.
+    fn load_hidden_orders() -> Vec<String> {
+        let content = match std::fs::read(Self::get_hidden_orders_path()) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return vec![],
+            Err(e) => {
+                logger::warning_con(
+                    "LiveScraper",
+                    format!("Could not read the hidden orders: {}", e).as_str(),
+                );
+                return vec![];
+            }
+        };
+        match serde_json::from_slice(&content) {
+            Ok(ids) => ids,
+            Err(e) => {
+                logger::warning_con(
+                    "LiveScraper",
+                    format!("Could not parse the hidden orders: {}", e).as_str(),
+                );
+                vec![]
+            }
         }
     }
+    pub fn set_hidden_orders(&self, ids: Vec<String>) -> Result<(), AppError> {
+        std::fs::write(Self::get_hidden_orders_path(), json!(ids).to_string())
+            .map_err(|e| AppError::new("LiveScraper", eyre!(e.to_string())))?;
+        *self.hidden_orders.lock()? = ids;
+        Ok(())
+    }
+    pub fn orders_hidden(&self) -> bool {
+        !self.hidden_orders.lock().unwrap().is_empty()
+    }
     fn report_error(&self, error: AppError) {
         let component = error.component();
         let cause = error.cause();
@@ -101,6 +155,8 @@ impl LiveScraperClient {
 
             scraper.send_message("riven.reset", None);
             db.stock_riven().reset_listed_price().await.unwrap();
+            scraper.send_message("auction.reset", None);
+            db.stock_auction().reset_listed_price().await.unwrap();
             scraper.send_message("item.reset", None);
             db.stock_item().reset_listed_price().await.unwrap();
             scraper
@@ -108,8 +164,19 @@ impl LiveScraperClient {
                 .delete_all_orders(OrderMode::Both)
                 .await
                 .unwrap();
+            let mut orders_hidden = false;
             while is_running.load(Ordering::SeqCst) && forced_stop.load(Ordering::SeqCst) {
                 let settings = scraper.settings.lock().unwrap().clone();
+                // Posting or updating an order would show it again while the game is closed
+                if scraper.orders_hidden() != orders_hidden {
+                    orders_hidden = !orders_hidden;
+                    if orders_hidden {
+                        logger::info_con(
+                            "LiveScraper",
+                            "The orders are hidden while the game is closed, item stock is not checked",
+                        );
+                    }
+                }
                 if settings.live_scraper.stock_mode == StockMode::Riven
                     || settings.live_scraper.stock_mode == StockMode::All
                 {
@@ -119,10 +186,17 @@ impl LiveScraperClient {
                         Ok(_) => {}
                         Err(e) => scraper.report_error(e),
                     }
+                    logger::info_con("LiveScraper", "Checking auction stock");
+                    scraper.send_message("auction.starting", None);
+                    match scraper.auction().check_stock().await {
+                        Ok(_) => {}
+                        Err(e) => scraper.report_error(e),
+                    }
                 }
 
-                if settings.live_scraper.stock_mode == StockMode::Item
-                    || settings.live_scraper.stock_mode == StockMode::All
+                if !orders_hidden
+                    && (settings.live_scraper.stock_mode == StockMode::Item
+                        || settings.live_scraper.stock_mode == StockMode::All)
                 {
                     logger::info_con("LiveScraper", "Checking item stock");
                     scraper.send_message("riven.starting", None);
@@ -130,6 +204,22 @@ impl LiveScraperClient {
                         Ok(_) => {}
                         Err(e) => scraper.report_error(e),
                     }
+                    match scraper.set().check_stock().await {
+                        Ok(_) => {}
+                        Err(e) => scraper.report_error(e),
+                    }
+                    match scraper.ducat().check_stock().await {
+                        Ok(_) => {}
+                        Err(e) => scraper.report_error(e),
+                    }
+                    match scraper.rank_up().check_stock().await {
+                        Ok(_) => {}
+                        Err(e) => scraper.report_error(e),
+                    }
+                }
+                match scraper.watchlist().check_watchlist().await {
+                    Ok(_) => {}
+                    Err(e) => scraper.report_watchlist_error(e),
                 }
                 tokio::time::sleep(Duration::from_secs(1)).await;
             }
@@ -138,12 +228,54 @@ impl LiveScraperClient {
         });
         Ok(())
     }
+    // Checks the watchlist while the live scraper is stopped, the live scraper loop checks it while running.
+    pub fn start_watchlist_loop(&self) {
+        let scraper = self.clone();
+        tauri::async_runtime::spawn(async move {
+            // Wait first so the database is initialized before the first check
+            loop {
+                tokio::time::sleep(Duration::from_secs(30)).await;
+                if !scraper.is_running() {
+                    match scraper.watchlist().check_watchlist().await {
+                        Ok(_) => {}
+                        Err(e) => scraper.report_watchlist_error(e),
+                    }
+                }
+            }
+        });
+    }
+    // A failing watchlist check is logged but never stops the live scraper
+    fn report_watchlist_error(&self, error: AppError) {
+        crate::error::create_log_file(self.log_file.to_owned(), &error);
+        logger::warning_con(
+            "Watchlist",
+            format!("Error checking watchlist: {}", error.cause()).as_str(),
+        );
+    }
     pub fn item(&self) -> ItemModule {
         ItemModule { client: self }
     }
     pub fn riven(&self) -> RivenModule {
         RivenModule { client: self }
     }
+    pub fn auction(&self) -> AuctionModule<'_> {
+        AuctionModule { client: self }
+    }
+    pub fn set(&self) -> SetModule<'_> {
+        SetModule { client: self }
+    }
+    pub fn ducat(&self) -> DucatModule<'_> {
+        DucatModule { client: self }
+    }
+    pub fn rank_up(&self) -> RankUpModule<'_> {
+        RankUpModule { client: self }
+    }
+    pub fn indicator(&self) -> IndicatorModule<'_> {
+        IndicatorModule { client: self }
+    }
+    pub fn watchlist(&self) -> WatchlistModule<'_> {
+        WatchlistModule { client: self }
+    }
 
     pub fn send_message(&self, i18n_key: &str, data: Option<serde_json::Value>) {
         helper::send_message_to_window(
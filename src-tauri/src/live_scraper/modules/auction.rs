@@ -0,0 +1,266 @@
+use serde_json::json;
+
+use crate::{
+    error::AppError, live_scraper::client::LiveScraperClient, logger, structs::AuctionItem,
+};
+pub struct AuctionModule<'a> {
+    pub client: &'a LiveScraperClient,
+}
+
+impl<'a> AuctionModule<'a> {
+    pub async fn check_stock(&self) -> Result<(), AppError> {
+        let db = self.client.db.lock()?.clone();
+        let wfm = self.client.wfm.lock()?.clone();
+        let auth = self.client.auth.lock()?.clone();
+        let settings = self.client.settings.lock()?.clone().live_scraper;
+        logger::info_con("AuctionModule", "Run auction module");
+        let stock_auctions = db.stock_auction().get_auctions().await?;
+        // The auctions of blocked players do not count as competing prices
+        let blocked_names = db.trade_partner().get_blocked_names().await?;
+        let my_auctions = wfm.auction().get_my_auctions().await?;
+        let my_auctions = my_auctions
+            .iter()
+            .filter(|a| a.item.item_type == "lich" || a.item.item_type == "sister")
+            .collect::<Vec<_>>();
+        let mut current_index = stock_auctions.len();
+        let total = stock_auctions.len();
+        for stock in stock_auctions {
+            current_index -= 1;
+            self.client.send_message(
+                "auction.checking",
+                Some(json!({ "name": stock.weapon_name, "count": current_index, "total": total})),
+            );
+            // Check if client is running
+            if !self.client.is_running() {
+                break;
+            }
+
+            // Find my auction for this weapon if exists
+            let auction = my_auctions
+                .iter()
+                .find(|a| a.id == stock.order_id.clone().unwrap_or("".to_string()));
+
+            // Check if auction is private
+            if stock.private {
+                if let Some(auction) = auction {
+                    self.client
+                        .send_message("auction.deleting", Some(json!({ "name": stock.weapon_url})));
+                    wfm.auction().delete(auction.id.as_str()).await?;
+                }
+
+                // Update database status to inactive
+                if stock.status != "inactive" {
+                    db.stock_auction()
+                        .update_by_id(
+                            stock.id,
+                            Some("".to_string()),
+                            None,
+                            None,
+                            None,
+                            None,
+                            Some("inactive".to_string()),
+                            None,
+                        )
+                        .await?;
+                }
+                continue;
+            }
+
+            // Match element, damage and ephemera
+            let match_auction = stock.match_auction.0.clone();
+            let element = match_auction.element.clone();
+            let mut damage_min: Option<i64> = None;
+            let mut damage_max: Option<i64> = None;
+            if match_auction.damage.is_some() {
+                let damage = match_auction.damage.clone().unwrap();
+                damage_min = Some(damage.min);
+                damage_max = Some(damage.max);
+            }
+
+            // Search for live auctions for this weapon
+            self.client.send_message(
+                "auction.searching",
+                Some(json!({ "name": stock.weapon_url})),
+            );
+            let live_auctions = wfm
+                .auction()
+                .search_weapon(
+                    &stock.auction_type,
+                    &stock.weapon_url,
+                    element.as_deref(),
+                    match_auction.having_ephemera,
+                    damage_min,
+                    damage_max,
+                    None,
+                    Some("direct"),
+                    Some("price_asc"),
+                )
+                .await?;
+
+            // Filter auctions that are not mine or from a blocked player and are not closed and are player is ingame
+            let live_auctions = live_auctions
+                .iter()
+                .filter(|a| {
+                    a.owner.ingame_name != auth.ingame_name
+                        && !blocked_names.contains(&a.owner.ingame_name)
+                        && !a.closed
+                        && a.visible
+                        && a.owner.status == "ingame"
+                })
+                .collect::<Vec<_>>();
+
+            if live_auctions.is_empty() {
+                logger::info_con(
+                    "AuctionModule",
+                    format!("No live auctions for {}", stock.weapon_url).as_str(),
+                );
+                self.client.send_message(
+                    "auction.no_offers",
+                    Some(json!({ "name": stock.weapon_url})),
+                );
+                db.stock_auction()
+                    .update_by_id(
+                        stock.id,
+                        None,
+                        None,
+                        None,
+                        None,
+                        None,
+                        Some("no_offers".to_string()),
+                        None,
+                    )
+                    .await?;
+                continue;
+            }
+
+            // Get the minimum price of the weapon.
+            let minimum_price = stock.minium_price;
+
+            // Get the lowest price
+            let lowest_price = live_auctions[0].starting_price;
+
+            // The new price of the weapon
+            let mut post_price = lowest_price;
+
+            if let Some(minimum_price) = minimum_price {
+                post_price = post_price.max(minimum_price as i64);
+            }
+
+            // Calculate profit of the weapon
+            let profit = post_price as f64 - stock.price;
+
+            logger::debug_con(
+                "AuctionModule",
+                format!(
+                    "Lowest price for {} is {} and yours is {} and profit is {}",
+                    stock.weapon_url, lowest_price, stock.price, profit
+                )
+                .as_str(),
+            );
+
+            // Check if profit is greater than the range threshold
+            let mut order_id: Option<String> = None;
+            if profit > settings.stock_auction.range_threshold as f64 {
+                match auction {
+                    Some(auction) => {
+                        if auction.starting_price != post_price as i64 {
+                            self.client.send_message(
+                                "auction.updating",
+                                Some(json!({ "name": stock.weapon_url, "price": post_price})),
+                            );
+                            wfm.auction()
+                                .update(
+                                    auction.id.as_str(),
+                                    post_price as i32,
+                                    0,
+                                    "",
+                                    post_price as i32,
+                                    true,
+                                )
+                                .await?;
+                        }
+                    }
+                    None => {
+                        self.client.send_message(
+                            "auction.creating",
+                            Some(json!({ "name": stock.weapon_url, "price": post_price})),
+                        );
+                        let new_aut = wfm
+                            .auction()
+                            .create(
+                                &stock.auction_type,
+                                "",
+                                post_price,
+                                post_price,
+                                0,
+                                1,
+                                false,
+                                AuctionItem {
+                                    item_type: stock.auction_type.clone(),
+                                    weapon_url_name: Some(stock.weapon_url.clone()),
+                                    re_rolls: None,
+                                    attributes: None,
+                                    name: stock.name.clone(),
+                                    mod_rank: None,
+                                    polarity: None,
+                                    mastery_level: None,
+                                    element: Some(stock.element.clone()),
+                                    quirk: stock.quirk.clone(),
+                                    having_ephemera: Some(stock.having_ephemera),
+                                    damage: Some(stock.damage as i64),
+                                },
+                            )
+                            .await?;
+                        order_id = Some(new_aut.id);
+                    }
+                }
+                // Update database status to live
+                if post_price != stock.listed_price.unwrap_or(0) as i64
+                    || stock.status != "live"
+                    || order_id.is_some()
+                {
+                    db.stock_auction()
+                        .update_by_id(
+                            stock.id,
+                            order_id,
+                            None,
+                            Some(post_price as i32),
+                            None,
+                            None,
+                            Some("live".to_string()),
+                            None,
+                        )
+                        .await?;
+                }
+            } else {
+                if let Some(auction) = auction.filter(|a| a.visible) {
+                    wfm.auction()
+                        .update(
+                            auction.id.as_str(),
+                            (stock.price + 50.0) as i32,
+                            0,
+                            "",
+                            (stock.price + 50.0) as i32,
+                            false,
+                        )
+                        .await?;
+                }
+                if stock.listed_price.is_some() || stock.status != "to_low_profit" {
+                    db.stock_auction()
+                        .update_by_id(
+                            stock.id,
+                            None,
+                            None,
+                            None,
+                            None,
+                            None,
+                            Some("to_low_profit".to_string()),
+                            None,
+                        )
+                        .await?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,281 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use eyre::eyre;
+use polars::prelude::*;
+use serde_json::json;
+
+use crate::{
+    error::AppError,
+    helper::{self, ColumnType, ColumnValue, ColumnValues},
+    live_scraper::client::LiveScraperClient,
+    logger,
+};
+
+pub struct DucatModule<'a> {
+    pub client: &'a LiveScraperClient,
+}
+
+impl<'a> DucatModule<'a> {
+    fn get_file_path() -> PathBuf {
+        helper::get_app_roaming_path().join("ducat_orders.json")
+    }
+
+    // The items we have ducat buy orders for, kept in a file so the orders are still known after a restart.
+    pub fn load_orders() -> Vec<String> {
+        let content = match std::fs::read(Self::get_file_path()) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return vec![],
+            Err(e) => {
+                logger::warning_con(
+                    "DucatModule",
+                    format!("Could not read the ducat orders: {}", e).as_str(),
+                );
+                return vec![];
+            }
+        };
+        match serde_json::from_slice(&content) {
+            Ok(names) => names,
+            Err(e) => {
+                logger::warning_con(
+                    "DucatModule",
+                    format!("Could not parse the ducat orders: {}", e).as_str(),
+                );
+                vec![]
+            }
+        }
+    }
+
+    fn set_orders(&self, names: Vec<String>) -> Result<(), AppError> {
+        std::fs::write(Self::get_file_path(), json!(names).to_string())
+            .map_err(|e| AppError::new("DucatModule", eyre!(e.to_string())))?;
+        *self.client.ducat_orders.lock()? = names;
+        Ok(())
+    }
+
+    // Ranks all items with a ducat value by how much platinum one ducat costs, cheapest first.
+    pub async fn get_ducat_ranking(&self) -> Result<DataFrame, AppError> {
+        let cache = self.client.db.lock()?.clone().cache.lock()?.clone();
+        let price_scraper = self.client.price_scraper.lock()?.clone();
+        let df = price_scraper.get_price_historys(None).await?;
+        let ducats: HashMap<String, i64> = cache
+            .items()
+            .get_types()?
+            .iter()
+            .filter_map(|i| i.ducats.map(|d| (i.url_name.clone(), d)))
+            .filter(|(_, d)| *d > 0)
+            .collect();
+
+        let ducat_names = Series::new("ducat_names", ducats.keys().cloned().collect::<Vec<_>>());
+        let averaged_df = df
+            .lazy()
+            .filter(
+                col("order_type")
+                    .eq(lit("closed"))
+                    .and(col("name").is_in(lit(ducat_names))),
+            )
+            .groupby(["name"])
+            .agg(&[
+                col("volume").mean().alias("volume"),
+                col("avg_price").mean().alias("avg_price"),
+                col("median").mean().alias("median"),
+                col("item_id").first().alias("item_id"),
+            ])
+            .collect()
+            .map_err(|e| AppError::new("DucatModule", eyre!(e.to_string())))?;
+
+        let names =
+            match helper::get_column_values(averaged_df.clone(), None, "name", ColumnType::String)?
+            {
+                ColumnValues::String(values) => values,
+                _ => {
+                    return Err(AppError::new(
+                        "DucatModule",
+                        eyre!("Expected string values"),
+                    ))
+                }
+            };
+        let ducat_values = names
+            .iter()
+            .map(|n| ducats.get(n).cloned().unwrap_or(0))
+            .collect::<Vec<_>>();
+
+        let df = averaged_df
+            .clone()
+            .with_column(Series::new("ducats", ducat_values))
+            .cloned()
+            .map_err(|e| AppError::new("DucatModule", eyre!(e.to_string())))?
+            .lazy()
+            .with_column(
+                (col("avg_price") / col("ducats").cast(DataType::Float64)).alias("platPerDucat"),
+            )
+            .collect()
+            .map_err(|e| AppError::new("DucatModule", eyre!(e.to_string())))?;
+        helper::sort_dataframe(df, "platPerDucat", false)
+    }
+
+    pub async fn check_stock(&self) -> Result<(), AppError> {
+        let settings = self.client.settings.lock()?.clone().live_scraper;
+        if !settings.stock_ducat.enabled {
+            return Ok(());
+        }
+        logger::info_con("DucatModule", "Run ducat module");
+        let wfm = self.client.wfm.lock()?.clone();
+        let max_ratio = settings.stock_ducat.max_plat_per_ducat;
+        let blacklist = settings.stock_item.blacklist.clone();
+
+        // Parts that trade under the ratio, best ratio first
+        let ranking = self
+            .get_ducat_ranking()
+            .await?
+            .lazy()
+            .filter(
+                col("platPerDucat")
+                    .lt_eq(lit(max_ratio))
+                    .and(col("volume").gt_eq(lit(settings.stock_ducat.min_volume))),
+            )
+            .limit(settings.stock_ducat.max_orders as u32)
+            .collect()
+            .map_err(|e| AppError::new("DucatModule", eyre!(e.to_string())))?;
+        let names =
+            match helper::get_column_values(ranking.clone(), None, "name", ColumnType::String)? {
+                ColumnValues::String(values) => values,
+                _ => {
+                    return Err(AppError::new(
+                        "DucatModule",
+                        eyre!("Expected string values"),
+                    ))
+                }
+            };
+        let names = names
+            .into_iter()
+            .filter(|n| !blacklist.contains(n))
+            .collect::<Vec<_>>();
+
+        let my_buy_orders = wfm.orders().get_my_orders().await?.buy_orders;
+
+        // Remove the buy orders for parts that are no longer cheap enough
+        let previous = self.client.ducat_orders.lock()?.clone();
+        for url_name in previous.iter().filter(|n| !names.contains(n)) {
+            let order = my_buy_orders
+                .iter()
+                .find(|o| o.item.as_ref().map(|i| &i.url_name) == Some(url_name));
+            if let Some(order) = order {
+                self.client
+                    .send_message("ducat.buy.deleting", Some(json!({ "name": url_name})));
+                wfm.orders().delete(&order.id).await?;
+            }
+        }
+
+        let total = names.len();
+        for (index, url_name) in names.iter().enumerate() {
+            if !self.client.is_running() {
+                break;
+            }
+            self.client.send_message(
+                "ducat.checking",
+                Some(json!({ "name": url_name, "count": index + 1, "total": total})),
+            );
+            let ducats = match helper::get_column_value(
+                ranking.clone(),
+                Some(col("name").eq(lit(url_name.clone()))),
+                "ducats",
+                ColumnType::I64,
+            )? {
+                ColumnValue::I64(value) => value.unwrap_or(0),
+                _ => return Err(AppError::new("DucatModule", eyre!("Expected i64 values"))),
+            };
+            let item_id = match helper::get_column_value(
+                ranking.clone(),
+                Some(col("name").eq(lit(url_name.clone()))),
+                "item_id",
+                ColumnType::String,
+            )? {
+                ColumnValue::String(value) => value.unwrap_or("".to_string()),
+                _ => {
+                    return Err(AppError::new(
+                        "DucatModule",
+                        eyre!("Expected string values"),
+                    ))
+                }
+            };
+
+            // The most we are willing to pay for this part
+            let max_price = (ducats as f64 * max_ratio).floor() as i64;
+            if max_price < 1 {
+                continue;
+            }
+
+            // Outbid the highest buyer, but never above the ratio
+            let live_orders_df = wfm.orders().get_ordres_by_item(url_name).await?;
+            let mut post_price = max_price;
+            if live_orders_df.height() > 0 {
+                let (buy_orders_df, _sell_orders_df, buyers, _sellers, _range) = self
+                    .client
+                    .item()
+                    .restructure_live_order_df(&live_orders_df)
+                    .await?;
+                if buyers > 0 {
+                    let highest_buy = match helper::get_column_value(
+                        buy_orders_df,
+                        None,
+                        "platinum",
+                        ColumnType::I64,
+                    )? {
+                        ColumnValue::I64(value) => value.unwrap_or(0),
+                        _ => {
+                            return Err(AppError::new("DucatModule", eyre!("Expected i64 values")))
+                        }
+                    };
+                    post_price = (highest_buy + 1).min(max_price);
+                }
+            }
+
+            let order = my_buy_orders
+                .iter()
+                .find(|o| o.item.as_ref().map(|i| &i.url_name) == Some(url_name));
+            match order {
+                Some(order) => {
+                    if order.platinum != post_price {
+                        self.client.send_message(
+                            "ducat.buy.updating",
+                            Some(json!({ "name": url_name, "price": post_price})),
+                        );
+                        wfm.orders()
+                            .update(
+                                &order.id,
+                                post_price as i32,
+                                settings.stock_ducat.quantity as i32,
+                                true,
+                            )
+                            .await?;
+                    }
+                }
+                None => {
+                    self.client.send_message(
+                        "ducat.buy.creating",
+                        Some(json!({ "name": url_name, "price": post_price})),
+                    );
+                    wfm.orders()
+                        .create(
+                            &item_id,
+                            "buy",
+                            post_price,
+                            settings.stock_ducat.quantity,
+                            true,
+                            None,
+                            None,
+                        )
+                        .await?;
+                    // Saved right away, a later failure would otherwise leave this order unknown
+                    let mut orders = self.client.ducat_orders.lock()?.clone();
+                    if !orders.contains(url_name) {
+                        orders.push(url_name.clone());
+                        self.set_orders(orders)?;
+                    }
+                }
+            }
+        }
+        self.set_orders(names)?;
+        Ok(())
+    }
+}
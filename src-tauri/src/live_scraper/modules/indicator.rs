@@ -0,0 +1,100 @@
+use eyre::eyre;
+use polars::prelude::*;
+
+use crate::{error::AppError, live_scraper::client::LiveScraperClient};
+
+pub struct IndicatorModule<'a> {
+    pub client: &'a LiveScraperClient,
+}
+
+impl<'a> IndicatorModule<'a> {
+    // Loads the price history window from the settings and computes the indicators for it.
+    pub async fn get_indicators(&self) -> Result<DataFrame, AppError> {
+        let settings = self.client.settings.lock()?.clone().live_scraper.indicators;
+        let price_scraper = self.client.price_scraper.lock()?.clone();
+        let df = price_scraper
+            .get_price_historys(Some(settings.window_days))
+            .await?;
+        self.compute(&df, settings.short_window)
+    }
+
+    // Computes the indicators per name, sub type and rank from the closed rows of a price history DataFrame.
+    // Percentages are in percent, `short_window` is the number of newest days used for the short average.
+    //  maShort      mean median of the newest `short_window` days
+    //  maLong       mean median of the whole window
+    //  volatility   standard deviation of the median relative to its mean
+    //  volumeTrend  mean volume of the newest days relative to the whole window
+    //  momentum     change of the median over the newest days
+    //  liquidity    mean daily volume weighted by the share of the window days with trades
+    //  spread       mean difference between the average price and the median
+    pub fn compute(&self, df: &DataFrame, short_window: i64) -> Result<DataFrame, AppError> {
+        let short_window = short_window.max(2) as usize;
+        let window_days = df
+            .column("datetime")
+            .and_then(|c| c.n_unique())
+            .map_err(|e| AppError::new("IndicatorModule", eyre!(e.to_string())))?
+            .max(1);
+        let median_sorted = || col("median").sort_by([col("datetime")], [false]);
+        let volume_sorted = || col("volume").sort_by([col("datetime")], [false]);
+
+        let indicators = df
+            .clone()
+            .lazy()
+            .filter(
+                col("order_type")
+                    .eq(lit("closed"))
+                    .and(col("median").gt(lit(0.0))),
+            )
+            .with_column(
+                ((col("avg_price") - col("median")) / col("median") * lit(100.0)).alias("spread"),
+            )
+            .groupby(["name", "sub_type", "mod_rank"])
+            .agg(&[
+                median_sorted()
+                    .tail(Some(short_window))
+                    .mean()
+                    .alias("maShort"),
+                col("median").mean().alias("maLong"),
+                (col("median").std(1) / col("median").mean() * lit(100.0)).alias("volatility"),
+                volume_sorted()
+                    .tail(Some(short_window))
+                    .cast(DataType::Float64)
+                    .mean()
+                    .alias("recentVolume"),
+                col("volume")
+                    .cast(DataType::Float64)
+                    .mean()
+                    .alias("meanVolume"),
+                median_sorted()
+                    .tail(Some(short_window))
+                    .first()
+                    .alias("firstMedian"),
+                median_sorted().last().alias("lastMedian"),
+                (col("volume").gt(lit(0)).cast(DataType::Float64).sum() / lit(window_days as f64))
+                    .alias("activeDays"),
+                col("spread").mean().alias("spread"),
+            ])
+            .with_columns([
+                ((col("recentVolume") - col("meanVolume")) / col("meanVolume") * lit(100.0))
+                    .alias("volumeTrend"),
+                ((col("lastMedian") - col("firstMedian")) / col("firstMedian") * lit(100.0))
+                    .alias("momentum"),
+                (col("meanVolume") * col("activeDays")).alias("liquidity"),
+            ])
+            .select(&[
+                col("name"),
+                col("sub_type"),
+                col("mod_rank"),
+                col("maShort"),
+                col("maLong"),
+                col("volatility").fill_null(lit(0.0)),
+                col("volumeTrend").fill_nan(lit(0.0)),
+                col("momentum"),
+                col("liquidity"),
+                col("spread"),
+            ])
+            .collect()
+            .map_err(|e| AppError::new("IndicatorModule", eyre!(e.to_string())))?;
+        Ok(indicators)
+    }
+}
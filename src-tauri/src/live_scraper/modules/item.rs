@@ -10,9 +10,12 @@ use crate::{
 use eyre::eyre;
 use polars::prelude::*;
 use serde_json::json;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::vec;
 
+// The (url_name, sub_type, rank) key of an item, rank is None for items without ranks
+type ItemKey = (String, Option<String>, Option<i64>);
+
 pub struct ItemModule<'a> {
     pub client: &'a LiveScraperClient,
 }
@@ -27,14 +30,16 @@ impl<'a> ItemModule<'a> {
 
         let wfm = self.client.wfm.lock()?.clone();
 
-        // List of strings that will be checked
-        let mut stock_items: Vec<String> = vec![];
+        // List of (url_name, sub_type, rank) that will be checked, rank is None for items without ranks
+        let mut stock_items: Vec<(String, Option<String>, Option<i64>)> = vec![];
         let mut stock_items_df = DataFrame::new(vec![
             Series::new("item_url", &[] as &[&str]),
+            Series::new("sub_type", &[] as &[&str]),
+            Series::new("rank", &[] as &[i32]),
             Series::new("owned", &[] as &[i32]),
         ])
         .unwrap();
-        let mut popular_items: Vec<String> = vec![];
+        let mut popular_items: Vec<(String, Option<String>, Option<i64>)> = vec![];
         let popular_items_df = self.get_buy_sell_overlap().await?;
         let whitelist_items: Vec<String> = settings.stock_item.whitelist.clone();
 
@@ -82,41 +87,60 @@ impl<'a> ItemModule<'a> {
                 .stock_item()
                 .convet_stock_item_to_datafream(db.stock_item().get_items().await?)
                 .unwrap();
-            stock_items.append(&mut db.stock_item().get_items_names().await?.clone());
+            let cache = db.cache.lock()?.clone();
+            for (url, sub_type, rank) in db.stock_item().get_items_keys().await? {
+                // Only mods and arcanes are listed with a rank
+                let rankable = match cache.items().find_type(&url)? {
+                    Some(item) => item.mod_max_rank.is_some(),
+                    None => false,
+                };
+                stock_items.push((url, sub_type, if rankable { Some(rank) } else { None }));
+            }
         }
 
+        let popular_keys = self.get_item_keys(&popular_items_df)?;
+
         // Get the items names from the database based on order_mode
         if order_mode == OrderMode::Buy || order_mode == OrderMode::Both {
-            let mut items: Vec<String> = match helper::get_column_values(
-                popular_items_df.clone(),
-                None,
-                "name",
-                ColumnType::String,
-            )? {
-                ColumnValues::String(values) => values,
-                _ => return Err(AppError::new("LiveScraper", eyre!("Expected f64 values"))),
-            };
-
-            popular_items.append(&mut items);
+            let names: Vec<String> = popular_keys
+                .iter()
+                .map(|(name, _, _)| name.clone())
+                .collect();
+            popular_items.append(&mut popular_keys.clone());
 
             if current_buy_orders_df.shape().0 != 0 {
                 current_buy_orders_df = current_buy_orders_df
                     .lazy()
-                    .filter(
-                        col("url_name")
-                            .is_in(lit(Series::new("interesting_items", popular_items.clone()))),
-                    )
+                    .filter(col("url_name").is_in(lit(Series::new("interesting_items", names))))
                     .collect()
                     .map_err(|e| AppError::new("LiveScraper", eyre!(e.to_string())))?;
 
+                // Match the buy orders on the rank too, "rank_key" is only used for the join
+                let with_rank_key = |df: DataFrame| {
+                    df.lazy()
+                        .with_column(
+                            col("mod_rank")
+                                .cast(DataType::Float64)
+                                .fill_null(lit(-1.0))
+                                .alias("rank_key"),
+                        )
+                        .collect()
+                        .map_err(|e| AppError::new("LiveScraper", eyre!(e.to_string())))
+                };
                 let order_buy_df = helper::filter_and_extract(
-                    popular_items_df.clone(),
+                    with_rank_key(popular_items_df.clone())?,
                     None,
-                    vec!["name", "closedAvg"],
+                    vec!["name", "sub_type", "rank_key", "closedAvg"],
                 )?;
 
-                current_buy_orders_df = current_buy_orders_df
-                    .inner_join(&order_buy_df, ["url_name"], ["name"])
+                current_buy_orders_df = with_rank_key(current_buy_orders_df)?
+                    .inner_join(
+                        &order_buy_df,
+                        ["url_name", "sub_type", "rank_key"],
+                        ["name", "sub_type", "rank_key"],
+                    )
+                    .map_err(|e| AppError::new("LiveScraper", eyre!(e.to_string())))?
+                    .drop("rank_key")
                     .map_err(|e| AppError::new("LiveScraper", eyre!(e.to_string())))?;
 
                 current_buy_orders_df = current_buy_orders_df
@@ -130,17 +154,50 @@ impl<'a> ItemModule<'a> {
             }
         }
 
+        // A whitelisted item is checked with the ranks and subtypes it has price history for,
+        // otherwise at rank 0 for mods and arcanes and with every subtype it can have
+        let cache = db.cache.lock()?.clone();
+        let mut whitelist_keys: Vec<(String, Option<String>, Option<i64>)> = vec![];
+        for name in whitelist_items {
+            let keys = popular_keys
+                .iter()
+                .filter(|(popular_name, _, _)| popular_name == &name)
+                .cloned()
+                .collect::<Vec<_>>();
+            if !keys.is_empty() {
+                whitelist_keys.extend(keys);
+                continue;
+            }
+            let (rank, sub_types) = match cache.items().find_type(&name)? {
+                Some(item) => (
+                    item.mod_max_rank.map(|_| 0),
+                    item.subtypes.unwrap_or_default(),
+                ),
+                None => (None, vec![]),
+            };
+            if sub_types.is_empty() {
+                whitelist_keys.push((name, None, rank));
+            } else {
+                whitelist_keys.extend(
+                    sub_types
+                        .into_iter()
+                        .map(|sub_type| (name.clone(), Some(sub_type), rank)),
+                );
+            }
+        }
+
         // Combine inventory_names and interesting_items and whitelist
         let all_interesting_items = stock_items
             .clone()
             .into_iter()
             .chain(popular_items.clone().into_iter())
-            .chain(whitelist_items.clone().into_iter())
+            .chain(whitelist_keys)
             .collect::<HashSet<_>>()
             .into_iter()
             .collect::<Vec<_>>();
         // Remove duplicates
-        let all_interesting_items: HashSet<String> = HashSet::from_iter(all_interesting_items);
+        let all_interesting_items: HashSet<(String, Option<String>, Option<i64>)> =
+            HashSet::from_iter(all_interesting_items);
 
         logger::info_file(
             "LiveScraper",
@@ -150,25 +207,40 @@ impl<'a> ItemModule<'a> {
 
         let mut current_index = all_interesting_items.len();
         // Loop through all interesting items
-        for item in all_interesting_items.clone() {
+        for (item, item_sub_type, rank) in all_interesting_items.clone() {
             if self.client.is_running() == false || item == "" {
                 continue;
             }
             current_index -= 1;
+            let sub_type = item_sub_type.as_deref();
+            let rank_filter = match rank {
+                Some(rank) => col("mod_rank").eq(lit(rank as f64)),
+                None => col("mod_rank").is_null(),
+            };
+            let item_filter = col("name")
+                .eq(lit(item.clone()))
+                .and(col("sub_type").eq(lit(sub_type.unwrap_or(""))))
+                .and(rank_filter);
 
             logger::info_con(
                 "LiveScraper",
                 format!(
-                    "Checking item: {}, ({}/{})",
+                    "Checking item: {} {} {}, ({}/{})",
                     item,
+                    sub_type.unwrap_or(""),
+                    rank.map(|r| format!("rank {}", r)).unwrap_or_default(),
                     current_index,
                     all_interesting_items.len()
                 )
                 .as_str(),
             );
-            self.client.send_message("item.checking", Some(json!({ "name": item, "count": current_index, "total": all_interesting_items.len()})));
+            self.client.send_message("item.checking", Some(json!({ "name": item, "sub_type": sub_type, "rank": rank, "count": current_index, "total": all_interesting_items.len()})));
 
-            let item_live_orders_df = wfm.orders().get_ordres_by_item(&item).await?;
+            // Only the orders of the same rank are compared, a rank 0 mod is not priced against maxed ones
+            let item_live_orders_df = wfm
+                .orders()
+                .get_ordres_by_item_and_rank(&item, rank, sub_type)
+                .await?;
             // Check if item_orders_df is empty and skip if it is
             if item_live_orders_df.height() == 0 {
                 continue;
@@ -176,11 +248,11 @@ impl<'a> ItemModule<'a> {
             let item_stats = popular_items_df
                 .clone()
                 .lazy()
-                .filter(col("name").eq(lit(item.clone())))
+                .filter(item_filter.clone())
                 .collect()
                 .map_err(|e| AppError::new("LiveScraper", eyre!(e.to_string())))?;
             // Check if item is in all_interesting_items
-            if !popular_items.contains(&item) {
+            if !popular_items.contains(&(item.clone(), item_sub_type.clone(), rank)) {
                 logger::info_file(
                     "LiveScraper",
                     format!("Item: {item} is not in all_interesting_items").as_str(),
@@ -189,11 +261,11 @@ impl<'a> ItemModule<'a> {
                 let item_info = wfm.items().get_item(item.to_string()).await?;
 
                 let item_id = item_info.id;
-                let item_rank = item_info.items_in_set.get(0).unwrap().mod_max_rank;
                 self.compare_live_orders_when_selling(
                     &item,
                     &item_id,
-                    item_rank,
+                    rank.map(|r| r as f64),
+                    sub_type,
                     current_sell_orders_df.clone(),
                     &item_live_orders_df,
                     &item_stats,
@@ -203,10 +275,10 @@ impl<'a> ItemModule<'a> {
                 continue;
             }
 
-            // Get the item_id and item_rank
+            // Get the item_id
             let item_id: String = match helper::get_column_value(
                 popular_items_df.clone(),
-                Some(col("name").eq(lit(item.clone()))),
+                Some(item_filter.clone()),
                 "item_id",
                 ColumnType::String,
             )? {
@@ -214,28 +286,14 @@ impl<'a> ItemModule<'a> {
                 _ => return Err(AppError::new("LiveScraper", eyre!("Expected f64 values"))),
             };
 
-            let item_rank: Option<f64> = match helper::get_column_value(
-                popular_items_df.clone(),
-                Some(col("name").eq(lit(item.clone()))),
-                "mod_rank",
-                ColumnType::F64,
-            )? {
-                ColumnValue::F64(values) => values,
-                _ => return Err(AppError::new("LiveScraper", eyre!("Expected f64 values"))),
-            };
-
-            let item_stats = popular_items_df
-                .clone()
-                .lazy()
-                .filter(col("name").eq(lit(item.clone())))
-                .collect()
-                .map_err(|e| AppError::new("LiveScraper", eyre!(e.to_string())))?;
+            let item_rank = rank.map(|r| r as f64);
 
             if order_mode == OrderMode::Buy || order_mode == OrderMode::Both {
                 self.compare_live_orders_when_buying(
                     &item,
                     &item_id,
                     item_rank,
+                    sub_type,
                     current_buy_orders_df.clone(),
                     &item_live_orders_df,
                     &item_stats,
@@ -249,6 +307,7 @@ impl<'a> ItemModule<'a> {
                     &item,
                     &item_id,
                     item_rank,
+                    sub_type,
                     current_sell_orders_df.clone(),
                     &item_live_orders_df,
                     &item_stats,
@@ -259,7 +318,13 @@ impl<'a> ItemModule<'a> {
         }
         Ok(())
     }
-    fn get_week_increase(&self, df: &DataFrame, row_name: &str) -> Result<f64, AppError> {
+    fn get_week_increase(
+        &self,
+        df: &DataFrame,
+        row_name: &str,
+        sub_type: &str,
+        mod_rank: f64,
+    ) -> Result<f64, AppError> {
         // Pre-filter DataFrame based on "order_type" == "closed"
         let week_df = df
             .clone()
@@ -267,7 +332,9 @@ impl<'a> ItemModule<'a> {
             .filter(
                 col("order_type")
                     .eq(lit("closed"))
-                    .and(col("name").eq(lit(row_name))),
+                    .and(col("name").eq(lit(row_name)))
+                    .and(col("sub_type").eq(lit(sub_type)))
+                    .and(col("mod_rank").eq(lit(mod_rank))),
             )
             .collect()
             .map_err(|e| AppError::new("LiveScraper", eyre!(e.to_string())))?;
@@ -343,22 +410,84 @@ impl<'a> ItemModule<'a> {
         }
         Ok(())
     }
+    // Returns the key of every row.
+    fn get_item_keys(&self, df: &DataFrame) -> Result<Vec<ItemKey>, AppError> {
+        let names: Vec<String> =
+            match helper::get_column_values(df.clone(), None, "name", ColumnType::String)? {
+                ColumnValues::String(values) => values,
+                _ => {
+                    return Err(AppError::new(
+                        "LiveScraper",
+                        eyre!("Expected string values"),
+                    ))
+                }
+            };
+        let sub_types: Vec<String> =
+            match helper::get_column_values(df.clone(), None, "sub_type", ColumnType::String)? {
+                ColumnValues::String(values) => values,
+                _ => {
+                    return Err(AppError::new(
+                        "LiveScraper",
+                        eyre!("Expected string values"),
+                    ))
+                }
+            };
+
+        // Items without a rank have a null rank, fill it so the rows stay aligned
+        let ranks: Vec<f64> = match helper::get_column_values(
+            df.clone()
+                .lazy()
+                .with_column(col("mod_rank").fill_null(lit(-1.0)))
+                .collect()
+                .map_err(|e| AppError::new("LiveScraper", eyre!(e.to_string())))?,
+            None,
+            "mod_rank",
+            ColumnType::F64,
+        )? {
+            ColumnValues::F64(values) => values,
+            _ => return Err(AppError::new("LiveScraper", eyre!("Expected f64 values"))),
+        };
+
+        Ok(names
+            .into_iter()
+            .zip(sub_types)
+            .zip(ranks)
+            .map(|((name, sub_type), rank)| {
+                (
+                    name,
+                    helper::sub_type_from_str(&sub_type),
+                    if rank < 0.0 { None } else { Some(rank as i64) },
+                )
+            })
+            .collect())
+    }
     pub async fn get_buy_sell_overlap(&self) -> Result<DataFrame, AppError> {
         let settings = self.client.settings.lock()?.clone().live_scraper;
         let db = self.client.db.lock()?.clone();
-        let df = self.client.price_scraper.lock()?.get_price_historys()?;
+        let price_scraper = self.client.price_scraper.lock()?.clone();
+        let df = price_scraper.get_price_historys(None).await?;
+        // Every rank is traded on its own, items without a rank get -1 so they can be grouped and joined on
+        let df = df
+            .lazy()
+            .with_column(col("mod_rank").fill_null(lit(-1.0)))
+            .collect()
+            .map_err(|e| AppError::new("LiveScraper", eyre!(e.to_string())))?;
         let volume_threshold = settings.stock_item.volume_threshold;
         let range_threshold = settings.stock_item.range_threshold;
         let avg_price_cap = settings.stock_item.avg_price_cap;
         let price_shift_threshold = settings.stock_item.price_shift_threshold;
         let strict_whitelist = settings.stock_item.strict_whitelist;
         let whitelist = settings.stock_item.whitelist.clone();
+        let max_volatility = settings.stock_item.max_volatility;
+        let min_volume_trend = settings.stock_item.min_volume_trend;
+        let min_momentum = settings.stock_item.min_momentum;
+        let min_liquidity = settings.stock_item.min_liquidity;
 
-        // Group by the "name" and "order_type" columns, and compute the mean of the other columns
+        // Group by the "name", "sub_type", "mod_rank" and "order_type" columns, and compute the mean of the other columns
         let averaged_df = df
             .clone()
             .lazy()
-            .groupby(&["name", "order_type"])
+            .groupby(["name", "sub_type", "mod_rank", "order_type"])
             .agg(&[
                 // List the other columns you want to average
                 col("volume").mean().alias("volume"),
@@ -367,11 +496,14 @@ impl<'a> ItemModule<'a> {
                 col("range").mean().alias("range"),
                 col("median").mean().alias("median"),
                 col("avg_price").mean().alias("avg_price"),
-                col("mod_rank").mean().alias("mod_rank"),
                 col("item_id").first().alias("item_id"),
             ])
             .collect()
             .map_err(|e| AppError::new("LiveScraper", eyre!(e.to_string())))?;
+
+        // Add the trade tax in platinum, the range has to cover it to be worth trading
+        let averaged_df = self.with_trade_tax(averaged_df, "name", "mod_rank")?;
+
         // Call the database to get the inventory names and DataFrame
         let inventory_names = db.stock_item().get_items_names().await?;
         let inventory_names_s = Series::new("desired_column_name", inventory_names);
@@ -387,7 +519,7 @@ impl<'a> ItemModule<'a> {
                 col("order_type").eq(lit("closed")).and(
                     col("volume")
                         .gt(lit(volume_threshold))
-                        .and(col("range").gt(lit(range_threshold)))
+                        .and((col("range") - col("taxCost")).gt(lit(range_threshold)))
                         .or(col("name").is_in(lit(inventory_names_s.clone()))),
                 ),
             )
@@ -399,8 +531,9 @@ impl<'a> ItemModule<'a> {
 
         // If the DataFrame is empty, return an empty DataFrame
         if filtered_df.height() == 0 {
-            return Ok(DataFrame::new(vec![
+            return DataFrame::new(vec![
                 Series::new("name", &[] as &[&str]),
+                Series::new("sub_type", &[] as &[&str]),
                 Series::new("minSell", &[] as &[f64]),
                 Series::new("maxBuy", &[] as &[f64]),
                 Series::new("overlap", &[] as &[f64]),
@@ -410,32 +543,84 @@ impl<'a> ItemModule<'a> {
                 Series::new("closedAvg", &[] as &[f64]),
                 Series::new("closedMedian", &[] as &[f64]),
                 Series::new("priceShift", &[] as &[f64]),
-                Series::new("mod_rank", &[] as &[i32]),
+                Series::new("mod_rank", &[] as &[f64]),
                 Series::new("item_id", &[] as &[&str]),
+                Series::new("taxCost", &[] as &[f64]),
+                Series::new("maShort", &[] as &[f64]),
+                Series::new("maLong", &[] as &[f64]),
+                Series::new("volatility", &[] as &[f64]),
+                Series::new("volumeTrend", &[] as &[f64]),
+                Series::new("momentum", &[] as &[f64]),
+                Series::new("liquidity", &[] as &[f64]),
+                Series::new("spread", &[] as &[f64]),
             ])
-            .map_err(|e| AppError::new("LiveScraper", eyre!(e.to_string())))?);
+            .map_err(|e| AppError::new("LiveScraper", eyre!(e.to_string())));
         }
 
-        // Get the "name" column from the DataFrame
+        // Get the "name", "sub_type" and "mod_rank" columns from the DataFrame
         let name_column = filtered_df
             .column("name")
+            .map_err(|e| AppError::new("LiveScraper", eyre!(e.to_string())))?
+            .utf8()
+            .map_err(|e| AppError::new("LiveScraper", eyre!(e.to_string())))?;
+        let sub_type_column = filtered_df
+            .column("sub_type")
+            .map_err(|e| AppError::new("LiveScraper", eyre!(e.to_string())))?
+            .utf8()
+            .map_err(|e| AppError::new("LiveScraper", eyre!(e.to_string())))?;
+        let mod_rank_column = filtered_df
+            .column("mod_rank")
+            .map_err(|e| AppError::new("LiveScraper", eyre!(e.to_string())))?
+            .f64()
             .map_err(|e| AppError::new("LiveScraper", eyre!(e.to_string())))?;
 
         // Create a new Series with the calculated week price shifts
         let week_price_shifts: Vec<f64> = name_column
-            .utf8()
-            .map_err(|e| AppError::new("LiveScraper", eyre!(e.to_string())))?
             .into_iter()
-            .filter_map(|opt_name| {
-                opt_name.map(|name| self.get_week_increase(&df, name).unwrap_or(0.0))
+            .zip(sub_type_column)
+            .zip(mod_rank_column)
+            .filter_map(|((opt_name, opt_sub_type), opt_mod_rank)| {
+                opt_name.map(|name| {
+                    self.get_week_increase(
+                        &df,
+                        name,
+                        opt_sub_type.unwrap_or(""),
+                        opt_mod_rank.unwrap_or(-1.0),
+                    )
+                    .unwrap_or(0.0)
+                })
             })
             .collect();
 
-        let mut filtered_df = filtered_df
+        let filtered_df = filtered_df
             .with_column(Series::new("weekPriceShift", week_price_shifts))
             .cloned()
             .map_err(|e| AppError::new("LiveScraper", eyre!(e.to_string())))?;
 
+        // Add the market indicators of every rank
+        let indicators = self
+            .client
+            .indicator()
+            .get_indicators()
+            .await?
+            .lazy()
+            .with_column(col("mod_rank").fill_null(lit(-1.0)))
+            .collect()
+            .map_err(|e| AppError::new("LiveScraper", eyre!(e.to_string())))?;
+        let keys = ["name", "sub_type", "mod_rank"];
+        let mut filtered_df = filtered_df
+            .left_join(&indicators, keys, keys)
+            .map_err(|e| AppError::new("LiveScraper", eyre!(e.to_string())))?;
+
+        // Each indicator filter is skipped while it is at its disabled value
+        let indicator_filter = lit(max_volatility < 0.0)
+            .or(col("volatility").lt_eq(lit(max_volatility)))
+            .and(
+                lit(min_volume_trend <= -100.0).or(col("volumeTrend").gt_eq(lit(min_volume_trend))),
+            )
+            .and(lit(min_momentum <= -100.0).or(col("momentum").gt_eq(lit(min_momentum))))
+            .and(lit(min_liquidity <= 0.0).or(col("liquidity").gt_eq(lit(min_liquidity))));
+
         // Handle the whitelist if it is strict or not
         let whitelist_s = Series::new("whitelist", whitelist);
         if strict_whitelist {
@@ -451,6 +636,7 @@ impl<'a> ItemModule<'a> {
                     col("avg_price")
                         .lt(lit(avg_price_cap))
                         .and(col("weekPriceShift").gt_eq(lit(price_shift_threshold)))
+                        .and(indicator_filter)
                         .or(col("name").is_in(lit(inventory_names_s)))
                         .or(col("name").is_in(lit(whitelist_s))),
                 )
@@ -476,15 +662,18 @@ impl<'a> ItemModule<'a> {
             .collect()
             .map_err(|e| AppError::new("LiveScraper", eyre!(e.to_string())))?;
 
-        // Start the creation of the buy_sell_overlap DataFrame
-        let buy_sell_overlap = DataFrame::new(vec![unique_names_series])
-            .map_err(|e| AppError::new("LiveScraper", eyre!(e.to_string())))?;
+        // Start the creation of the buy_sell_overlap DataFrame, one row per name, sub type and rank
+        let buy_sell_overlap = helper::filter_and_extract(
+            filtered_df.clone(),
+            None,
+            vec!["name", "sub_type", "mod_rank"],
+        )?;
 
         // Get Order type "sell" and "buy" into separate DataFrames
         let mut order_sell_df = helper::filter_and_extract(
             df_filtered.clone(),
             Some(col("order_type").eq(lit("sell"))),
-            vec!["name", "min_price"],
+            vec!["name", "sub_type", "mod_rank", "min_price"],
         )?;
         let order_sell_df = order_sell_df
             .rename("min_price", "minSell")
@@ -493,7 +682,7 @@ impl<'a> ItemModule<'a> {
         let mut order_buy_df = helper::filter_and_extract(
             df_filtered.clone(),
             Some(col("order_type").eq(lit("buy"))),
-            vec!["name", "max_price"],
+            vec!["name", "sub_type", "mod_rank", "max_price"],
         )?;
         let order_buy_df = order_buy_df
             .rename("max_price", "maxBuy")
@@ -504,11 +693,11 @@ impl<'a> ItemModule<'a> {
 
         // Join the DataFrames together
         let buy_sell_overlap = buy_sell_overlap
-            .inner_join(&order_sell_df, ["name"], ["name"])
+            .inner_join(order_sell_df, keys, keys)
             .map_err(|e| AppError::new("LiveScraper", eyre!(e.to_string())))?
-            .inner_join(&order_buy_df, ["name"], ["name"])
+            .inner_join(order_buy_df, keys, keys)
             .map_err(|e| AppError::new("LiveScraper", eyre!(e.to_string())))?
-            .inner_join(&filtered_df, ["name"], ["name"])
+            .inner_join(&filtered_df, keys, keys)
             .map_err(|e| AppError::new("LiveScraper", eyre!(e.to_string())))?;
 
         // Calculate the overlap
@@ -536,17 +725,37 @@ impl<'a> ItemModule<'a> {
             .rename("weekPriceShift", "priceShift")
             .map_err(|e| AppError::new("LiveScraper", eyre!(e.to_string())))?;
 
-        return Ok(buy_sell_overlap.clone());
+        // Items without a rank go back to a null rank
+        let buy_sell_overlap = buy_sell_overlap
+            .clone()
+            .lazy()
+            .with_column(
+                when(col("mod_rank").lt(lit(0.0)))
+                    .then(lit(NULL).cast(DataType::Float64))
+                    .otherwise(col("mod_rank"))
+                    .alias("mod_rank"),
+            )
+            .collect()
+            .map_err(|e| AppError::new("LiveScraper", eyre!(e.to_string())))?;
+
+        Ok(buy_sell_overlap)
     }
     async fn get_my_order_information(
         &self,
         item_name: &str,
+        sub_type: Option<&str>,
+        rank: Option<i64>,
         df: &DataFrame,
     ) -> Result<(Option<String>, bool, i64, bool), AppError> {
         let orders_by_item = df
             .clone()
             .lazy()
-            .filter(col("url_name").eq(lit(item_name)))
+            .filter(
+                col("url_name")
+                    .eq(lit(item_name))
+                    .and(col("sub_type").eq(lit(sub_type.unwrap_or(""))))
+                    .and(rank.map_or(lit(true), |rank| col("mod_rank").eq(lit(rank)))),
+            )
             .collect()
             .map_err(|e| AppError::new("LiveScraper", eyre!(e.to_string())))?;
         let id: Option<String> = None;
@@ -583,17 +792,85 @@ impl<'a> ItemModule<'a> {
         };
         Ok((id.clone(), visibility, price, true))
     }
-    async fn restructure_live_order_df(
+    // Adds a "taxCost" column with the trade tax of each row converted to platinum.
+    // The tax depends on the rank, so it is computed from the rank of the row and not the max rank.
+    pub fn with_trade_tax(
+        &self,
+        df: DataFrame,
+        name_column: &str,
+        rank_column: &str,
+    ) -> Result<DataFrame, AppError> {
+        let credits_per_plat = self
+            .client
+            .settings
+            .lock()?
+            .clone()
+            .live_scraper
+            .credits_per_plat;
+        let cache = self.client.db.lock()?.clone().cache.lock()?.clone();
+        let tags: HashMap<String, Vec<String>> = cache
+            .items()
+            .get_types()?
+            .into_iter()
+            .map(|i| (i.url_name, i.tags.unwrap_or_default()))
+            .collect();
+        let names =
+            match helper::get_column_values(df.clone(), None, name_column, ColumnType::String)? {
+                ColumnValues::String(values) => values,
+                _ => {
+                    return Err(AppError::new(
+                        "LiveScraper",
+                        eyre!("Expected string values"),
+                    ))
+                }
+            };
+        // Read the ranks with their nulls so they stay aligned with the names
+        let ranks = df
+            .column(rank_column)
+            .and_then(|c| c.f64())
+            .map_err(|e| AppError::new("LiveScraper", eyre!(e.to_string())))?
+            .into_iter()
+            .collect::<Vec<_>>();
+        let tax_cost = names
+            .iter()
+            .zip(ranks)
+            .map(|(name, rank)| {
+                let tax = match tags.get(name) {
+                    // Items without a rank are stored with -1
+                    Some(tags) => helper::calculate_trade_tax(
+                        tags.clone(),
+                        rank.filter(|r| *r >= 0.0).map(|r| r as i64),
+                    ),
+                    None => 0,
+                };
+                helper::trade_tax_to_plat(tax, credits_per_plat)
+            })
+            .collect::<Vec<_>>();
+        let df = df
+            .clone()
+            .with_column(Series::new("taxCost", tax_cost))
+            .cloned()
+            .map_err(|e| AppError::new("LiveScraper", eyre!(e.to_string())))?;
+        Ok(df)
+    }
+    pub async fn restructure_live_order_df(
         &self,
         item_live_orders_df: &DataFrame,
     ) -> Result<(DataFrame, DataFrame, i64, i64, i64), AppError> {
         let in_game_name = self.client.auth.lock()?.clone().ingame_name;
+        let db = self.client.db.lock()?.clone();
+        // The orders of blocked players do not count as competing prices
+        let blocked_names = db.trade_partner().get_blocked_names().await?;
+        let not_blocked = blocked_names.into_iter().fold(lit(true), |expr, name| {
+            expr.and(col("username").neq(lit(name)))
+        });
         let buy_orders_df = item_live_orders_df
             .clone()
             .lazy()
             .filter(
                 col("username")
                     .neq(lit(in_game_name.clone()))
+                    .and(not_blocked.clone())
                     .and(col("order_type").eq(lit("buy"))), // Add this line
             )
             .collect()
@@ -606,6 +883,7 @@ impl<'a> ItemModule<'a> {
             .filter(
                 col("username")
                     .neq(lit(in_game_name))
+                    .and(not_blocked)
                     .and(col("order_type").eq(lit("sell"))), // Add this line
             )
             .collect()
@@ -701,11 +979,13 @@ impl<'a> ItemModule<'a> {
 
         Ok((dp[n][max_weight as usize], selected_items, unselected_items))
     }
+    #[allow(clippy::too_many_arguments)]
     async fn compare_live_orders_when_buying(
         &self,
         item_name: &str,
         item_id: &str,
         item_rank: Option<f64>,
+        sub_type: Option<&str>,
         current_orders: DataFrame,
         item_live_orders_df: &DataFrame,
         item_stats: &DataFrame,
@@ -716,14 +996,30 @@ impl<'a> ItemModule<'a> {
             return Ok(None);
         }
 
+        // Buy orders for ducat parts and rank up items are handled by their own modules
+        if self
+            .client
+            .ducat_orders
+            .lock()?
+            .contains(&item_name.to_string())
+            || self
+                .client
+                .rank_up_orders
+                .lock()?
+                .contains(&item_name.to_string())
+        {
+            return Ok(None);
+        }
+
         let settings = self.client.settings.lock()?.clone().live_scraper;
         let wfm = self.client.wfm.lock()?.clone();
         let mut current_orders = current_orders.clone();
         let avg_price_cap = settings.stock_item.avg_price_cap;
         let max_total_price_cap = settings.stock_item.max_total_price_cap;
+        let rank = item_rank.map(|r| r as i64);
         // Get the current orders for the item from the Warframe Market API
         let (order_id, visibility, price, active) = self
-            .get_my_order_information(item_name, &current_orders)
+            .get_my_order_information(item_name, sub_type, rank, &current_orders)
             .await?;
 
         // Get all the live orders for the item from the Warframe Market API
@@ -777,7 +1073,7 @@ impl<'a> ItemModule<'a> {
                     Some(json!({ "name": item_name, "price": post_price})),
                 );
                 wfm.orders()
-                    .create(item_id, "buy", post_price, 1, true, item_rank)
+                    .create(item_id, "buy", post_price, 1, true, item_rank, sub_type)
                     .await?;
                 logger::info_con("LiveScraper",format!("Automatically Posted Visible Buy Order Item: {item_name}, ItemId: {item_id}, Price: {post_price}").as_str());
                 return Ok(None);
@@ -797,11 +1093,18 @@ impl<'a> ItemModule<'a> {
             _ => return Err(AppError::new("LiveScraper", eyre!("Expected i64 values"))),
         };
 
+        // Get the trade tax we pay when selling the item again
+        let tax_cost: f64 =
+            match helper::get_column_value(item_stats.clone(), None, "taxCost", ColumnType::F64)? {
+                ColumnValue::F64(values) => values.unwrap_or(0.0),
+                _ => return Err(AppError::new("LiveScraper", eyre!("Expected f64 values"))),
+            };
+
         // Get the average price of the item from the Warframe Market API
         let closed_avg_metric: f64 =
             match helper::get_column_value(item_stats.clone(), None, "closedAvg", ColumnType::F64)?
             {
-                ColumnValue::F64(values) => values.unwrap_or(0.0) - post_price as f64,
+                ColumnValue::F64(values) => values.unwrap_or(0.0) - post_price as f64 - tax_cost,
                 _ => return Err(AppError::new("LiveScraper", eyre!("Expected f64 values"))),
             };
         let potential_profit = closed_avg_metric - 1.0;
@@ -814,7 +1117,12 @@ impl<'a> ItemModule<'a> {
         // Get the owned value from the database
         let owned: i32 = match helper::get_column_value(
             inventory_df.clone(),
-            Some(col("item_url").eq(lit(item_name))),
+            Some(
+                col("item_url")
+                    .eq(lit(item_name))
+                    .and(col("sub_type").eq(lit(sub_type.unwrap_or(""))))
+                    .and(rank.map_or(lit(true), |rank| col("rank").eq(lit(rank as i32)))),
+            ),
             "owned",
             ColumnType::I32,
         )? {
@@ -860,12 +1168,13 @@ impl<'a> ItemModule<'a> {
                         .await?;
                     let df = DataFrame::new(vec![
                         Series::new("url_name", vec![item_name]),
+                        Series::new("sub_type", vec![sub_type.unwrap_or("")]),
                         Series::new("platinum", vec![post_price]),
                         Series::new("potential_profit", vec![(post_price - price)]),
                     ])
                     .map_err(|e| AppError::new("LiveScraper", eyre!(e.to_string())))?;
                     let updatede = current_orders
-                        .inner_join(&df, ["url_name"], ["url_name"])
+                        .inner_join(&df, ["url_name", "sub_type"], ["url_name", "sub_type"])
                         .map_err(|e| AppError::new("LiveScraper", eyre!(e.to_string())))?;
                     return Ok(Some(updatede));
                 } else {
@@ -1018,7 +1327,7 @@ impl<'a> ItemModule<'a> {
                     );
                     let new_order = wfm
                         .orders()
-                        .create(item_id, "buy", post_price, 1, true, item_rank)
+                        .create(item_id, "buy", post_price, 1, true, item_rank, sub_type)
                         .await?;
                     let current_orders =
                         self.get_new_buy_data(current_orders.clone(), new_order, item_closed_avg)?;
@@ -1038,11 +1347,13 @@ impl<'a> ItemModule<'a> {
 
         Ok(None)
     }
+    #[allow(clippy::too_many_arguments)]
     async fn compare_live_orders_when_selling(
         &self,
         item_name: &str,
         item_id: &str,
         item_rank: Option<f64>,
+        sub_type: Option<&str>,
         current_orders: DataFrame,
         item_live_orders_df: &DataFrame,
         _item_stats: &DataFrame,
@@ -1050,22 +1361,42 @@ impl<'a> ItemModule<'a> {
     ) -> Result<(), AppError> {
         let wfm = self.client.wfm.lock()?.clone();
         let db = self.client.db.lock()?.clone();
+        let rank = item_rank.map(|r| r as i64);
 
         // Get the current orders for the item from the Warframe Market API
         let (order_id, visibility, price, active) = self
-            .get_my_order_information(item_name, &current_orders)
+            .get_my_order_information(item_name, sub_type, rank, &current_orders)
             .await?;
 
-        let inventory_names = db.stock_item().get_items_names().await?;
+        let inventory_keys = db.stock_item().get_items_keys().await?;
+        let in_inventory = inventory_keys
+            .iter()
+            .any(|(url, item_sub_type, item_rank)| {
+                url == item_name
+                    && item_sub_type.as_deref() == sub_type
+                    && rank.is_none_or(|rank| *item_rank == rank)
+            });
+
+        // Parts held back for a set are sold with the set
+        if db
+            .stock_set()
+            .get_reserved_parts()
+            .await?
+            .contains(&item_name.to_string())
+        {
+            return Ok(());
+        }
 
-        if !inventory_names.contains(&item_name.to_string()) && !active {
+        if !in_inventory && !active {
             return Ok(());
-        } else if !inventory_names.contains(&item_name.to_string()) {
+        } else if !in_inventory {
             self.client
                 .send_message("item.sell.deleting", Some(json!({ "name": item_name})));
             db.stock_item()
                 .update_by_url(
                     item_name,
+                    sub_type,
+                    rank,
                     None,
                     None,
                     None,
@@ -1091,7 +1422,7 @@ impl<'a> ItemModule<'a> {
         // Get Invantory Data from the database
         let stock_item = db
             .stock_item()
-            .get_item_by_url_name(item_name)
+            .get_item_by_key(item_name, sub_type, rank)
             .await?
             .unwrap();
 
@@ -1119,6 +1450,8 @@ impl<'a> ItemModule<'a> {
             db.stock_item()
                 .update_by_url(
                     item_name,
+                    sub_type,
+                    rank,
                     None,
                     None,
                     Some(post_price as i32),
@@ -1141,7 +1474,7 @@ impl<'a> ItemModule<'a> {
             } else {
                 wfm.orders()
                     .create(
-                         item_id, "sell", post_price, quantity, true, item_rank,
+                        item_id, "sell", post_price, quantity, true, item_rank, sub_type,
                     )
                     .await?;
                 return Ok(());
@@ -1162,8 +1495,27 @@ impl<'a> ItemModule<'a> {
         // Get lowest buy order price from the DataFrame of live sell orders
         let mut post_price = post_prices.get(0).unwrap_or(&0).clone();
 
+        // Get the trade tax we pay when giving the item
+        let credits_per_plat = self
+            .client
+            .settings
+            .lock()?
+            .clone()
+            .live_scraper
+            .credits_per_plat;
+        let tags = stock_item
+            .tags
+            .split(",")
+            .map(|t| t.to_string())
+            .collect::<Vec<_>>();
+        let tax_cost = helper::trade_tax_to_plat(
+            helper::calculate_trade_tax(tags, Some(stock_item.rank as i64)),
+            credits_per_plat,
+        )
+        .ceil() as i64;
+
         // Get the profit from the current order
-        let profit = post_price - bought_avg_price as i64;
+        let profit = post_price - bought_avg_price as i64 - tax_cost;
 
         if profit <= -10 {
             // Only update the database if the item is not already marked as to_low_profit
@@ -1171,6 +1523,8 @@ impl<'a> ItemModule<'a> {
                 db.stock_item()
                     .update_by_url(
                         item_name,
+                        sub_type,
+                        rank,
                         None,
                         None,
                         Some(-1),
@@ -1220,6 +1574,8 @@ impl<'a> ItemModule<'a> {
                 db.stock_item()
                     .update_by_url(
                         item_name,
+                        sub_type,
+                        rank,
                         None,
                         None,
                         Some(post_price as i32),
@@ -1250,12 +1606,14 @@ impl<'a> ItemModule<'a> {
             );
             wfm.orders()
                 .create(
-                     item_id, "sell", post_price, quantity, true, item_rank,
+                    item_id, "sell", post_price, quantity, true, item_rank, sub_type,
                 )
                 .await?;
             db.stock_item()
                 .update_by_url(
                     item_name,
+                    sub_type,
+                    rank,
                     None,
                     None,
                     Some(post_price as i32),
@@ -1,3 +1,9 @@
+pub mod auction;
+pub mod ducat;
 pub mod helper;
+pub mod indicator;
 pub mod item;
-pub mod riven;
\ No newline at end of file
+pub mod rank_up;
+pub mod riven;
+pub mod set;
+pub mod watchlist;
@@ -0,0 +1,409 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use eyre::eyre;
+use polars::prelude::*;
+use serde_json::json;
+
+use crate::{
+    error::AppError,
+    helper::{self, ColumnType, ColumnValue, ColumnValues},
+    live_scraper::client::LiveScraperClient,
+    logger,
+};
+
+pub struct RankUpModule<'a> {
+    pub client: &'a LiveScraperClient,
+}
+
+impl<'a> RankUpModule<'a> {
+    fn get_file_path() -> PathBuf {
+        helper::get_app_roaming_path().join("rank_up_orders.json")
+    }
+
+    // The items we have unranked buy orders for, kept in a file so the orders are still known after a restart.
+    pub fn load_orders() -> Vec<String> {
+        let content = match std::fs::read(Self::get_file_path()) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return vec![],
+            Err(e) => {
+                logger::warning_con(
+                    "RankUpModule",
+                    format!("Could not read the rank up orders: {}", e).as_str(),
+                );
+                return vec![];
+            }
+        };
+        match serde_json::from_slice(&content) {
+            Ok(names) => names,
+            Err(e) => {
+                logger::warning_con(
+                    "RankUpModule",
+                    format!("Could not parse the rank up orders: {}", e).as_str(),
+                );
+                vec![]
+            }
+        }
+    }
+
+    fn set_orders(&self, names: Vec<String>) -> Result<(), AppError> {
+        std::fs::write(Self::get_file_path(), json!(names).to_string())
+            .map_err(|e| AppError::new("RankUpModule", eyre!(e.to_string())))?;
+        *self.client.rank_up_orders.lock()? = names;
+        Ok(())
+    }
+
+    // Endo needed to take a mod from rank 0 to max rank, credits are roughly 48.3 per endo.
+    fn get_endo_cost(&self, tags: &[String], max_rank: i64) -> i64 {
+        let rarity = if tags.contains(&"legendary".to_string()) {
+            4
+        } else if tags.contains(&"rare".to_string()) {
+            3
+        } else if tags.contains(&"uncommon".to_string()) {
+            2
+        } else {
+            1
+        };
+        10 * rarity * (2_i64.pow(max_rank as u32) - 1)
+    }
+
+    // Arcanes are ranked by fusing copies, rank r needs 1 + 2 + ... + (r + 1) copies.
+    fn get_arcane_copies(&self, max_rank: i64) -> i64 {
+        (max_rank + 1) * (max_rank + 2) / 2
+    }
+
+    // Compares the unranked price with the max ranked price for every mod and arcane.
+    pub async fn get_rank_up_profit(&self) -> Result<DataFrame, AppError> {
+        let settings = self.client.settings.lock()?.clone().live_scraper;
+        let cache = self.client.db.lock()?.clone().cache.lock()?.clone();
+        let price_scraper = self.client.price_scraper.lock()?.clone();
+        let df = price_scraper.get_price_historys(None).await?;
+
+        let ranked_df = df
+            .lazy()
+            .filter(
+                col("order_type")
+                    .eq(lit("closed"))
+                    .and(col("mod_rank").is_not_null()),
+            )
+            .groupby(["name", "mod_rank"])
+            .agg(&[
+                col("avg_price").mean().alias("avg_price"),
+                col("volume").mean().alias("volume"),
+            ])
+            .collect()
+            .map_err(|e| AppError::new("RankUpModule", eyre!(e.to_string())))?;
+
+        let names =
+            match helper::get_column_values(ranked_df.clone(), None, "name", ColumnType::String)? {
+                ColumnValues::String(values) => values,
+                _ => {
+                    return Err(AppError::new(
+                        "RankUpModule",
+                        eyre!("Expected string values"),
+                    ))
+                }
+            };
+        let ranks = match helper::get_column_values(
+            ranked_df.clone(),
+            None,
+            "mod_rank",
+            ColumnType::F64,
+        )? {
+            ColumnValues::F64(values) => values,
+            _ => return Err(AppError::new("RankUpModule", eyre!("Expected f64 values"))),
+        };
+        let prices =
+            match helper::get_column_values(ranked_df.clone(), None, "avg_price", ColumnType::F64)?
+            {
+                ColumnValues::F64(values) => values,
+                _ => return Err(AppError::new("RankUpModule", eyre!("Expected f64 values"))),
+            };
+        let volumes = match helper::get_column_values(ranked_df, None, "volume", ColumnType::F64)? {
+            ColumnValues::F64(values) => values,
+            _ => return Err(AppError::new("RankUpModule", eyre!("Expected f64 values"))),
+        };
+        // (name, rank) -> (price, volume)
+        let mut history: HashMap<(String, i64), (f64, f64)> = HashMap::new();
+        for i in 0..names
+            .len()
+            .min(ranks.len())
+            .min(prices.len())
+            .min(volumes.len())
+        {
+            history.insert((names[i].clone(), ranks[i] as i64), (prices[i], volumes[i]));
+        }
+
+        let mut out_names: Vec<String> = vec![];
+        let mut out_ids: Vec<String> = vec![];
+        let mut out_kinds: Vec<String> = vec![];
+        let mut out_max_ranks: Vec<i64> = vec![];
+        let mut out_unranked: Vec<f64> = vec![];
+        let mut out_ranked: Vec<f64> = vec![];
+        let mut out_copies: Vec<i64> = vec![];
+        let mut out_endo: Vec<i64> = vec![];
+        let mut out_credits: Vec<i64> = vec![];
+        let mut out_tax: Vec<i64> = vec![];
+        let mut out_volume: Vec<f64> = vec![];
+
+        for item in cache.items().get_types()? {
+            let max_rank = item.mod_max_rank.unwrap_or(0);
+            if max_rank <= 0 {
+                continue;
+            }
+            let tags = item.tags.clone().unwrap_or_default();
+            let kind = if tags.contains(&"arcane_enhancement".to_string()) {
+                "arcane"
+            } else if tags.contains(&"mod".to_string()) {
+                "mod"
+            } else {
+                continue;
+            };
+            let unranked = history.get(&(item.url_name.clone(), 0));
+            let ranked = history.get(&(item.url_name.clone(), max_rank));
+            let ((unranked_price, unranked_volume), (ranked_price, ranked_volume)) =
+                match (unranked, ranked) {
+                    (Some(unranked), Some(ranked)) => (*unranked, *ranked),
+                    _ => continue,
+                };
+
+            let (copies, endo) = if kind == "arcane" {
+                (self.get_arcane_copies(max_rank), 0)
+            } else {
+                (1, self.get_endo_cost(&tags, max_rank))
+            };
+
+            out_names.push(item.url_name.clone());
+            out_ids.push(item.id.clone());
+            out_kinds.push(kind.to_string());
+            out_max_ranks.push(max_rank);
+            out_unranked.push(unranked_price);
+            out_ranked.push(ranked_price);
+            out_copies.push(copies);
+            out_endo.push(endo);
+            out_credits.push((endo as f64 * 48.3) as i64);
+            out_tax.push(helper::calculate_trade_tax(tags.clone(), Some(max_rank)));
+            out_volume.push(unranked_volume.min(ranked_volume));
+        }
+
+        let df = DataFrame::new(vec![
+            Series::new("name", out_names),
+            Series::new("item_id", out_ids),
+            Series::new("kind", out_kinds),
+            Series::new("maxRank", out_max_ranks),
+            Series::new("unrankedPrice", out_unranked),
+            Series::new("rankedPrice", out_ranked),
+            Series::new("copies", out_copies),
+            Series::new("endo", out_endo),
+            Series::new("credits", out_credits),
+            Series::new("tax", out_tax),
+            Series::new("volume", out_volume),
+        ])
+        .map_err(|e| AppError::new("RankUpModule", eyre!(e.to_string())))?;
+
+        // Endo, credits and the trade tax of the ranked item are converted to platinum with the configured rates
+        let endo_per_plat = settings.stock_rank_up.endo_per_plat.max(1) as f64;
+        let credits_per_plat = settings.credits_per_plat.max(1) as f64;
+        let df = df
+            .lazy()
+            .with_column(
+                (col("copies").cast(DataType::Float64) * col("unrankedPrice")
+                    + col("endo").cast(DataType::Float64) / lit(endo_per_plat)
+                    + (col("credits") + col("tax")).cast(DataType::Float64)
+                        / lit(credits_per_plat))
+                .alias("cost"),
+            )
+            .with_column((col("rankedPrice") - col("cost")).alias("profit"))
+            .collect()
+            .map_err(|e| AppError::new("RankUpModule", eyre!(e.to_string())))?;
+        helper::sort_dataframe(df, "profit", true)
+    }
+
+    pub async fn check_stock(&self) -> Result<(), AppError> {
+        let settings = self.client.settings.lock()?.clone().live_scraper;
+        if !settings.stock_rank_up.enabled {
+            return Ok(());
+        }
+        logger::info_con("RankUpModule", "Run rank up module");
+        let wfm = self.client.wfm.lock()?.clone();
+        let min_profit = settings.stock_rank_up.min_profit as f64;
+        let blacklist = settings.stock_item.blacklist.clone();
+
+        let opportunities = self
+            .get_rank_up_profit()
+            .await?
+            .lazy()
+            .filter(
+                col("profit")
+                    .gt_eq(lit(min_profit))
+                    .and(col("volume").gt_eq(lit(settings.stock_rank_up.min_volume))),
+            )
+            .limit(settings.stock_rank_up.max_orders as u32)
+            .collect()
+            .map_err(|e| AppError::new("RankUpModule", eyre!(e.to_string())))?;
+        let names = match helper::get_column_values(
+            opportunities.clone(),
+            None,
+            "name",
+            ColumnType::String,
+        )? {
+            ColumnValues::String(values) => values,
+            _ => {
+                return Err(AppError::new(
+                    "RankUpModule",
+                    eyre!("Expected string values"),
+                ))
+            }
+        };
+        let names = names
+            .into_iter()
+            .filter(|n| !blacklist.contains(n))
+            .collect::<Vec<_>>();
+
+        let my_buy_orders = wfm.orders().get_my_orders().await?.buy_orders;
+        let find_order = |url_name: &str| {
+            my_buy_orders.iter().find(|o| {
+                o.mod_rank.unwrap_or(0) == 0
+                    && o.item.as_ref().map(|i| i.url_name.as_str()) == Some(url_name)
+            })
+        };
+
+        // Remove the buy orders for items that are no longer worth ranking up
+        let previous = self.client.rank_up_orders.lock()?.clone();
+        for url_name in previous.iter().filter(|n| !names.contains(n)) {
+            if let Some(order) = find_order(url_name) {
+                self.client
+                    .send_message("rank_up.buy.deleting", Some(json!({ "name": url_name})));
+                wfm.orders().delete(&order.id).await?;
+            }
+        }
+
+        let total = names.len();
+        for (index, url_name) in names.iter().enumerate() {
+            if !self.client.is_running() {
+                break;
+            }
+            self.client.send_message(
+                "rank_up.checking",
+                Some(json!({ "name": url_name, "count": index + 1, "total": total})),
+            );
+            let filter = Some(col("name").eq(lit(url_name.clone())));
+            let item_id = match helper::get_column_value(
+                opportunities.clone(),
+                filter.clone(),
+                "item_id",
+                ColumnType::String,
+            )? {
+                ColumnValue::String(value) => value.unwrap_or("".to_string()),
+                _ => {
+                    return Err(AppError::new(
+                        "RankUpModule",
+                        eyre!("Expected string values"),
+                    ))
+                }
+            };
+            let copies = match helper::get_column_value(
+                opportunities.clone(),
+                filter.clone(),
+                "copies",
+                ColumnType::I64,
+            )? {
+                ColumnValue::I64(value) => value.unwrap_or(1),
+                _ => return Err(AppError::new("RankUpModule", eyre!("Expected i64 values"))),
+            };
+            let ranked_price = match helper::get_column_value(
+                opportunities.clone(),
+                filter.clone(),
+                "rankedPrice",
+                ColumnType::F64,
+            )? {
+                ColumnValue::F64(value) => value.unwrap_or(0.0),
+                _ => return Err(AppError::new("RankUpModule", eyre!("Expected f64 values"))),
+            };
+            let cost = match helper::get_column_value(
+                opportunities.clone(),
+                filter.clone(),
+                "cost",
+                ColumnType::F64,
+            )? {
+                ColumnValue::F64(value) => value.unwrap_or(0.0),
+                _ => return Err(AppError::new("RankUpModule", eyre!("Expected f64 values"))),
+            };
+            let unranked_price = match helper::get_column_value(
+                opportunities.clone(),
+                filter,
+                "unrankedPrice",
+                ColumnType::F64,
+            )? {
+                ColumnValue::F64(value) => value.unwrap_or(0.0),
+                _ => return Err(AppError::new("RankUpModule", eyre!("Expected f64 values"))),
+            };
+
+            // The most we can pay per copy and still make the minimum profit
+            let fixed_cost = cost - unranked_price * copies as f64;
+            let max_price =
+                ((ranked_price - fixed_cost - min_profit) / copies as f64).floor() as i64;
+            if max_price < 1 {
+                continue;
+            }
+
+            // Outbid the highest unranked buyer, but never above the max price
+            let live_orders_df = wfm
+                .orders()
+                .get_ordres_by_item_and_rank(url_name, Some(0), None)
+                .await?;
+            let mut post_price = max_price;
+            if live_orders_df.height() > 0 {
+                let (buy_orders_df, _sell_orders_df, buyers, _sellers, _range) = self
+                    .client
+                    .item()
+                    .restructure_live_order_df(&live_orders_df)
+                    .await?;
+                if buyers > 0 {
+                    let highest_buy = match helper::get_column_value(
+                        buy_orders_df,
+                        None,
+                        "platinum",
+                        ColumnType::I64,
+                    )? {
+                        ColumnValue::I64(value) => value.unwrap_or(0),
+                        _ => {
+                            return Err(AppError::new("RankUpModule", eyre!("Expected i64 values")))
+                        }
+                    };
+                    post_price = (highest_buy + 1).min(max_price);
+                }
+            }
+
+            match find_order(url_name) {
+                Some(order) => {
+                    if order.platinum != post_price {
+                        self.client.send_message(
+                            "rank_up.buy.updating",
+                            Some(json!({ "name": url_name, "price": post_price})),
+                        );
+                        wfm.orders()
+                            .update(&order.id, post_price as i32, copies as i32, true)
+                            .await?;
+                    }
+                }
+                None => {
+                    self.client.send_message(
+                        "rank_up.buy.creating",
+                        Some(json!({ "name": url_name, "price": post_price})),
+                    );
+                    wfm.orders()
+                        .create(&item_id, "buy", post_price, copies, true, Some(0.0), None)
+                        .await?;
+                    // Saved right away, a later failure would otherwise leave this order unknown
+                    let mut orders = self.client.rank_up_orders.lock()?.clone();
+                    if !orders.contains(url_name) {
+                        orders.push(url_name.clone());
+                        self.set_orders(orders)?;
+                    }
+                }
+            }
+        }
+        self.set_orders(names)?;
+        Ok(())
+    }
+}
@@ -17,6 +17,8 @@ impl<'a> RivenModule<'a> {
         let settings = self.client.settings.lock()?.clone().live_scraper;
         logger::info_con("RivenModule", "Run riven module");
         let stockrivens = db.stock_riven().get_rivens().await?;
+        // The auctions of blocked players do not count as competing prices
+        let blocked_names = db.trade_partner().get_blocked_names().await?;
         let my_auctions = wfm.auction().get_my_auctions().await?;
         let my_rivens = my_auctions
             .iter()
@@ -112,11 +114,12 @@ impl<'a> RivenModule<'a> {
                 )
                 .await?;
 
-            // Filter auctions that are not mine and are not closed and are player is ingame
+            // Filter auctions that are not mine or from a blocked player and are not closed and are player is ingame
             let live_auctions = live_auctions
                 .iter()
                 .filter(|a| {
                     a.owner.ingame_name != auth.ingame_name
+                        && !blocked_names.contains(&a.owner.ingame_name)
                         && a.closed == false
                         && a.visible
                         && a.owner.status == "ingame"
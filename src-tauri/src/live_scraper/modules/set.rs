@@ -0,0 +1,508 @@
+use std::collections::HashMap;
+
+use eyre::eyre;
+use polars::prelude::*;
+use serde_json::json;
+
+use crate::{
+    database::modules::stock_set::{SetPartStruct, StockSetStruct},
+    error::AppError,
+    helper::{self, ColumnType, ColumnValue},
+    live_scraper::client::LiveScraperClient,
+    logger,
+    structs::Order,
+};
+
+pub struct SetModule<'a> {
+    pub client: &'a LiveScraperClient,
+}
+
+impl<'a> SetModule<'a> {
+    // Ranks every set by the margin between the set price (sell side) and the sum of its part prices (buy side).
+    // Part quantities come from the item cache, sets whose quantities are not cached yet are left out.
+    pub async fn get_set_arbitrage(&self) -> Result<DataFrame, AppError> {
+        let cache = self.client.db.lock()?.clone().cache.lock()?.clone();
+        let price_scraper = self.client.price_scraper.lock()?.clone();
+        let df = price_scraper.get_price_historys(None).await?;
+        let items = cache.items().get_types()?;
+
+        // Group by the "name" and "order_type" columns, and compute the mean of the other columns
+        let averaged_df = df
+            .lazy()
+            .groupby(["name", "order_type"])
+            .agg(&[
+                col("volume").mean().alias("volume"),
+                col("min_price").mean().alias("min_price"),
+                col("max_price").mean().alias("max_price"),
+                col("avg_price").mean().alias("avg_price"),
+            ])
+            .collect()
+            .map_err(|e| AppError::new("SetModule", eyre!(e.to_string())))?;
+
+        let closed = self.get_price_map(&averaged_df, "closed", "avg_price")?;
+        let closed_volume = self.get_price_map(&averaged_df, "closed", "volume")?;
+        let max_buy = self.get_price_map(&averaged_df, "buy", "max_price")?;
+        let min_sell = self.get_price_map(&averaged_df, "sell", "min_price")?;
+
+        let mut names: Vec<String> = vec![];
+        let mut item_ids: Vec<String> = vec![];
+        let mut parts_count: Vec<i64> = vec![];
+        let mut parts_costs: Vec<f64> = vec![];
+        let mut set_prices: Vec<f64> = vec![];
+        let mut volumes: Vec<f64> = vec![];
+
+        for set in items.iter().filter(|i| {
+            i.tags
+                .clone()
+                .unwrap_or_default()
+                .contains(&"set".to_string())
+                && !i.set_items.clone().unwrap_or_default().is_empty()
+        }) {
+            // The set price is what we can sell it for, fall back to the closed average.
+            let set_price = match min_sell.get(&set.url_name).or(closed.get(&set.url_name)) {
+                Some(price) => *price,
+                None => continue,
+            };
+            let mut volume = closed_volume.get(&set.url_name).cloned().unwrap_or(0.0);
+
+            // Resolve the parts of the set, set_items can hold both ids and url names.
+            let parts = set
+                .set_items
+                .clone()
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|p| {
+                    items
+                        .iter()
+                        .find(|i| (i.id == *p || i.url_name == *p) && i.url_name != set.url_name)
+                })
+                .map(|i| (i.url_name.clone(), i.quantity_for_set))
+                .collect::<Vec<_>>();
+            if parts.is_empty() || parts.iter().any(|(_, quantity)| quantity.is_none()) {
+                continue;
+            }
+
+            // The parts cost is what we have to bid, fall back to the closed average.
+            let mut parts_cost = 0.0;
+            let mut complete = true;
+            for (part, quantity) in parts.iter() {
+                let quantity = quantity.unwrap_or(1);
+                match max_buy.get(part).or(closed.get(part)) {
+                    Some(price) => parts_cost += price * quantity as f64,
+                    None => {
+                        complete = false;
+                        break;
+                    }
+                }
+                volume = volume.min(closed_volume.get(part).cloned().unwrap_or(0.0));
+            }
+            if !complete {
+                continue;
+            }
+
+            names.push(set.url_name.clone());
+            item_ids.push(set.id.clone());
+            parts_count.push(
+                parts
+                    .iter()
+                    .map(|(_, quantity)| quantity.unwrap_or(1))
+                    .sum(),
+            );
+            parts_costs.push(parts_cost);
+            set_prices.push(set_price);
+            volumes.push(volume);
+        }
+
+        let df = DataFrame::new(vec![
+            Series::new("name", names),
+            Series::new("item_id", item_ids),
+            Series::new("parts", parts_count),
+            Series::new("partsCost", parts_costs),
+            Series::new("setPrice", set_prices),
+            Series::new("volume", volumes),
+        ])
+        .map_err(|e| AppError::new("SetModule", eyre!(e.to_string())))?;
+
+        // Rank by margin weighted by how often the set and its parts trade
+        let df = df
+            .lazy()
+            .with_column((col("setPrice") - col("partsCost")).alias("margin"))
+            .with_column((col("margin") * col("volume")).alias("score"))
+            .collect()
+            .map_err(|e| AppError::new("SetModule", eyre!(e.to_string())))?;
+        helper::sort_dataframe(df, "score", true)
+    }
+
+    // Builds a map of name -> value for the given order type from the averaged price history.
+    fn get_price_map(
+        &self,
+        df: &DataFrame,
+        order_type: &str,
+        column: &str,
+    ) -> Result<HashMap<String, f64>, AppError> {
+        let df = helper::filter_and_extract(
+            df.clone(),
+            Some(col("order_type").eq(lit(order_type))),
+            vec!["name", column],
+        )?;
+        let names = df
+            .column("name")
+            .map_err(|e| AppError::new("SetModule", eyre!(e.to_string())))?
+            .utf8()
+            .map_err(|e| AppError::new("SetModule", eyre!(e.to_string())))?
+            .into_iter()
+            .collect::<Vec<_>>();
+        let values = df
+            .column(column)
+            .map_err(|e| AppError::new("SetModule", eyre!(e.to_string())))?
+            .f64()
+            .map_err(|e| AppError::new("SetModule", eyre!(e.to_string())))?
+            .into_iter()
+            .collect::<Vec<_>>();
+        let mut map = HashMap::new();
+        for (name, value) in names.into_iter().zip(values) {
+            if let (Some(name), Some(value)) = (name, value) {
+                if value > 0.0 {
+                    map.insert(name.to_string(), value);
+                }
+            }
+        }
+        Ok(map)
+    }
+
+    // Returns the highest buy order and the lowest sell order for an item from the live orders.
+    async fn get_live_prices(
+        &self,
+        url_name: &str,
+    ) -> Result<(Option<i64>, Option<i64>), AppError> {
+        let wfm = self.client.wfm.lock()?.clone();
+        let live_orders_df = wfm.orders().get_ordres_by_item(url_name).await?;
+        if live_orders_df.height() == 0 {
+            return Ok((None, None));
+        }
+        let (buy_orders_df, sell_orders_df, buyers, sellers, _range) = self
+            .client
+            .item()
+            .restructure_live_order_df(&live_orders_df)
+            .await?;
+
+        let mut highest_buy = None;
+        if buyers > 0 {
+            highest_buy =
+                match helper::get_column_value(buy_orders_df, None, "platinum", ColumnType::I64)? {
+                    ColumnValue::I64(value) => value,
+                    _ => return Err(AppError::new("SetModule", eyre!("Expected i64 values"))),
+                };
+        }
+        let mut lowest_sell = None;
+        if sellers > 0 {
+            lowest_sell = match helper::get_column_value(
+                sell_orders_df,
+                None,
+                "platinum",
+                ColumnType::I64,
+            )? {
+                ColumnValue::I64(value) => value,
+                _ => return Err(AppError::new("SetModule", eyre!("Expected i64 values"))),
+            };
+        }
+        Ok((highest_buy, lowest_sell))
+    }
+
+    // Checks a set against the live orders.
+    // Returns the parts with the price to bid, the total parts cost and the set price.
+    async fn get_live_set(
+        &self,
+        url_name: &str,
+    ) -> Result<Option<(Vec<SetPartStruct>, f64, f64)>, AppError> {
+        let wfm = self.client.wfm.lock()?.clone();
+        let item_info = wfm.items().get_item(url_name.to_string()).await?;
+
+        let set_price = match self.get_live_prices(url_name).await?.1 {
+            Some(price) => price as f64,
+            None => return Ok(None),
+        };
+
+        let mut parts: Vec<SetPartStruct> = vec![];
+        let mut parts_cost = 0.0;
+        for part in item_info
+            .items_in_set
+            .iter()
+            .filter(|p| !p.set_root.unwrap_or(false))
+        {
+            let part_url = match part.url_name.clone() {
+                Some(url) => url,
+                None => return Ok(None),
+            };
+            let quantity = part.quantity_for_set.unwrap_or(1);
+            // Bid one above the highest buyer, without buyers bid under the lowest seller.
+            let bid = match self.get_live_prices(&part_url).await? {
+                (Some(highest_buy), _) => highest_buy + 1,
+                (None, Some(lowest_sell)) => (lowest_sell - 1).max(1),
+                (None, None) => return Ok(None),
+            };
+            parts_cost += (bid * quantity) as f64;
+            parts.push(SetPartStruct {
+                wfm_id: part.id.clone(),
+                url: part_url,
+                quantity,
+                order_id: None,
+                listed_price: Some(bid),
+            });
+        }
+        if parts.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some((parts, parts_cost, set_price)))
+    }
+
+    pub async fn check_stock(&self) -> Result<(), AppError> {
+        let settings = self.client.settings.lock()?.clone().live_scraper;
+        if !settings.stock_set.enabled {
+            return Ok(());
+        }
+        logger::info_con("SetModule", "Run set module");
+        let db = self.client.db.lock()?.clone();
+        let min_margin = settings.stock_set.min_margin as f64;
+
+        // Update the sets we are already working on
+        let sets = db.stock_set().get_sets().await?;
+        let total = sets.len();
+        let mut current_index = total;
+        for set in sets.iter() {
+            current_index -= 1;
+            if !self.client.is_running() {
+                return Ok(());
+            }
+            self.client.send_message(
+                "set.checking",
+                Some(json!({ "name": set.url, "count": current_index, "total": total})),
+            );
+            self.check_set(set, min_margin).await?;
+        }
+
+        // Start new sets until we reach the limit
+        let open_slots = settings.stock_set.max_sets - total as i64;
+        if open_slots <= 0 {
+            return Ok(());
+        }
+        let opportunities = self
+            .get_set_arbitrage()
+            .await?
+            .lazy()
+            .filter(
+                col("margin")
+                    .gt_eq(lit(min_margin))
+                    .and(col("volume").gt_eq(lit(settings.stock_set.min_volume))),
+            )
+            .collect()
+            .map_err(|e| AppError::new("SetModule", eyre!(e.to_string())))?;
+        let names =
+            match helper::get_column_values(opportunities, None, "name", ColumnType::String)? {
+                helper::ColumnValues::String(values) => values,
+                _ => return Err(AppError::new("SetModule", eyre!("Expected string values"))),
+            };
+
+        let mut started = 0;
+        for name in names {
+            if started >= open_slots || !self.client.is_running() {
+                break;
+            }
+            if sets.iter().any(|s| s.url == name) {
+                continue;
+            }
+            self.client
+                .send_message("set.searching", Some(json!({ "name": name})));
+            let (parts, parts_cost, set_price) = match self.get_live_set(&name).await? {
+                Some(live) => live,
+                None => continue,
+            };
+            if set_price - parts_cost < min_margin {
+                logger::info_con(
+                    "SetModule",
+                    format!(
+                        "Set {} is not profitable live, set: {} parts: {}",
+                        name, set_price, parts_cost
+                    )
+                    .as_str(),
+                );
+                continue;
+            }
+            let set = db
+                .stock_set()
+                .create(&name, parts, parts_cost, set_price)
+                .await?;
+            self.check_set(&set, min_margin).await?;
+            started += 1;
+        }
+        Ok(())
+    }
+
+    async fn check_set(&self, set: &StockSetStruct, min_margin: f64) -> Result<(), AppError> {
+        let db = self.client.db.lock()?.clone();
+        let wfm = self.client.wfm.lock()?.clone();
+        let stock_items = db.stock_item().get_items().await?;
+        let my_orders = wfm.orders().get_my_orders().await?;
+
+        // Forget orders that no longer exist on warframe.market
+        let has_order = |orders: &Vec<Order>, id: &Option<String>| -> bool {
+            id.is_some() && orders.iter().any(|o| Some(o.id.clone()) == *id)
+        };
+
+        let owned = |url: &str| -> i64 {
+            stock_items
+                .iter()
+                .find(|i| i.url == url)
+                .map(|i| i.owned as i64)
+                .unwrap_or(0)
+        };
+        let complete = set.parts.0.iter().all(|p| owned(&p.url) >= p.quantity);
+        let nothing_owned = set.parts.0.iter().all(|p| owned(&p.url) == 0);
+
+        // Set is complete, stop buying and sell it as a whole
+        if complete {
+            let mut parts = set.parts.0.clone();
+            for part in parts.iter_mut() {
+                if has_order(&my_orders.buy_orders, &part.order_id) {
+                    wfm.orders()
+                        .delete(part.order_id.clone().unwrap().as_str())
+                        .await?;
+                }
+                part.order_id = None;
+            }
+            let lowest_sell = self.get_live_prices(&set.url).await?.1;
+            let mut post_price = lowest_sell
+                .unwrap_or(0)
+                .max((set.parts_cost + min_margin) as i64);
+            if post_price <= 0 {
+                post_price = (set.parts_cost + min_margin) as i64;
+            }
+            let mut set_order_id = set.set_order_id.clone();
+            if has_order(&my_orders.sell_orders, &set_order_id) {
+                if set.listed_price.unwrap_or(0) as i64 != post_price {
+                    self.client.send_message(
+                        "set.sell.updating",
+                        Some(json!({ "name": set.url, "price": post_price})),
+                    );
+                    wfm.orders()
+                        .update(
+                            set_order_id.clone().unwrap().as_str(),
+                            post_price as i32,
+                            1,
+                            true,
+                        )
+                        .await?;
+                }
+            } else {
+                self.client.send_message(
+                    "set.sell.creating",
+                    Some(json!({ "name": set.url, "price": post_price})),
+                );
+                let order = wfm
+                    .orders()
+                    .create(&set.wfm_id, "sell", post_price, 1, true, None, None)
+                    .await?;
+                set_order_id = Some(order.id);
+            }
+            db.stock_set()
+                .update_by_id(
+                    set.id,
+                    Some(parts),
+                    set_order_id,
+                    None,
+                    Some(post_price as i32),
+                    Some("selling".to_string()),
+                )
+                .await?;
+            return Ok(());
+        }
+
+        // Still buying, check that the set is still worth it
+        let live = self.get_live_set(&set.url).await?;
+        let still_profitable = match &live {
+            Some((_, parts_cost, set_price)) => set_price - parts_cost >= min_margin,
+            None => false,
+        };
+        if !still_profitable && nothing_owned {
+            logger::info_con(
+                "SetModule",
+                format!("Set {} is no longer profitable, cancelling", set.url).as_str(),
+            );
+            for part in set.parts.0.iter() {
+                if has_order(&my_orders.buy_orders, &part.order_id) {
+                    self.client
+                        .send_message("set.buy.deleting", Some(json!({ "name": part.url})));
+                    wfm.orders()
+                        .delete(part.order_id.clone().unwrap().as_str())
+                        .await?;
+                }
+            }
+            db.stock_set().delete(set.id).await?;
+            return Ok(());
+        }
+
+        // Keep the buy orders for the missing parts up to date
+        let live_parts = live.clone().map(|l| l.0).unwrap_or_default();
+        let mut parts = set.parts.0.clone();
+        for part in parts.iter_mut() {
+            let missing = part.quantity - owned(&part.url);
+            let active = has_order(&my_orders.buy_orders, &part.order_id);
+            if missing <= 0 {
+                if active {
+                    wfm.orders()
+                        .delete(part.order_id.clone().unwrap().as_str())
+                        .await?;
+                }
+                part.order_id = None;
+                continue;
+            }
+            // Once we hold parts we keep bidding at the old price when the set turned unprofitable
+            let bid = match live_parts.iter().find(|p| p.url == part.url) {
+                Some(live_part) if still_profitable => live_part.listed_price,
+                _ => part.listed_price,
+            };
+            let bid = match bid {
+                Some(bid) => bid,
+                None => continue,
+            };
+            if active {
+                if part.listed_price != Some(bid) {
+                    self.client.send_message(
+                        "set.buy.updating",
+                        Some(json!({ "name": part.url, "price": bid})),
+                    );
+                    wfm.orders()
+                        .update(
+                            part.order_id.clone().unwrap().as_str(),
+                            bid as i32,
+                            missing as i32,
+                            true,
+                        )
+                        .await?;
+                }
+            } else {
+                self.client.send_message(
+                    "set.buy.creating",
+                    Some(json!({ "name": part.url, "price": bid})),
+                );
+                let order = wfm
+                    .orders()
+                    .create(&part.wfm_id, "buy", bid, missing, true, None, None)
+                    .await?;
+                part.order_id = Some(order.id);
+            }
+            part.listed_price = Some(bid);
+        }
+        db.stock_set()
+            .update_by_id(
+                set.id,
+                Some(parts),
+                None,
+                None,
+                None,
+                Some("buying".to_string()),
+            )
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,243 @@
+use eyre::eyre;
+use polars::prelude::*;
+
+use crate::{
+    database::{
+        client::DBClient,
+        modules::watchlist::{WatchRuleStruct, WatchlistStruct},
+    },
+    error::{self, AppError},
+    helper::{self, ColumnType, ColumnValue, ColumnValues},
+    live_scraper::client::LiveScraperClient,
+    logger,
+    wfm_client::client::WFMClient,
+};
+
+pub struct WatchlistModule<'a> {
+    pub client: &'a LiveScraperClient,
+}
+
+impl<'a> WatchlistModule<'a> {
+    // Checks every watched item whose poll interval has passed and fires the rules that match.
+    // Runs from the live scraper loop and from the watchlist loop while the live scraper is stopped.
+    pub async fn check_watchlist(&self) -> Result<(), AppError> {
+        let settings = self.client.settings.lock()?.clone().watchlist;
+        if !settings.enabled {
+            return Ok(());
+        }
+        let db = self.client.db.lock()?.clone();
+        let wfm = self.client.wfm.lock()?.clone();
+        let now = chrono::Local::now();
+
+        // Only the items that have not been checked within the poll interval
+        let items = db.watchlist().get_items().await?;
+        let checked = self.client.watchlist_checked.lock()?.clone();
+        let items = items
+            .into_iter()
+            .filter(|w| {
+                checked
+                    .get(&w.id)
+                    .is_none_or(|last| now.timestamp() - last >= settings.poll_interval)
+            })
+            .collect::<Vec<_>>();
+        if items.is_empty() {
+            return Ok(());
+        }
+
+        // The median rules need the price history of the last two days
+        let needs_history = items
+            .iter()
+            .any(|w| w.rules.0.iter().any(|r| r.rule_type == "median_change"));
+        let history_df = if needs_history {
+            let price_scraper = self.client.price_scraper.lock()?.clone();
+            price_scraper.get_price_historys(Some(2)).await.ok()
+        } else {
+            None
+        };
+
+        for watch in items {
+            // One failing item should not stop the others, it is checked again after the poll interval
+            if let Err(e) = self
+                .check_item(&db, &wfm, &watch, history_df.as_ref(), now)
+                .await
+            {
+                error::create_log_file(self.client.log_file.to_owned(), &e);
+                logger::warning_con(
+                    "WatchlistModule",
+                    format!("Could not check {}: {:?}", watch.url, e).as_str(),
+                );
+            }
+            self.client
+                .watchlist_checked
+                .lock()?
+                .insert(watch.id, now.timestamp());
+        }
+        Ok(())
+    }
+
+    // Fires the rules of one watched item that match its live orders and price history.
+    async fn check_item(
+        &self,
+        db: &DBClient,
+        wfm: &WFMClient,
+        watch: &WatchlistStruct,
+        history_df: Option<&DataFrame>,
+        now: chrono::DateTime<chrono::Local>,
+    ) -> Result<(), AppError> {
+        // A watched item has no subtype, items with subtypes are watched at their first one (intact for relics)
+        let cache = db.cache.lock()?.clone();
+        let sub_type = cache
+            .items()
+            .find_type(&watch.url)?
+            .and_then(|item| item.subtypes)
+            .and_then(|subtypes| subtypes.first().cloned());
+        let live_orders_df = wfm
+            .orders()
+            .get_ordres_by_item_and_rank(&watch.url, watch.mod_rank, sub_type.as_deref())
+            .await?;
+        let (lowest_sell, highest_buy) = if live_orders_df.height() > 0 {
+            let (buy_orders_df, sell_orders_df, buyers, sellers, _range) = self
+                .client
+                .item()
+                .restructure_live_order_df(&live_orders_df)
+                .await?;
+            (
+                self.get_first_price(sell_orders_df, sellers)?,
+                self.get_first_price(buy_orders_df, buyers)?,
+            )
+        } else {
+            (None, None)
+        };
+        let median_change = match history_df {
+            Some(df) => self.get_median_change(df, watch)?,
+            None => None,
+        };
+
+        let mut rules = watch.rules.0.clone();
+        let mut fired = false;
+        for rule in rules.iter_mut() {
+            if self.is_on_cooldown(rule, now.naive_local()) {
+                continue;
+            }
+            let message = match rule.rule_type.as_str() {
+                "sell_below" => lowest_sell
+                    .filter(|price| (*price as f64) < rule.value)
+                    .map(|price| format!("Lowest sell order is {} platinum", price)),
+                "buy_above" => highest_buy
+                    .filter(|price| (*price as f64) > rule.value)
+                    .map(|price| format!("Highest buy order is {} platinum", price)),
+                "median_change" => median_change
+                    .filter(|change| change.abs() > rule.value)
+                    .map(|change| format!("Median moved {:.1}% in 24h", change)),
+                _ => None,
+            };
+            if let Some(message) = message {
+                self.notify(&watch.name, &message)?;
+                rule.last_triggered = Some(now.naive_local().to_string());
+                fired = true;
+            }
+        }
+        if fired {
+            db.watchlist()
+                .update_by_id(watch.id, None, Some(rules))
+                .await?;
+        }
+        Ok(())
+    }
+
+    fn get_first_price(&self, df: DataFrame, count: i64) -> Result<Option<i64>, AppError> {
+        if count == 0 {
+            return Ok(None);
+        }
+        match helper::get_column_value(df, None, "platinum", ColumnType::I64)? {
+            ColumnValue::I64(value) => Ok(value),
+            _ => Err(AppError::new(
+                "WatchlistModule",
+                eyre!("Expected i64 values"),
+            )),
+        }
+    }
+
+    // Percentage change of the closed median between the two newest days.
+    fn get_median_change(
+        &self,
+        df: &DataFrame,
+        watch: &WatchlistStruct,
+    ) -> Result<Option<f64>, AppError> {
+        let rank_filter = match watch.mod_rank {
+            Some(rank) => col("mod_rank").eq(lit(rank as f64)),
+            None => col("mod_rank").is_null().or(col("mod_rank").neq(lit(0.0))),
+        };
+        let filter = col("order_type")
+            .eq(lit("closed"))
+            .and(col("name").eq(lit(watch.url.clone())))
+            .and(rank_filter);
+        let item_df = df
+            .clone()
+            .lazy()
+            .filter(filter)
+            .collect()
+            .map_err(|e| AppError::new("WatchlistModule", eyre!(e.to_string())))?;
+        let item_df = helper::sort_dataframe(item_df, "datetime", true)?;
+        let medians = match helper::get_column_values(item_df, None, "median", ColumnType::F64)? {
+            ColumnValues::F64(values) => values,
+            _ => {
+                return Err(AppError::new(
+                    "WatchlistModule",
+                    eyre!("Expected f64 values"),
+                ))
+            }
+        };
+        if medians.len() < 2 || medians[1] == 0.0 {
+            return Ok(None);
+        }
+        Ok(Some((medians[0] - medians[1]) / medians[1] * 100.0))
+    }
+
+    fn is_on_cooldown(&self, rule: &WatchRuleStruct, now: chrono::NaiveDateTime) -> bool {
+        match &rule.last_triggered {
+            Some(last) => match last.parse::<chrono::NaiveDateTime>() {
+                Ok(last) => (now - last).num_minutes() < rule.cooldown,
+                Err(_) => false,
+            },
+            None => false,
+        }
+    }
+
+    fn notify(&self, item_name: &str, message: &str) -> Result<(), AppError> {
+        let settings = self
+            .client
+            .settings
+            .lock()?
+            .clone()
+            .notifications
+            .on_watchlist_alert;
+        logger::info_con(
+            "WatchlistModule",
+            format!("{}: {}", item_name, message).as_str(),
+        );
+        let content = settings
+            .content
+            .replace("<ITEM_NAME>", item_name)
+            .replace("<MESSAGE>", message);
+        // If system notification is enabled, show it
+        if settings.system_notify {
+            self.client.mh.lock()?.show_notification(
+                settings.title.as_str(),
+                &content,
+                Some("assets/icons/icon.png"),
+                Some("Default"),
+            );
+        }
+        // If discord webhook is enabled, send it
+        if settings.discord_notify && settings.webhook.is_some() {
+            helper::send_message_to_discord(
+                settings.webhook.unwrap_or("".to_string()),
+                settings.title,
+                content,
+                settings.user_ids.clone(),
+            );
+        }
+        Ok(())
+    }
+}
@@ -9,6 +9,7 @@ use handler::MonitorHandler;
 use live_scraper::client::LiveScraperClient;
 use once_cell::sync::Lazy;
 use price_scraper::PriceScraper;
+use scheduler::Scheduler;
 use settings::SettingsState;
 use std::path::{self, PathBuf};
 use std::sync::Arc;
@@ -34,6 +35,7 @@ mod live_scraper;
 mod logger;
 mod price_scraper;
 mod rate_limiter;
+mod scheduler;
 mod settings;
 mod system_tray;
 mod wf_ee_log_parser;
@@ -83,6 +85,8 @@ async fn setup_async(app: &mut App) -> Result<(), AppError> {
     let price_scraper: Arc<Mutex<PriceScraper>> = Arc::new(Mutex::new(PriceScraper::new(
         Arc::clone(&wfm_client),
         Arc::clone(&auth_arc),
+        Arc::clone(&database_client),
+        Arc::clone(&settings_arc),
     )));
     app.manage(price_scraper.clone());
 
@@ -95,13 +99,30 @@ async fn setup_async(app: &mut App) -> Result<(), AppError> {
         Arc::clone(&database_client),
         Arc::clone(&monitor_handler_arc),
     );
-    app.manage(Arc::new(Mutex::new(live_scraper)));
+    live_scraper.start_watchlist_loop();
+    let live_scraper_arc = Arc::new(Mutex::new(live_scraper));
+    app.manage(live_scraper_arc.clone());
+
+    // create and manage Scheduler state
+    let scheduler = Scheduler::new(
+        Arc::clone(&settings_arc),
+        Arc::clone(&monitor_handler_arc),
+        Arc::clone(&cache_arc),
+        Arc::clone(&database_client),
+        Arc::clone(&price_scraper),
+    );
+    scheduler.start();
+    app.manage(Arc::new(Mutex::new(scheduler)));
 
     // create and manage WhisperScraper state
     let ee_log = EELogParser::new(
         Arc::clone(&settings_arc),
         Arc::clone(&monitor_handler_arc),
         Arc::clone(&cache_arc),
+        Arc::clone(&database_client),
+        Arc::clone(&auth_arc),
+        Arc::clone(&wfm_client),
+        Arc::clone(&live_scraper_arc),
     );
     app.manage(Arc::new(Mutex::new(ee_log)));
     // create and manage WhisperScraper state
@@ -203,8 +224,30 @@ fn main() {
             commands::stock::delete_riven_stock,
             commands::stock::update_riven_stock,
             commands::stock::sell_riven_stock,
+            commands::stock::create_auction_stock,
+            commands::stock::update_auction_stock,
+            commands::stock::delete_auction_stock,
+            commands::stock::sell_auction_stock,
+            commands::stock::delete_set_stock,
+            commands::stock::sell_set_stock,
+            // Analytics commands
+            commands::analytics::get_set_arbitrage,
+            commands::analytics::get_ducat_ranking,
+            commands::analytics::refresh_ducat_values,
+            commands::analytics::get_rank_up_profit,
+            commands::analytics::get_market_indicators,
+            commands::watchlist::create_watchlist_item,
+            commands::watchlist::update_watchlist_item,
+            commands::watchlist::delete_watchlist_item,
+            commands::trade_partner::get_trade_partners,
+            commands::trade_partner::update_trade_partner,
+            commands::scheduler::get_scheduler_jobs,
+            commands::scheduler::run_scheduler_job,
+            commands::ee_log::replay_ee_log,
+            commands::ee_log::benchmark_ee_log,
             // Warframe Market Commands
             wfm_client::modules::auction::auction_search,
+            wfm_client::modules::auction::auction_search_weapon,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
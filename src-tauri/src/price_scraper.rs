@@ -1,91 +1,282 @@
-use crate::enums::LogLevel;
-use crate::error::{ApiResult, AppError, ErrorApiResponse};
+use crate::database::client::DBClient;
+use crate::database::modules::price_history::PriceHistoryStruct;
+use crate::enums::{LogLevel, PriceSource};
+use crate::error::{self, ApiResult, AppError, ErrorApiResponse};
+use crate::settings::SettingsState;
+use crate::structs::{ItemStatistic, ItemStatistics};
 use crate::wfm_client::client::WFMClient;
 use crate::{helper, logger};
 use eyre::eyre;
 use polars::prelude::*;
 use reqwest::{Client, Method, Url};
+use serde::Deserialize;
 use serde_json::{json, Value};
-use std::path::Path;
-use std::sync::Mutex;
-use std::time::{SystemTime, UNIX_EPOCH};
-use std::{
-    collections::HashMap,
-    fs::{self, File},
-    io::BufWriter,
-};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
 extern crate chrono;
 
 use crate::auth::AuthState;
 
-// Structs for the Warframe Market API
+// Structs for the relics.run price history
+
+// One row of an item for a day, there is a row per order type ("buy", "sell", "closed"),
+// per mod rank and per subtype.
+#[derive(Deserialize, Clone, Debug)]
+pub struct PriceHistoryEntry {
+    #[serde(rename = "datetime")]
+    pub datetime: String,
+
+    #[serde(rename = "order_type")]
+    pub order_type: String,
+
+    #[serde(rename = "volume")]
+    pub volume: Option<f64>,
+
+    #[serde(rename = "min_price")]
+    pub min_price: Option<f64>,
+
+    #[serde(rename = "max_price")]
+    pub max_price: Option<f64>,
+
+    #[serde(rename = "avg_price")]
+    pub avg_price: Option<f64>,
+
+    #[serde(rename = "median")]
+    pub median: Option<f64>,
+
+    #[serde(rename = "mod_rank")]
+    pub mod_rank: Option<f64>,
+
+    #[serde(rename = "subtype")]
+    pub subtype: Option<String>,
+}
+
+// The price history of a day keyed by the item name.
+pub type PriceHistoryDay = HashMap<String, Vec<PriceHistoryEntry>>;
+
+// What was left out while parsing a day.
+#[derive(Default, Clone, Debug)]
+pub struct PriceDayReport {
+    pub items: usize,
+    pub partial_items: usize,
+    pub anomalies: usize,
+    pub unmapped: Vec<String>,
+}
+
+// Highest median that is still taken as a real trade price.
+const MAX_MEDIAN: f64 = 100_000.0;
+// A day with less than this share of complete items is not stored and downloaded again on the next run.
+const MIN_COMPLETE_SHARE: f64 = 0.5;
+
+// Item names and ranks from warframe.market used to map the price history.
+struct PriceItemMaps {
+    url: HashMap<String, String>,
+    id: HashMap<String, String>,
+    max_rank: HashMap<String, i64>,
+}
 
 #[derive(Clone)]
 pub struct PriceScraper {
-    csv_path: String,
-    csv_backop_path: String,
+    client: Client,
     wfm: Arc<Mutex<WFMClient>>,
     auth: Arc<Mutex<AuthState>>,
+    db: Arc<Mutex<DBClient>>,
+    settings: Arc<Mutex<SettingsState>>,
 }
 
 impl PriceScraper {
-    pub fn new(wfm: Arc<Mutex<WFMClient>>, auth: Arc<Mutex<AuthState>>) -> Self {
+    pub fn new(
+        wfm: Arc<Mutex<WFMClient>>,
+        auth: Arc<Mutex<AuthState>>,
+        db: Arc<Mutex<DBClient>>,
+        settings: Arc<Mutex<SettingsState>>,
+    ) -> Self {
         PriceScraper {
-            csv_path: helper::get_app_roaming_path()
-                .join("price_data.csv")
-                .to_str()
-                .unwrap()
-                .to_string(),
-            csv_backop_path: helper::get_app_roaming_path()
-                .join("price_data_backup.csv")
-                .to_str()
-                .unwrap()
-                .to_string(),
+            client: Client::new(),
             wfm,
             auth,
+            db,
+            settings,
         }
     }
-    /// Reads the price history data from a CSV file and returns it as a DataFrame.
-    /// If the backup file is available, it is used instead of the main file.
-    pub fn get_price_historys(&self) -> Result<DataFrame, AppError> {
-        // Try to read from "allItemDataBackup.csv", and if it fails, read from "allItemData.csv".
-        let file = File::open(&self.csv_path)
-            .or_else(|_| File::open(&self.csv_backop_path))
-            .map_err(|e| AppError::new("PriceScraper", eyre!("Error opening csv file: {}", e)))?;
-
-        // Parse the CSV file into a DataFrame
-        CsvReader::new(file)
-            .infer_schema(None)
-            .has_header(true)
-            .finish()
+    /// Returns the price history of the newest `days` stored days as a DataFrame,
+    /// `None` uses the window from the settings.
+    /// Only popular items are returned, an item is popular when it has a ranked row for every order type on every day.
+    /// The warframe.market days only hold the items we trade, so popularity is counted on the relics.run days.
+    pub async fn get_price_historys(&self, days: Option<i64>) -> Result<DataFrame, AppError> {
+        let db = self.db.lock()?.clone();
+        let days = days
+            .unwrap_or(self.settings.lock()?.price_scraper.window_days)
+            .max(1);
+
+        // Use the newest stored days, so a stale history still returns a full window
+        let stored_days = db.price_history().get_days(None).await?;
+        let window = stored_days
+            .into_iter()
+            .take(days as usize)
+            .collect::<Vec<_>>();
+        if window.is_empty() {
+            return Err(AppError::new(
+                "PriceScraper",
+                eyre!("No price history found, run the price scraper first"),
+            ));
+        }
+        let full_df = db
+            .price_history()
+            .get_window_df(window.last().unwrap(), window.first().unwrap(), None)
+            .await?;
+
+        // Only the warframe.market source was used, there are no relics.run days to count on
+        let relics_run_days = db
+            .price_history()
+            .get_days(Some(PriceSource::RelicsRun.as_str()))
+            .await?
+            .into_iter()
+            .filter(|day| window.contains(day))
+            .count();
+        let (counted_source, counted_days) = if relics_run_days > 0 {
+            (PriceSource::RelicsRun.as_str(), relics_run_days)
+        } else {
+            (PriceSource::WarframeMarket.as_str(), window.len())
+        };
+
+        // Count the rows per name, unranked rows don't count towards popularity
+        let popular_items = full_df
+            .clone()
+            .lazy()
+            .filter(col("source").eq(lit(counted_source)))
+            .filter(col("mod_rank").neq(0).or(col("mod_rank").is_null()))
+            .groupby(["name"])
+            .agg(&[col("name").count().alias("name_count")])
+            .filter(col("name_count").gt_eq(lit(3 * counted_days as u32)))
+            .collect()
+            .map_err(|e| AppError::new("PriceScraper", eyre!(e.to_string())))?;
+
+        // Filter out items that are not popular
+        let popular_items_s = popular_items
+            .column("name")
+            .map_err(|e| AppError::new("PriceScraper", eyre!(e.to_string())))?;
+        let mask = full_df
+            .column("name")
+            .map_err(|e| AppError::new("PriceScraper", eyre!(e.to_string())))?
+            .is_in(&popular_items_s)
+            .map_err(|e| AppError::new("PriceScraper", eyre!(e.to_string())))?;
+        full_df
+            .filter(&mask)
+            .and_then(|df| df.drop("source"))
             .map_err(|e| AppError::new("PriceScraper", eyre!(e.to_string())))
     }
 
-    pub fn get_status(&self) -> Option<u128> {
-        // Try to read from "allItemDataBackup.csv", and if it fails, read from "allItemData.csv".
-        let file = File::open(&self.csv_path).or_else(|_| File::open(&self.csv_backop_path));
-        match file {
-            Ok(file) => Some(
-                file.metadata()
-                    .unwrap()
-                    .modified()
-                    .unwrap()
-                    .duration_since(UNIX_EPOCH)
-                    .unwrap()
-                    .as_millis(),
-            ),
-            Err(_) => None,
+    /// Returns the unix time in milliseconds of the last time price data was stored.
+    pub async fn get_status(&self) -> Result<Option<i64>, AppError> {
+        let db = self.db.lock()?.clone();
+        db.price_history().get_last_update().await
+    }
+    /// Returns the folder the raw price data of a platform is cached in.
+    fn get_cache_path(&self, platform: &str) -> Result<PathBuf, AppError> {
+        let path = helper::get_app_roaming_path()
+            .join("price_history")
+            .join(platform);
+        fs::create_dir_all(&path)
+            .map_err(|e| AppError::new("PriceScraper", eyre!(e.to_string())))?;
+        Ok(path)
+    }
+    /// Reads the cached price data of a day.
+    /// Returns None if the day is not cached or the file does not match the size and checksum it was stored with.
+    fn read_cached_day(&self, platform: &str, day: &str) -> Option<PriceHistoryDay> {
+        let path = self.get_cache_path(platform).ok()?;
+        let content = fs::read(path.join(format!("{}.json", day))).ok()?;
+        let meta: Value =
+            serde_json::from_slice(&fs::read(path.join(format!("{}.meta.json", day))).ok()?)
+                .ok()?;
+        if meta["size"].as_u64() != Some(content.len() as u64)
+            || meta["checksum"].as_str() != Some(helper::fnv1a_hash(&content).as_str())
+        {
+            logger::warning_con(
+                "PriceScraper",
+                format!(
+                    "Cached price data for {} is corrupt, downloading it again",
+                    day
+                )
+                .as_str(),
+            );
+            return None;
+        }
+        serde_json::from_slice(&content).ok()
+    }
+    /// Writes the raw price data of a day to the cache together with its size and checksum.
+    /// The data is written to a temporary file first so an interrupted run never leaves a partial file behind.
+    fn write_cached_day(&self, platform: &str, day: &str, content: &str) -> Result<(), AppError> {
+        let path = self.get_cache_path(platform)?;
+        let tmp_path = path.join(format!("{}.json.tmp", day));
+        fs::write(&tmp_path, content)
+            .map_err(|e| AppError::new("PriceScraper", eyre!(e.to_string())))?;
+        fs::rename(&tmp_path, path.join(format!("{}.json", day)))
+            .map_err(|e| AppError::new("PriceScraper", eyre!(e.to_string())))?;
+        let meta = json!({
+            "size": content.len(),
+            "checksum": helper::fnv1a_hash(content.as_bytes()),
+        });
+        fs::write(path.join(format!("{}.meta.json", day)), meta.to_string())
+            .map_err(|e| AppError::new("PriceScraper", eyre!(e.to_string())))?;
+        Ok(())
+    }
+    /// Removes the cached price data of a day so it is downloaded again.
+    fn remove_cached_day(&self, platform: &str, day: &str) -> Result<(), AppError> {
+        let path = self.get_cache_path(platform)?;
+        for file in [format!("{}.json", day), format!("{}.meta.json", day)] {
+            if path.join(&file).exists() {
+                fs::remove_file(path.join(&file))
+                    .map_err(|e| AppError::new("PriceScraper", eyre!(e.to_string())))?;
+            }
         }
+        Ok(())
     }
-    /// Returns a JSON object containing price data for the given platform and day.
+    /// Removes the cached price data of the days before `day`.
+    fn prune_cache(&self, platform: &str, day: &str) -> Result<(), AppError> {
+        let path = self.get_cache_path(platform)?;
+        let entries =
+            fs::read_dir(&path).map_err(|e| AppError::new("PriceScraper", eyre!(e.to_string())))?;
+        for entry in entries.flatten() {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            // Files are named after their day, "YYYY-MM-DD" sorts the same as the date
+            if file_name.len() >= 10 && &file_name[..10] < day {
+                fs::remove_file(entry.path())
+                    .map_err(|e| AppError::new("PriceScraper", eyre!(e.to_string())))?;
+            }
+        }
+        Ok(())
+    }
+    /// Sends the status of a single day to the frontend.
+    /// The status is one of "downloading", "done", "partial", "missing" or "error".
+    fn send_progress(&self, total: usize, current: usize, day: &str, status: &str) {
+        helper::send_message_to_window(
+            "PriceScraper:OnChange",
+            Some(json!({"max": total, "min": 0, "current": current, "day": day, "status": status})),
+        );
+    }
+    /// Returns the price data for the given platform and day.
     /// The `platform` argument should be one of "pc", "ps4", or "xb1".
     /// The `day` argument should be a string in the format "YYYY-MM-DD".
+    /// The data is read from the cache if a valid copy exists, otherwise it is downloaded and cached.
     /// If the request fails, returns a `AppError` with information about the error.
     async fn get_price_by_day(
         &self,
         platform: &str,
         day: &str,
-    ) -> Result<ApiResult<Value>, AppError> {
+    ) -> Result<ApiResult<PriceHistoryDay>, AppError> {
+        if let Some(cached) = self.read_cached_day(platform, day) {
+            return Ok(ApiResult::Success(
+                cached,
+                reqwest::header::HeaderMap::new(),
+            ));
+        }
+
         let mut url = format!("http://relics.run/history/price_history_{}.json", day);
         if platform != "pc" {
             url = format!(
@@ -93,8 +284,7 @@ impl PriceScraper {
                 platform, day
             );
         }
-        let client = Client::new();
-        let request = client.request(Method::GET, Url::parse(&url).unwrap());
+        let request = self.client.request(Method::GET, Url::parse(&url).unwrap());
         let response = request.send().await;
 
         // Define the error response
@@ -122,6 +312,7 @@ impl PriceScraper {
         let response_data = response.unwrap();
         error_def.status_code = response_data.status().as_u16() as i64;
         let headers = response_data.headers().clone();
+        let content_length = response_data.content_length();
         let content = response_data.text().await.unwrap_or_default();
         error_def.raw_response = Some(content.clone());
 
@@ -129,8 +320,27 @@ impl PriceScraper {
             return Ok(ApiResult::Error(error_def, headers));
         }
 
-        // Convert the response to a Value object
-        let response: Value = serde_json::from_str(content.as_str()).map_err(|e| {
+        // A dropped connection can leave a truncated body behind
+        if let Some(content_length) = content_length {
+            if content_length != content.len() as u64 {
+                error_def.error = "SizeMismatch".to_string();
+                error_def.messages.push(format!(
+                    "Expected {} bytes but got {}",
+                    content_length,
+                    content.len()
+                ));
+                error_def.raw_response = None;
+                return Err(AppError::new_api(
+                    "PriceScraper",
+                    error_def,
+                    eyre!(format!("Incomplete price data for day: {}", day)),
+                    LogLevel::Error,
+                ));
+            }
+        }
+
+        // Convert the response to the typed price data
+        let response: PriceHistoryDay = serde_json::from_str(content.as_str()).map_err(|e| {
             error_def.messages.push(e.to_string());
             error_def.error = "ParseError".to_string();
             AppError::new_api(
@@ -140,219 +350,262 @@ impl PriceScraper {
                 LogLevel::Critical,
             )
         })?;
+        self.write_cached_day(platform, day, &content)?;
         return Ok(ApiResult::Success(response, headers));
     }
-    /// Returns true if the given vector of item data is valid for price scraping, false otherwise.
-    /// A valid item data vector must have at least one element, and the first element must have either 3 or 6 keys.
-    /// The first element must also have a "mod_rank" key.
-    fn is_valid_price_data(&self, _name: &str, item_datas: &Vec<Value>) -> bool {
-        if item_datas.len() == 0 {
-            return false;
-        }
-        // Check if the first element has a "mod_rank" key
-        let is_mod = match item_datas[0].get("mod_rank") {
-            Some(_mod_rank) => true,
-            None => false,
-        };
-
-        if is_mod && item_datas.len() == 6 {
+    /// Returns true if a row can not be a real day of trading.
+    /// Rows without volume, negative prices and medians outside of the price range or above `MAX_MEDIAN` are anomalies.
+    fn is_anomaly(&self, entry: &PriceHistoryEntry) -> bool {
+        let volume = entry.volume.unwrap_or(0.0);
+        let min_price = entry.min_price.unwrap_or(0.0);
+        let max_price = entry.max_price.unwrap_or(0.0);
+        let median = entry.median.unwrap_or(0.0);
+        if volume <= 0.0 || min_price < 0.0 || max_price < min_price {
             return true;
         }
-        if !is_mod && item_datas.len() == 3 {
+        if entry.order_type == "closed"
+            && (median <= 0.0 || median > MAX_MEDIAN || median < min_price || median > max_price)
+        {
             return true;
         }
-        return false;
+        false
     }
-    /// Returns a map of item names to their corresponding IDs, based on the `items` list.
-    /// The map is represented as a `HashMap` with `String` keys and values.
-    async fn get_items_map_url_map(
-        &self,
-    ) -> Result<(HashMap<String, String>, HashMap<String, String>), AppError> {
+    /// Returns the maps of item names to url names, url names to ids and url names to max ranks.
+    async fn get_item_maps(&self) -> Result<PriceItemMaps, AppError> {
         let wfm = self.wfm.lock()?.clone();
 
         let items = wfm.items().get_all_items().await?;
 
-        let item_map_url: std::collections::HashMap<String, String> = items
-            .iter()
-            .map(|item| (item.item_name.clone(), item.url_name.clone()))
-            .collect();
-        let item_map_id: std::collections::HashMap<String, String> = items
-            .iter()
-            .map(|item| (item.url_name.clone(), item.id.clone()))
-            .collect();
-        Ok((item_map_url, item_map_id))
+        Ok(PriceItemMaps {
+            url: items
+                .iter()
+                .map(|item| (item.item_name.clone(), item.url_name.clone()))
+                .collect(),
+            id: items
+                .iter()
+                .map(|item| (item.url_name.clone(), item.id.clone()))
+                .collect(),
+            max_rank: items
+                .iter()
+                .filter_map(|item| item.mod_max_rank.map(|rank| (item.url_name.clone(), rank)))
+                .collect(),
+        })
+    }
+    /// Converts the price data of one day into database rows.
+    /// Every item has a row per order type for every rank and subtype. A rank or subtype that
+    /// misses an order type is kept with the rows it has and counted as partial.
+    /// Ranks an item can not have and anomalies are left out, items without a url name are reported.
+    fn parse_price_day(
+        &self,
+        day: &str,
+        items: &PriceHistoryDay,
+        maps: &PriceItemMaps,
+    ) -> (Vec<PriceHistoryStruct>, PriceDayReport) {
+        let mut rows: Vec<PriceHistoryStruct> = vec![];
+        let mut report = PriceDayReport::default();
+        for (item_name, entries) in items {
+            if entries.is_empty() {
+                continue;
+            }
+            report.items += 1;
+
+            // Get the url_name and id for the item
+            let url_name = match maps.url.get(item_name) {
+                Some(url_name) => url_name.clone(),
+                None => {
+                    report.unmapped.push(item_name.clone());
+                    continue;
+                }
+            };
+            let id = maps.id.get(&url_name).cloned().unwrap_or_default();
+            let max_rank = maps.max_rank.get(&url_name).cloned();
+
+            // (mod_rank, subtype) -> order types seen
+            let mut groups: HashMap<(i64, String), HashSet<String>> = HashMap::new();
+            for entry in entries {
+                // Unranked mod and arcane rows are kept for the rank-up calculator.
+                let mod_rank = entry.mod_rank.map(|v| v as i64).unwrap_or(-1);
+                let valid_rank = match max_rank {
+                    Some(max_rank) => mod_rank >= 0 && mod_rank <= max_rank,
+                    None => mod_rank == -1,
+                };
+                if !valid_rank {
+                    report.anomalies += 1;
+                    continue;
+                }
+                let sub_type = entry.subtype.clone().unwrap_or_default();
+                let order_types = groups.entry((mod_rank, sub_type)).or_default();
+                // A second row for the same order type is a duplicate
+                if !order_types.insert(entry.order_type.clone()) || self.is_anomaly(entry) {
+                    report.anomalies += 1;
+                    continue;
+                }
+                rows.push(self.entry_to_row(
+                    day,
+                    &url_name,
+                    &id,
+                    entry,
+                    PriceSource::RelicsRun.as_str(),
+                ));
+            }
+            if groups.values().any(|order_types| order_types.len() < 3) {
+                report.partial_items += 1;
+            }
+        }
+        (rows, report)
     }
+    /// Stores the price history of the last `days` days from the price source in the settings and
+    /// removes the days older than the retention from the settings.
+    /// Returns the number of rows that were stored.
     pub async fn generate(&self, days: i64) -> Result<i64, AppError> {
-        let auth = self.auth.lock().unwrap().clone();
-        // Should only get 7 days of data
-        let valid_days = 7;
-        let csv_path: &Path = Path::new(self.csv_path.as_str());
-        let csv_backop_path = Path::new(self.csv_backop_path.as_str());
-        if csv_path.exists() {
-            logger::debug_con(
-                "PriceScraper",
-                format!("Backuping csv file: {}", self.csv_path).as_str(),
-            );
-            fs::copy(csv_path, csv_backop_path)
-                .map_err(|e| AppError::new("PriceScraper", eyre!(e.to_string())))?;
+        let auth = self.auth.lock()?.clone();
+        let db = self.db.lock()?.clone();
+        let settings = self.settings.lock()?.clone().price_scraper;
+        let retention_days = settings.retention_days.max(7);
+        let days = days.min(retention_days);
+
+        let (mut inserted, total, failed_days, unmapped) =
+            if settings.source != PriceSource::WarframeMarket {
+                self.generate_from_relics_run(days).await?
+            } else {
+                (0, 0, vec![], vec![])
+            };
+
+        // Fall back to warframe.market when relics.run is down or has not published yesterday yet
+        let newest_day = db
+            .price_history()
+            .get_days(Some(PriceSource::RelicsRun.as_str()))
+            .await?
+            .first()
+            .cloned();
+        let lagging = newest_day != helper::last_x_days(1).first().cloned();
+        if settings.source == PriceSource::WarframeMarket
+            || (settings.source == PriceSource::Auto && (!failed_days.is_empty() || lagging))
+        {
+            inserted += self.generate_from_wfm(days).await?;
         }
-        let last_days = helper::last_x_days(days).clone();
-        let mut dataframes: Vec<DataFrame> = Vec::new();
-        let (url_map, id_map) = self.get_items_map_url_map().await?;
 
-        let mut found_data = 0;
+        // Drop the days that fell out of the retention
+        if let Some(oldest_day) = helper::last_x_days(retention_days).last() {
+            let removed = db.price_history().delete_before(oldest_day).await?;
+            if removed > 0 {
+                logger::info_con(
+                    "PriceScraper",
+                    format!("Removed {} rows older than {}", removed, oldest_day).as_str(),
+                );
+            }
+            self.prune_cache(&auth.platform, oldest_day)?;
+        }
+        helper::send_message_to_window(
+            "PriceScraper:Complete",
+            Some(json!({ "max": total, "failed": failed_days, "unmapped": unmapped })),
+        );
+        Ok(inserted)
+    }
+    /// Downloads the days of the last `days` days that are not stored yet from relics.run.
+    /// Days are downloaded in parallel and cached on disk, an interrupted run continues where it stopped.
+    /// A day that only has warframe.market rows is missing, its rows are replaced once relics.run has it.
+    /// Returns the number of stored rows, the number of days, the failed days and the unmapped item names.
+    async fn generate_from_relics_run(
+        &self,
+        days: i64,
+    ) -> Result<(i64, usize, Vec<String>, Vec<Value>), AppError> {
+        let auth = self.auth.lock()?.clone();
+        let db = self.db.lock()?.clone();
+        let settings = self.settings.lock()?.clone().price_scraper;
+
+        let stored_days = db
+            .price_history()
+            .get_days(Some(PriceSource::RelicsRun.as_str()))
+            .await?;
+        let missing_days = helper::last_x_days(days)
+            .into_iter()
+            .filter(|day| !stored_days.contains(day))
+            .collect::<Vec<_>>();
+        logger::info_con(
+            "PriceScraper",
+            format!("Missing price data for {} days", missing_days.len()).as_str(),
+        );
 
-        for day in last_days.clone() {
-            if found_data >= valid_days {
-                continue;
+        let maps = if missing_days.is_empty() {
+            PriceItemMaps {
+                url: HashMap::new(),
+                id: HashMap::new(),
+                max_rank: HashMap::new(),
             }
+        } else {
+            self.get_item_maps().await?
+        };
+        let total = missing_days.len();
+        let completed = Arc::new(AtomicUsize::new(0));
+        let semaphore = Arc::new(Semaphore::new(
+            settings.max_concurrent_downloads.max(1) as usize
+        ));
+        let mut tasks = JoinSet::new();
+
+        for day in missing_days {
+            let scraper = self.clone();
+            let semaphore = semaphore.clone();
+            let completed = completed.clone();
+            let platform = auth.platform.clone();
+            tasks.spawn(async move {
+                let _permit = semaphore.acquire_owned().await;
+                scraper.send_progress(total, completed.load(Ordering::SeqCst), &day, "downloading");
+                let result = scraper.get_price_by_day(&platform, &day).await;
+                (day, result)
+            });
+        }
 
-            // Get the price data for the day for all items
-            match self.get_price_by_day(auth.platform.as_str(), &day).await {
+        // Store the days as they come in, a day is only stored once it is complete
+        let mut inserted = 0;
+        let mut failed_days: Vec<String> = vec![];
+        let mut unmapped: HashSet<String> = HashSet::new();
+        while let Some(task) = tasks.join_next().await {
+            let (day, result) =
+                task.map_err(|e| AppError::new("PriceScraper", eyre!(e.to_string())))?;
+            let current = completed.fetch_add(1, Ordering::SeqCst) + 1;
+            match result {
                 Ok(ApiResult::Success(items, _headers)) => {
-                    found_data += 1;
                     logger::info_con(
                         "PriceScraper",
                         format!("Getting data for day: {}", day).as_str(),
                     );
-                    helper::send_message_to_window(
-                        "PriceScraper:OnChange",
-                        Some(json!({"max": valid_days, "min": 0, "current": found_data})),
-                    );
-                    if let Value::Object(map) = &items {
-                        for (item_name, item_data_list) in map {
-                            if let Value::Array(array) = item_data_list {
-                                if !self.is_valid_price_data(&item_name, array) {
-                                    // println!("Invalid price data for item: {}", item_name);
-                                    continue;
-                                }
-
-                                // Get the url_name and id for the item
-                                let url_name = url_map
-                                    .get(item_name)
-                                    .unwrap_or(&"not_found".to_string())
-                                    .clone();
-
-                                // Get the id for the item
-                                let id = id_map
-                                    .get(&url_name)
-                                    .unwrap_or(&"not_found".to_string())
-                                    .clone();
-
-                                let name_vec: Vec<Option<String>> = array
-                                    .iter()
-                                    .map(|_item_data| Some(url_name.clone()))
-                                    .collect();
-
-                                let id_vec: Vec<Option<String>> =
-                                    array.iter().map(|_item_data| Some(id.clone())).collect();
-
-                                let order_type_vec: Vec<Option<String>> = array
-                                    .iter()
-                                    .map(|item_data| {
-                                        item_data
-                                            .get("order_type")
-                                            .and_then(|v| v.as_str())
-                                            .map(String::from)
-                                    })
-                                    .collect();
-
-                                let volume_vec: Vec<Option<i64>> = array
-                                    .iter()
-                                    .map(|item_data| {
-                                        item_data
-                                            .get("volume")
-                                            .and_then(|v| v.as_i64())
-                                            .map(i64::from)
-                                    })
-                                    .collect();
-
-                                let datetime_vec: Vec<Option<String>> = array
-                                    .iter()
-                                    .map(|item_data| {
-                                        item_data
-                                            .get("datetime")
-                                            .and_then(|v| v.as_str())
-                                            .map(String::from)
-                                    })
-                                    .collect();
-
-                                let max_price_vec: Vec<Option<f64>> = array
-                                    .iter()
-                                    .map(|item_data| {
-                                        item_data.get("max_price").and_then(|v| v.as_f64())
-                                    })
-                                    .collect();
-
-                                let min_price_vec: Vec<Option<f64>> = array
-                                    .iter()
-                                    .map(|item_data| {
-                                        item_data.get("min_price").and_then(|v| v.as_f64())
-                                    })
-                                    .collect();
-
-                                let avg_price_vec: Vec<Option<f64>> = array
-                                    .iter()
-                                    .map(|item_data| {
-                                        item_data.get("avg_price").and_then(|v| v.as_f64())
-                                    })
-                                    .collect();
-
-                                let mod_rank_vec: Vec<Option<f64>> = array
-                                    .iter()
-                                    .map(|item_data| {
-                                        item_data.get("mod_rank").and_then(|v| v.as_f64())
-                                    })
-                                    .collect();
-
-                                let median_vec: Vec<Option<f64>> = array
-                                    .iter()
-                                    .map(|item_data| {
-                                        item_data.get("median").and_then(|v| v.as_f64())
-                                    })
-                                    .collect();
-
-                                let df = DataFrame::new_no_checks(vec![
-                                    Series::new("name", name_vec),
-                                    Series::new("datetime", datetime_vec),
-                                    Series::new("order_type", order_type_vec),
-                                    Series::new("volume", volume_vec),
-                                    Series::new("min_price", min_price_vec),
-                                    Series::new("max_price", max_price_vec),
-                                    Series::new("avg_price", avg_price_vec),
-                                    Series::new("mod_rank", mod_rank_vec),
-                                    Series::new("median", median_vec),
-                                    Series::new("item_id", id_vec),
-                                ]);
-                                // dump_dataframe(&mut df, format!("{}.csv", item_name).as_str())?;
-
-                                let df: DataFrame = df
-                                    .clone()
-                                    .lazy()
-                                    .fill_nan(lit(0.0).alias("max_price"))
-                                    .fill_nan(lit(0.0).alias("min_price"))
-                                    .with_column(
-                                        (col("max_price") - col("min_price")).alias("range"),
-                                    )
-                                    .collect()
-                                    .map_err(|e| {
-                                        AppError::new("PriceScraper", eyre!(e.to_string()))
-                                    })?;
-
-                                // Filter out items that are mod_rank 0.
-                                let df = df
-                                    .clone()
-                                    .lazy()
-                                    .filter(col("mod_rank").neq(0).or(col("mod_rank").is_null()))
-                                    .collect()
-                                    .map_err(|e| {
-                                        AppError::new("PriceScraper", eyre!(e.to_string()))
-                                    })?;
-                                // dump_dataframe(&mut df, format!("{} {}.csv", day, item_name).as_str())?;
-                                dataframes.push(df);
-                            }
-                        }
+                    let (rows, report) = self.parse_price_day(&day, &items, &maps);
+                    unmapped.extend(report.unmapped.iter().cloned());
+                    let complete = report.items - report.partial_items - report.unmapped.len();
+                    if report.items > 0
+                        && (complete as f64) < report.items as f64 * MIN_COMPLETE_SHARE
+                    {
+                        // The day was published before it was complete, try it again on the next run
+                        logger::warning_con(
+                            "PriceScraper",
+                            format!(
+                                "Price data for {} is partial, {} of {} items are complete",
+                                day, complete, report.items
+                            )
+                            .as_str(),
+                        );
+                        self.remove_cached_day(&auth.platform, &day)?;
+                        failed_days.push(day.clone());
+                        self.send_progress(total, current, &day, "partial");
+                        continue;
+                    }
+                    if report.anomalies > 0 || report.partial_items > 0 {
+                        logger::info_file(
+                            "PriceScraper",
+                            format!(
+                                "Day {}: left out {} anomalies, {} partial items",
+                                day, report.anomalies, report.partial_items
+                            )
+                            .as_str(),
+                            Some("price_scraper.log"),
+                        );
                     }
+                    db.price_history()
+                        .delete_day(&day, PriceSource::WarframeMarket.as_str())
+                        .await?;
+                    inserted += db.price_history().upsert(rows).await?;
+                    self.send_progress(total, current, &day, "done");
                 }
                 Ok(ApiResult::Error(e, _headers)) => {
                     if e.status_code == 404 {
@@ -360,89 +613,247 @@ impl PriceScraper {
                             "PriceScraper",
                             format!("No data for day: {}", day).as_str(),
                         );
+                        self.send_progress(total, current, &day, "missing");
                     } else {
                         logger::error_file(
                             "PriceScraper",
                             format!("Error getting data for day: {}", day).as_str(),
                             Some("price_scraper.log"),
                         );
+                        failed_days.push(day.clone());
+                        self.send_progress(total, current, &day, "error");
                     }
                 }
-                Err(e) => return Err(e),
+                Err(e) => {
+                    error::create_log_file("price_scraper.log".to_string(), &e);
+                    failed_days.push(day.clone());
+                    self.send_progress(total, current, &day, "error");
+                }
             }
         }
+        if !failed_days.is_empty() {
+            logger::warning_con(
+                "PriceScraper",
+                format!("Failed to get data for days: {}", failed_days.join(", ")).as_str(),
+            );
+        }
+
+        // Report the item names that have no warframe.market url name, with the closest known names
+        let known_names = maps.url.keys().cloned().collect::<Vec<_>>();
+        let mut unmapped = unmapped.into_iter().collect::<Vec<_>>();
+        unmapped.sort();
+        let unmapped = unmapped
+            .into_iter()
+            .map(|name| {
+                let suggestions = helper::fuzzy_suggestions(&name, &known_names, 3);
+                logger::warning_file(
+                    "PriceScraper",
+                    format!(
+                        "Unmapped item name: {}, did you mean: {:?}",
+                        name, suggestions
+                    )
+                    .as_str(),
+                    Some("price_scraper.log"),
+                );
+                json!({ "name": name, "suggestions": suggestions })
+            })
+            .collect::<Vec<_>>();
+        Ok((inserted, total, failed_days, unmapped))
+    }
+    /// Stores the price history of the whitelisted and stocked items from the warframe.market statistics.
+    /// The requests go through the WFMClient rate limiter, so this is only done for the items we trade.
+    /// Days that relics.run already has are left alone.
+    /// Returns the number of rows that were stored.
+    async fn generate_from_wfm(&self, days: i64) -> Result<i64, AppError> {
+        let wfm = self.wfm.lock()?.clone();
+        let db = self.db.lock()?.clone();
+        let cache = db.cache.lock()?.clone();
+        let whitelist = self
+            .settings
+            .lock()?
+            .clone()
+            .live_scraper
+            .stock_item
+            .whitelist;
+
+        let mut items = db.stock_item().get_items_names().await?;
+        items.extend(whitelist);
+        items.sort();
+        items.dedup();
+
+        let relics_run_days = db
+            .price_history()
+            .get_days(Some(PriceSource::RelicsRun.as_str()))
+            .await?;
+        let window = helper::last_x_days(days)
+            .into_iter()
+            .filter(|day| !relics_run_days.contains(day))
+            .collect::<Vec<_>>();
+        if window.is_empty() {
+            return Ok(0);
+        }
+        let total = items.len();
+        let mut inserted = 0;
+        for (index, url_name) in items.iter().enumerate() {
+            helper::send_message_to_window(
+                "PriceScraper:OnChange",
+                Some(
+                    json!({"max": total, "min": 0, "current": index, "item": url_name, "status": "downloading"}),
+                ),
+            );
+            let item_id = match cache.items().find_type(url_name)? {
+                Some(item) => item.id,
+                None => continue,
+            };
+            let statistics = match wfm.items().get_item_statistics(url_name).await {
+                Ok(statistics) => statistics,
+                Err(e) => {
+                    error::create_log_file("price_scraper.log".to_string(), &e);
+                    continue;
+                }
+            };
+            let rows = self.parse_item_statistics(url_name, &item_id, &statistics, &window);
+            inserted += db.price_history().upsert(rows).await?;
+        }
         logger::info_con(
             "PriceScraper",
             format!(
-                "Finished getting price data for all days. Merging dataframes... {:?}",
-                dataframes.len()
+                "Stored {} rows from warframe.market for {} items",
+                inserted, total
             )
             .as_str(),
         );
-        let full_df = helper::merge_dataframes(dataframes)?;
-        helper::send_message_to_window("PriceScraper:Complete", Some(json!({ "max": valid_days })));
-
-        // Group by name and get the average price
-        let group_by_name = full_df
-            .clone()
-            .lazy()
-            .groupby(&["name"])
-            .agg(&[
-                // List the other columns you want to average
-                col("name").count().alias("name_count"),
-            ])
-            .collect()
-            .map_err(|e| AppError::new("PriceScraper", eyre!(e.to_string())))?;
-
-        // Get the names of the items that are popular
+        Ok(inserted)
+    }
+    /// Converts the warframe.market statistics of an item into rows with the same layout as the relics.run data.
+    /// Closed trades become "closed" rows and live orders "buy" and "sell" rows, one row per day, rank and subtype.
+    /// Days that are not in the 90 day statistics yet are built from the hourly 48 hour statistics.
+    fn parse_item_statistics(
+        &self,
+        url_name: &str,
+        item_id: &str,
+        statistics: &ItemStatistics,
+        window: &[String],
+    ) -> Vec<PriceHistoryStruct> {
+        // (day, order_type, mod_rank, subtype) -> entry
+        let mut entries: HashMap<(String, String, i64, String), PriceHistoryEntry> = HashMap::new();
+        let periods = [
+            (&statistics.statistics_closed, true),
+            (&statistics.statistics_live, false),
+        ];
+        for (period, closed) in periods.iter() {
+            let to_key = |stat: &ItemStatistic| {
+                (
+                    stat.datetime.chars().take(10).collect::<String>(),
+                    if *closed {
+                        "closed".to_string()
+                    } else {
+                        stat.order_type.clone().unwrap_or_default()
+                    },
+                    stat.mod_rank.unwrap_or(-1),
+                    stat.subtype.clone().unwrap_or_default(),
+                )
+            };
+            for stat in period.days_90.iter() {
+                let key = to_key(stat);
+                entries.insert(key.clone(), self.statistic_to_entry(&key, stat));
+            }
 
-        let popular_items = group_by_name
-            .clone()
-            .lazy()
-            .filter(col("name_count").gt_eq(21))
-            .collect()
-            .map_err(|e| AppError::new("PriceScraper", eyre!(e.to_string())))?;
+            // Sum up the hours of the days the 90 day statistics do not have yet
+            let mut hours: HashMap<(String, String, i64, String), Vec<&ItemStatistic>> =
+                HashMap::new();
+            for stat in period.hours_48.iter() {
+                let key = to_key(stat);
+                if !entries.contains_key(&key) {
+                    hours.entry(key).or_default().push(stat);
+                }
+            }
+            for (key, stats) in hours {
+                let volume: f64 = stats.iter().map(|s| s.volume).sum();
+                let weighted = |value: fn(&ItemStatistic) -> f64| {
+                    if volume > 0.0 {
+                        stats.iter().map(|s| value(s) * s.volume).sum::<f64>() / volume
+                    } else {
+                        stats.iter().map(|s| value(s)).sum::<f64>() / stats.len() as f64
+                    }
+                };
+                let day_stat = ItemStatistic {
+                    datetime: format!("{}T00:00:00.000+00:00", key.0),
+                    volume,
+                    min_price: stats.iter().map(|s| s.min_price).fold(f64::MAX, f64::min),
+                    max_price: stats.iter().map(|s| s.max_price).fold(0.0, f64::max),
+                    avg_price: weighted(|s| s.avg_price),
+                    median: weighted(|s| s.median),
+                    order_type: None,
+                    mod_rank: None,
+                    subtype: None,
+                };
+                entries.insert(key.clone(), self.statistic_to_entry(&key, &day_stat));
+            }
+        }
 
-        // Filter out items that are not popular and sort by name
-        let popular_items_s = popular_items
-            .column("name")
-            .map_err(|e| AppError::new("PriceScraper", eyre!(e.to_string())))?;
-        let mask = full_df
-            .column("name")
-            .map_err(|e| AppError::new("PriceScraper", eyre!(e.to_string())))?
-            .is_in(&popular_items_s)
-            .map_err(|e| AppError::new("PriceScraper", eyre!(e.to_string())))?;
-        let filtered_df = full_df
-            .filter(&mask)
-            .map_err(|e| AppError::new("PriceScraper", eyre!(e.to_string())))?;
-        // Sort by name
-        let mut filtered_df = filtered_df
-            .lazy()
-            .sort(
-                "name",
-                SortOptions {
-                    descending: false,
-                    nulls_last: false,
-                    multithreaded: false,
-                },
-            )
+        entries
+            .into_iter()
+            .filter(|((day, _, _, _), entry)| window.contains(day) && !self.is_anomaly(entry))
+            .map(|((day, _, _, _), entry)| {
+                self.entry_to_row(
+                    &day,
+                    url_name,
+                    item_id,
+                    &entry,
+                    PriceSource::WarframeMarket.as_str(),
+                )
+            })
             .collect()
-            .map_err(|e| AppError::new("PriceScraper", eyre!(e.to_string())))?;
-
-        // Cerate a csv file with the sorted DataFrame of price data
-        let output_file: File = File::create(csv_path)
-            .map_err(|e| AppError::new("PriceScraper", eyre!(e.to_string())))?;
-        let writer = BufWriter::new(output_file);
-        // Write the DataFrame to a CSV file
-        CsvWriter::new(writer)
-            .finish(&mut filtered_df)
-            .map_err(|e| AppError::new("PriceScraper", eyre!(e.to_string())))?;
-
-        // Delete the backup file if it exists
-        if csv_backop_path.exists() {
-            fs::remove_file(csv_backop_path)
-                .map_err(|e| AppError::new("PriceScraper", eyre!(e.to_string())))?;
+    }
+    fn statistic_to_entry(
+        &self,
+        key: &(String, String, i64, String),
+        stat: &ItemStatistic,
+    ) -> PriceHistoryEntry {
+        PriceHistoryEntry {
+            datetime: stat.datetime.clone(),
+            order_type: key.1.clone(),
+            volume: Some(stat.volume),
+            min_price: Some(stat.min_price),
+            max_price: Some(stat.max_price),
+            avg_price: Some(stat.avg_price),
+            median: Some(stat.median),
+            mod_rank: if key.2 < 0 { None } else { Some(key.2 as f64) },
+            subtype: if key.3.is_empty() {
+                None
+            } else {
+                Some(key.3.clone())
+            },
+        }
+    }
+    /// Converts a price history entry into a database row.
+    fn entry_to_row(
+        &self,
+        day: &str,
+        url_name: &str,
+        item_id: &str,
+        entry: &PriceHistoryEntry,
+        source: &str,
+    ) -> PriceHistoryStruct {
+        let min_price = entry.min_price.unwrap_or(0.0);
+        let max_price = entry.max_price.unwrap_or(0.0);
+        PriceHistoryStruct {
+            day: day.to_string(),
+            name: url_name.to_string(),
+            item_id: item_id.to_string(),
+            order_type: entry.order_type.clone(),
+            mod_rank: entry.mod_rank.map(|v| v as i64).unwrap_or(-1),
+            sub_type: entry.subtype.clone().unwrap_or_default(),
+            datetime: entry.datetime.clone(),
+            volume: entry.volume.unwrap_or(0.0) as i64,
+            min_price,
+            max_price,
+            avg_price: entry.avg_price.unwrap_or(0.0),
+            median: entry.median.unwrap_or(0.0),
+            range: max_price - min_price,
+            updated: 0,
+            source: source.to_string(),
         }
-        Ok(full_df.height() as i64)
     }
 }
@@ -0,0 +1,328 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use eyre::eyre;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{
+    cache::client::CacheClient,
+    database::client::DBClient,
+    error::{self, AppError},
+    handler::MonitorHandler,
+    helper, logger,
+    price_scraper::PriceScraper,
+    settings::{JobSettings, SettingsState},
+};
+
+// The ids of the jobs, the settings of a job are found under the same name
+pub const JOBS: [&str; 5] = [
+    "price_history",
+    "cache_refresh",
+    "database_backup",
+    "prune_logs",
+    "daily_digest",
+];
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct JobStatus {
+    pub id: String,
+    pub enabled: bool,
+    pub running: bool,
+    pub last_run: Option<String>,
+    pub next_run: Option<String>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Clone)]
+pub struct Scheduler {
+    log_file: String,
+    settings: Arc<Mutex<SettingsState>>,
+    mh: Arc<Mutex<MonitorHandler>>,
+    cache: Arc<Mutex<CacheClient>>,
+    db: Arc<Mutex<DBClient>>,
+    price_scraper: Arc<Mutex<PriceScraper>>,
+    jobs: Arc<Mutex<HashMap<String, JobStatus>>>,
+}
+
+impl Scheduler {
+    pub fn new(
+        settings: Arc<Mutex<SettingsState>>,
+        mh: Arc<Mutex<MonitorHandler>>,
+        cache: Arc<Mutex<CacheClient>>,
+        db: Arc<Mutex<DBClient>>,
+        price_scraper: Arc<Mutex<PriceScraper>>,
+    ) -> Self {
+        Scheduler {
+            log_file: "scheduler.log".to_string(),
+            settings,
+            mh,
+            cache,
+            db,
+            price_scraper,
+            jobs: Arc::new(Mutex::new(Self::read_from_file())),
+        }
+    }
+    fn get_file_path() -> PathBuf {
+        helper::get_app_roaming_path().join("scheduler.json")
+    }
+    // The last runs are kept on disk, so a daily job does not run on every start
+    fn read_from_file() -> HashMap<String, JobStatus> {
+        fs::read(Self::get_file_path())
+            .ok()
+            .and_then(|content| serde_json::from_slice(&content).ok())
+            .unwrap_or_default()
+    }
+    fn save_to_file(&self) -> Result<(), AppError> {
+        let jobs = self.jobs.lock()?.clone();
+        fs::write(Self::get_file_path(), json!(jobs).to_string())
+            .map_err(|e| AppError::new("Scheduler", eyre!(e.to_string())))?;
+        Ok(())
+    }
+    fn get_job_settings(&self, id: &str) -> Result<JobSettings, AppError> {
+        let settings = self.settings.lock()?.clone().scheduler;
+        match id {
+            "price_history" => Ok(settings.price_history),
+            "cache_refresh" => Ok(settings.cache_refresh),
+            "database_backup" => Ok(settings.database_backup),
+            "prune_logs" => Ok(settings.prune_logs),
+            "daily_digest" => Ok(settings.daily_digest),
+            _ => Err(AppError::new("Scheduler", eyre!("Unknown job: {}", id))),
+        }
+    }
+    // Returns the status of every job, the next run is the last run plus the interval from the settings.
+    pub fn get_jobs(&self) -> Result<Vec<JobStatus>, AppError> {
+        let jobs = self.jobs.lock()?.clone();
+        let now = chrono::Local::now();
+        let mut statuses = vec![];
+        for id in JOBS {
+            let settings = self.get_job_settings(id)?;
+            let mut status = jobs.get(id).cloned().unwrap_or_default();
+            status.id = id.to_string();
+            status.enabled = settings.enabled;
+            status.next_run = if settings.enabled {
+                let next_run = match &status.last_run {
+                    Some(last_run) => chrono::DateTime::parse_from_rfc3339(last_run)
+                        .map(|last_run| {
+                            last_run.with_timezone(&chrono::Local)
+                                + chrono::Duration::minutes(settings.interval.max(1))
+                        })
+                        .unwrap_or(now),
+                    None => now,
+                };
+                Some(next_run.to_rfc3339())
+            } else {
+                None
+            };
+            statuses.push(status);
+        }
+        Ok(statuses)
+    }
+    fn send_status(&self) {
+        match self.get_jobs() {
+            Ok(jobs) => helper::send_message_to_window("Scheduler:OnChange", Some(json!(jobs))),
+            Err(e) => error::create_log_file(self.log_file.to_owned(), &e),
+        }
+    }
+    // Checks every minute for jobs that are due, the jobs run one after the other.
+    pub fn start(&self) {
+        let scheduler = self.clone();
+        tauri::async_runtime::spawn(async move {
+            // Wait first so the database and cache are loaded before the first run
+            loop {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+                let jobs = match scheduler.get_jobs() {
+                    Ok(jobs) => jobs,
+                    Err(e) => {
+                        error::create_log_file(scheduler.log_file.to_owned(), &e);
+                        continue;
+                    }
+                };
+                let now = chrono::Local::now();
+                for job in jobs {
+                    let due = job.next_run.as_ref().is_some_and(|next_run| {
+                        chrono::DateTime::parse_from_rfc3339(next_run)
+                            .is_ok_and(|next_run| next_run <= now)
+                    });
+                    if job.enabled && !job.running && due {
+                        // Errors are stored on the job status by run_job
+                        scheduler.run_job(&job.id).await.ok();
+                    }
+                }
+            }
+        });
+    }
+    // Runs a job now, used by the loop and by the "run now" command.
+    pub async fn run_job(&self, id: &str) -> Result<(), AppError> {
+        self.get_job_settings(id)?;
+        {
+            let mut jobs = self.jobs.lock()?;
+            let status = jobs.entry(id.to_string()).or_default();
+            if status.running {
+                return Err(AppError::new(
+                    "Scheduler",
+                    eyre!("Job {} is already running", id),
+                ));
+            }
+            status.id = id.to_string();
+            status.running = true;
+        }
+        self.send_status();
+        logger::info_con("Scheduler", format!("Running job: {}", id).as_str());
+
+        let result = match id {
+            "price_history" => self.run_price_history().await,
+            "cache_refresh" => self.run_cache_refresh().await,
+            "database_backup" => self.run_database_backup().await,
+            "prune_logs" => self.run_prune_logs(),
+            "daily_digest" => self.run_daily_digest().await,
+            _ => Ok(()),
+        };
+
+        // A failed job waits for its next interval too, the error is kept until the next run
+        {
+            let mut jobs = self.jobs.lock()?;
+            let status = jobs.entry(id.to_string()).or_default();
+            status.running = false;
+            status.last_run = Some(chrono::Local::now().to_rfc3339());
+            status.last_error = match &result {
+                Ok(_) => None,
+                Err(e) => Some(e.cause()),
+            };
+        }
+        if let Err(e) = &result {
+            error::create_log_file(self.log_file.to_owned(), e);
+            logger::warning_con(
+                "Scheduler",
+                format!("Job {} failed: {}", id, e.cause()).as_str(),
+            );
+        }
+        self.save_to_file()?;
+        self.send_status();
+        result
+    }
+    async fn run_price_history(&self) -> Result<(), AppError> {
+        let days = self.settings.lock()?.price_scraper.retention_days;
+        let price_scraper = self.price_scraper.lock()?.clone();
+        price_scraper.generate(days).await?;
+        Ok(())
+    }
+    async fn run_cache_refresh(&self) -> Result<(), AppError> {
+        let cache = self.cache.lock()?.clone();
+        cache.refresh().await?;
+        cache.save_to_file()?;
+        cache.refresh_ducats().await?;
+        Ok(())
+    }
+    // Backs up the database into the "backups" folder and removes the oldest backups above the limit.
+    async fn run_database_backup(&self) -> Result<(), AppError> {
+        let keep = self.settings.lock()?.scheduler.backups_to_keep.max(1) as usize;
+        let db = self.db.lock()?.clone();
+        let path = helper::get_app_roaming_path().join("backups");
+        fs::create_dir_all(&path).map_err(|e| AppError::new("Scheduler", eyre!(e.to_string())))?;
+
+        let file_name = format!(
+            "quantframe_{}.sqlite",
+            chrono::Local::now().format("%Y-%m-%d_%H%M%S")
+        );
+        db.backup(&path.join(file_name)).await?;
+
+        // Backups are named after their time, so sorting by name sorts them by age
+        let mut backups = fs::read_dir(&path)
+            .map_err(|e| AppError::new("Scheduler", eyre!(e.to_string())))?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|p| {
+                p.file_name()
+                    .is_some_and(|n| n.to_string_lossy().starts_with("quantframe_"))
+            })
+            .collect::<Vec<_>>();
+        backups.sort();
+        while backups.len() > keep {
+            fs::remove_file(backups.remove(0))
+                .map_err(|e| AppError::new("Scheduler", eyre!(e.to_string())))?;
+        }
+        Ok(())
+    }
+    // Removes the log folders older than the log retention, the folders are named after their day.
+    fn run_prune_logs(&self) -> Result<(), AppError> {
+        let days = self.settings.lock()?.scheduler.log_retention_days.max(1);
+        let oldest_day = (chrono::Local::now().naive_utc() - chrono::Duration::days(days))
+            .format("%Y-%m-%d")
+            .to_string();
+        let path = helper::get_app_roaming_path().join("logs");
+        if !path.exists() {
+            return Ok(());
+        }
+        let entries =
+            fs::read_dir(&path).map_err(|e| AppError::new("Scheduler", eyre!(e.to_string())))?;
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if entry.path().is_dir() && name.len() == 10 && name < oldest_day {
+                fs::remove_dir_all(entry.path())
+                    .map_err(|e| AppError::new("Scheduler", eyre!(e.to_string())))?;
+            }
+        }
+        Ok(())
+    }
+    // Sends a summary of the trades of the last 24 hours.
+    async fn run_daily_digest(&self) -> Result<(), AppError> {
+        let settings = self.settings.lock()?.clone().notifications.on_daily_digest;
+        let db = self.db.lock()?.clone();
+        let since = chrono::Utc::now() - chrono::Duration::hours(24);
+
+        let transactions = db
+            .transaction()
+            .get_items()
+            .await?
+            .into_iter()
+            .filter(|t| {
+                chrono::DateTime::parse_from_rfc3339(&t.created)
+                    .is_ok_and(|created| created >= since)
+            })
+            .collect::<Vec<_>>();
+        let sold = transactions
+            .iter()
+            .filter(|t| t.transaction_type == "sell")
+            .collect::<Vec<_>>();
+        let bought = transactions
+            .iter()
+            .filter(|t| t.transaction_type == "buy")
+            .collect::<Vec<_>>();
+        let income: i64 = sold.iter().map(|t| t.price as i64).sum();
+        let expense: i64 = bought.iter().map(|t| t.price as i64).sum();
+        let stock = db.stock_item().get_items_names().await?.len();
+
+        let content = settings
+            .content
+            .replace("<SOLD>", &sold.len().to_string())
+            .replace("<INCOME>", &income.to_string())
+            .replace("<BOUGHT>", &bought.len().to_string())
+            .replace("<EXPENSE>", &expense.to_string())
+            .replace("<PROFIT>", &(income - expense).to_string())
+            .replace("<STOCK>", &stock.to_string());
+        logger::info_con("Scheduler", content.as_str());
+        // If system notification is enabled, show it
+        if settings.system_notify {
+            self.mh.lock()?.show_notification(
+                settings.title.as_str(),
+                &content,
+                Some("assets/icons/icon.png"),
+                Some("Default"),
+            );
+        }
+        // If discord webhook is enabled, send it
+        if settings.discord_notify && settings.webhook.is_some() {
+            helper::send_message_to_discord(
+                settings.webhook.unwrap_or("".to_string()),
+                settings.title,
+                content,
+                settings.user_ids.clone(),
+            );
+        }
+        Ok(())
+    }
+}
@@ -4,7 +4,7 @@ use std::fs::File;
 use std::io::{Read, Write};
 use std::path::PathBuf;
 
-use crate::enums::{OrderMode, StockMode};
+use crate::enums::{LogRuleAction, OrderMode, PriceSource, StockMode};
 use crate::error::AppError;
 use crate::{helper, logger};
 use eyre::eyre;
@@ -15,6 +15,10 @@ pub struct SettingsState {
     pub debug: Vec<String>,
     pub dev_mode: bool,
     pub live_scraper: LiveScraperSettings,
+    pub price_scraper: PriceScraperSettings,
+    pub watchlist: WatchlistSettings,
+    pub scheduler: SchedulerSettings,
+    pub ee_log: EELogSettings,
     pub notifications: Notifications,
 }
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -27,6 +31,94 @@ pub struct LiveScraperSettings {
     pub stock_item: StockItemSettings,
     // Stock Riven Settings
     pub stock_riven: StockRivenSettings,
+    // Stock Auction Settings (Lich/Sister weapons)
+    pub stock_auction: StockAuctionSettings,
+    // Stock Set Settings (buy parts, sell the set)
+    pub stock_set: StockSetSettings,
+    // Stock Ducat Settings (bulk buy prime parts for Baro)
+    pub stock_ducat: StockDucatSettings,
+    // Stock Rank Up Settings (buy unranked, sell max ranked)
+    pub stock_rank_up: StockRankUpSettings,
+    // How many credits are worth one platinum
+    pub credits_per_plat: i64,
+    // Market Indicator Settings
+    pub indicators: IndicatorSettings,
+}
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct IndicatorSettings {
+    // Days of price history the indicators are computed over
+    pub window_days: i64,
+    // Newest days used for the short moving average, volume trend and momentum
+    pub short_window: i64,
+}
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PriceScraperSettings {
+    // Days of price history kept in the database
+    pub retention_days: i64,
+    // Days of price history the live scraper looks at
+    pub window_days: i64,
+    // How many days are downloaded at the same time
+    pub max_concurrent_downloads: i64,
+    // Where the price history comes from, "relics_run", "warframe_market" or "auto"
+    pub source: PriceSource,
+}
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WatchlistSettings {
+    pub enabled: bool,
+    // Seconds between two checks of the same item
+    pub poll_interval: i64,
+}
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SchedulerSettings {
+    pub price_history: JobSettings,
+    pub cache_refresh: JobSettings,
+    pub database_backup: JobSettings,
+    pub prune_logs: JobSettings,
+    pub daily_digest: JobSettings,
+    // How many database backups are kept
+    pub backups_to_keep: i64,
+    // Days of log folders that are kept
+    pub log_retention_days: i64,
+}
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EELogSettings {
+    // Path to EE.log or the folder it is in, empty finds it in the Windows, Proton and Wine folders
+    pub path: String,
+    // User defined rules, checked on every line after the built in events
+    pub rules: Vec<LogRule>,
+    pub game_session: GameSessionSettings,
+    // Removes a riven sold in a trade from the stock and closes its auction
+    pub sell_traded_rivens: bool,
+}
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GameSessionSettings {
+    // Sets the warframe.market status to in game on login and invisible when the game closes
+    pub sync_status: bool,
+    // Stops the live scraper when the game closes and starts it again on the next login
+    pub pause_live_scraper: bool,
+    // Hides the visible orders when the game closes and shows them again on the next login
+    pub hide_orders: bool,
+}
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LogRule {
+    pub name: String,
+    pub enabled: bool,
+    // Regex with named captures, a capture can be used as <name> in the title and content
+    pub pattern: String,
+    // Seconds before the rule can fire again
+    pub cooldown: i64,
+    pub action: LogRuleAction,
+    pub title: String,
+    pub content: String,
+    // Only used by the discord action
+    pub webhook: Option<String>,
+    pub user_ids: Option<Vec<String>>,
+}
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct JobSettings {
+    pub enabled: bool,
+    // Minutes between two runs
+    pub interval: i64,
 }
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct StockItemSettings {
@@ -44,11 +136,46 @@ pub struct StockItemSettings {
     pub strict_whitelist: bool,
     // What to post sell, buy, or both
     pub order_mode: OrderMode,
+    // Indicator filters, -1 disables the max filter and -100 the min filters
+    pub max_volatility: f64,
+    pub min_volume_trend: f64,
+    pub min_momentum: f64,
+    pub min_liquidity: f64,
 }
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct StockRivenSettings {
     pub range_threshold: i64,
 }
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StockAuctionSettings {
+    pub range_threshold: i64,
+}
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StockSetSettings {
+    pub enabled: bool,
+    pub min_margin: i64,
+    pub min_volume: i64,
+    // How many sets can be bought at the same time
+    pub max_sets: i64,
+}
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StockDucatSettings {
+    pub enabled: bool,
+    // Only buy parts that cost less than this per ducat
+    pub max_plat_per_ducat: f64,
+    pub min_volume: i64,
+    pub max_orders: i64,
+    pub quantity: i64,
+}
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StockRankUpSettings {
+    pub enabled: bool,
+    pub min_profit: i64,
+    pub min_volume: i64,
+    pub max_orders: i64,
+    // How much endo is worth one platinum
+    pub endo_per_plat: i64,
+}
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Notification {
@@ -64,7 +191,10 @@ pub struct Notification {
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Notifications {
     pub on_new_conversation: Notification,
+    pub on_whisper: Notification,
     pub on_wfm_chat_message: Notification,
+    pub on_watchlist_alert: Notification,
+    pub on_daily_digest: Notification,
 }
 // Allow us to run AuthState::default()
 impl Default for SettingsState {
@@ -87,10 +217,86 @@ impl Default for SettingsState {
                     report_to_wfm: true,
                     auto_trade: true,
                     order_mode: OrderMode::Both,
+                    max_volatility: -1.0,
+                    min_volume_trend: -100.0,
+                    min_momentum: -100.0,
+                    min_liquidity: 0.0,
                 },
                 stock_riven: StockRivenSettings {
                     range_threshold: 25,
                 },
+                stock_auction: StockAuctionSettings {
+                    range_threshold: 25,
+                },
+                stock_set: StockSetSettings {
+                    enabled: false,
+                    min_margin: 15,
+                    min_volume: 5,
+                    max_sets: 3,
+                },
+                stock_ducat: StockDucatSettings {
+                    enabled: false,
+                    max_plat_per_ducat: 0.1,
+                    min_volume: 1,
+                    max_orders: 10,
+                    quantity: 1,
+                },
+                stock_rank_up: StockRankUpSettings {
+                    enabled: false,
+                    min_profit: 20,
+                    min_volume: 2,
+                    max_orders: 5,
+                    endo_per_plat: 400,
+                },
+                credits_per_plat: 100000,
+                indicators: IndicatorSettings {
+                    window_days: 30,
+                    short_window: 7,
+                },
+            },
+            price_scraper: PriceScraperSettings {
+                retention_days: 90,
+                window_days: 7,
+                max_concurrent_downloads: 4,
+                source: PriceSource::Auto,
+            },
+            watchlist: WatchlistSettings {
+                enabled: true,
+                poll_interval: 300,
+            },
+            scheduler: SchedulerSettings {
+                price_history: JobSettings {
+                    enabled: true,
+                    interval: 1440,
+                },
+                cache_refresh: JobSettings {
+                    enabled: true,
+                    interval: 1440,
+                },
+                database_backup: JobSettings {
+                    enabled: true,
+                    interval: 1440,
+                },
+                prune_logs: JobSettings {
+                    enabled: true,
+                    interval: 1440,
+                },
+                daily_digest: JobSettings {
+                    enabled: false,
+                    interval: 1440,
+                },
+                backups_to_keep: 7,
+                log_retention_days: 14,
+            },
+            ee_log: EELogSettings {
+                path: "".to_string(),
+                rules: vec![],
+                game_session: GameSessionSettings {
+                    sync_status: true,
+                    pause_live_scraper: false,
+                    hide_orders: false,
+                },
+                sell_traded_rivens: true,
             },
             notifications: Notifications {
                 on_new_conversation: Notification {
@@ -101,6 +307,15 @@ impl Default for SettingsState {
                     webhook: Some("".to_string()),
                     user_ids: Some(vec![]),
                 },
+                on_whisper: Notification {
+                    discord_notify: false,
+                    system_notify: true,
+                    content: "<PLAYER_NAME> wants to <ACTION> <ITEM_NAME> for <PRICE>p. <LISTING>"
+                        .to_string(),
+                    title: "New Whisper".to_string(),
+                    webhook: Some("".to_string()),
+                    user_ids: Some(vec![]),
+                },
                 on_wfm_chat_message: Notification {
                     discord_notify: false,
                     system_notify: true,
@@ -109,6 +324,22 @@ impl Default for SettingsState {
                     webhook: Some("".to_string()),
                     user_ids: Some(vec![]),
                 },
+                on_watchlist_alert: Notification {
+                    discord_notify: false,
+                    system_notify: true,
+                    content: "<ITEM_NAME>: <MESSAGE>".to_string(),
+                    title: "Watchlist Alert".to_string(),
+                    webhook: Some("".to_string()),
+                    user_ids: Some(vec![]),
+                },
+                on_daily_digest: Notification {
+                    discord_notify: false,
+                    system_notify: true,
+                    content: "Sold: <SOLD> (<INCOME> plat), Bought: <BOUGHT> (<EXPENSE> plat), Profit: <PROFIT> plat, Stock: <STOCK> items".to_string(),
+                    title: "Daily Digest".to_string(),
+                    webhook: Some("".to_string()),
+                    user_ids: Some(vec![]),
+                },
             },
         }
     }
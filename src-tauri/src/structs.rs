@@ -66,6 +66,31 @@ pub struct RivenTypeInfo {
     pub riven_type: Option<String>,
 }
 
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct AuctionWeaponInfo {
+    #[serde(rename = "id")]
+    pub id: String,
+
+    #[serde(rename = "url_name")]
+    pub url_name: String,
+
+    #[serde(rename = "item_name")]
+    pub item_name: String,
+
+    #[serde(rename = "thumb")]
+    pub thumb: String,
+
+    #[serde(rename = "icon")]
+    pub icon: String,
+
+    #[serde(rename = "icon_format")]
+    pub icon_format: Option<String>,
+
+    // Lich or sister, set when the cache is refreshed
+    #[serde(rename = "auction_type")]
+    pub auction_type: Option<String>,
+}
+
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct RivenAttributeInfo {
     #[serde(rename = "negative_only")]
@@ -115,6 +140,56 @@ pub struct Item {
     pub tags: Option<Vec<String>>,
     pub mod_max_rank: Option<i64>,
     pub subtypes: Option<Vec<String>>,
+    pub ducats: Option<i64>,
+    // Copies of the part needed for its set, filled from the item details of the set
+    pub quantity_for_set: Option<i64>,
+}
+// Statistics of an item from warframe.market, closed trades and live orders over the last 48 hours and 90 days.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ItemStatistics {
+    #[serde(rename = "statistics_closed")]
+    pub statistics_closed: ItemStatisticsPeriod,
+
+    #[serde(rename = "statistics_live")]
+    pub statistics_live: ItemStatisticsPeriod,
+}
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ItemStatisticsPeriod {
+    #[serde(rename = "48hours")]
+    pub hours_48: Vec<ItemStatistic>,
+
+    #[serde(rename = "90days")]
+    pub days_90: Vec<ItemStatistic>,
+}
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ItemStatistic {
+    #[serde(rename = "datetime")]
+    pub datetime: String,
+
+    #[serde(rename = "volume")]
+    pub volume: f64,
+
+    #[serde(rename = "min_price")]
+    pub min_price: f64,
+
+    #[serde(rename = "max_price")]
+    pub max_price: f64,
+
+    #[serde(rename = "avg_price")]
+    pub avg_price: f64,
+
+    #[serde(rename = "median")]
+    pub median: f64,
+
+    // Only set on live statistics, "buy" or "sell"
+    #[serde(rename = "order_type")]
+    pub order_type: Option<String>,
+
+    #[serde(rename = "mod_rank")]
+    pub mod_rank: Option<i64>,
+
+    #[serde(rename = "subtype")]
+    pub subtype: Option<String>,
 }
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct ItemDetails {
@@ -126,8 +201,20 @@ pub struct ItemInfo {
     #[serde(rename = "id")]
     pub id: String,
 
+    #[serde(rename = "url_name")]
+    pub url_name: Option<String>,
+
     #[serde(rename = "mod_max_rank")]
     pub mod_max_rank: Option<f64>,
+
+    #[serde(rename = "set_root")]
+    pub set_root: Option<bool>,
+
+    #[serde(rename = "quantity_for_set")]
+    pub quantity_for_set: Option<i64>,
+
+    #[serde(rename = "ducats")]
+    pub ducats: Option<i64>,
 }
 #[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct Order {
@@ -1,33 +1,50 @@
+use crate::auth::AuthState;
 use crate::cache::client::CacheClient;
+use crate::database::client::DBClient;
 use crate::error::AppError;
 use crate::handler::MonitorHandler;
+use crate::live_scraper::client::LiveScraperClient;
 use crate::settings::SettingsState;
+use crate::wfm_client::client::WFMClient;
 use crate::{helper, logger};
-use serde_json::json;
-use std::fs::File;
-use std::io::{self, BufRead, BufReader, Seek, SeekFrom}; // Add Seek here
-use std::path::PathBuf;
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
 use std::sync::{Arc, Mutex};
 use std::thread::{self, JoinHandle};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use super::events::on_new_conversation::OnNewConversationEvent;
-use super::events::on_new_trading::OnTradingEvent;
+use super::events::helper::PatternSet;
+use super::events::on_game_session::OnGameSessionEvent;
+use super::events::on_log_rule::OnLogRuleEvent;
+use super::events::on_new_conversation::{ConversationStruct, OnNewConversationEvent};
+use super::events::on_new_trading::{OnTradingEvent, PlayerTradeStruct};
+use super::tailer::LogTailer;
 
 #[derive(Clone, Debug)]
 pub struct EELogParser {
     is_running: Arc<AtomicBool>,
-    wf_ee_path: PathBuf,
+    wf_ee_path: Arc<Mutex<PathBuf>>,
+    // If EE.log was found on the last check, None before the first check
+    file_found: Arc<Mutex<Option<bool>>>,
     component: String,
-    last_file_size: Arc<Mutex<u64>>,
-    last_line_index: Arc<Mutex<usize>>,
+    tailer: Arc<Mutex<LogTailer>>,
     handle: Arc<Mutex<Option<JoinHandle<()>>>>,
     cold_start: Arc<AtomicBool>,
     pub settings: Arc<Mutex<crate::settings::SettingsState>>,
+    mh: Arc<Mutex<MonitorHandler>>,
+    cache: Arc<Mutex<CacheClient>>,
+    db: Arc<Mutex<DBClient>>,
+    wfm: Arc<Mutex<WFMClient>>,
+    // The patterns of all built in events, a line that matches none of them is only seen by the user rules
+    prefilter: Arc<PatternSet>,
     // Events
     event_conversation: Arc<Mutex<OnNewConversationEvent>>,
     event_trading: Arc<Mutex<OnTradingEvent>>,
+    event_rules: Arc<Mutex<OnLogRuleEvent>>,
+    event_game_session: Arc<Mutex<OnGameSessionEvent>>,
 }
 
 impl EELogParser {
@@ -35,31 +52,77 @@ impl EELogParser {
         settings: Arc<Mutex<SettingsState>>,
         mh: Arc<Mutex<MonitorHandler>>,
         cache: Arc<Mutex<CacheClient>>,
+        db: Arc<Mutex<DBClient>>,
+        auth: Arc<Mutex<AuthState>>,
+        wfm: Arc<Mutex<WFMClient>>,
+        live_scraper: Arc<Mutex<LiveScraperClient>>,
     ) -> Self {
-        let wf_ee_path = helper::get_app_local_path().join("Warframe").join("EE.log");
+        let custom_path = settings.lock().unwrap().ee_log.path.clone();
+        let wf_folder = helper::find_warframe_folder(&custom_path);
+        helper::set_warframe_folder(wf_folder.clone());
+        let wf_ee_path = wf_folder.join("EE.log");
         Self {
             is_running: Arc::new(AtomicBool::new(false)),
             component: "EELogParser".to_string(),
-            wf_ee_path: wf_ee_path.clone(),
-            last_file_size: Arc::new(Mutex::new(0)),
-            last_line_index: Arc::new(Mutex::new(0)),
+            wf_ee_path: Arc::new(Mutex::new(wf_ee_path.clone())),
+            file_found: Arc::new(Mutex::new(None)),
+            tailer: Arc::new(Mutex::new(LogTailer::new(wf_ee_path.clone()))),
             handle: Arc::new(Mutex::new(None)),
             cold_start: Arc::new(AtomicBool::new(true)),
             settings: Arc::clone(&settings),
+            mh: Arc::clone(&mh),
+            cache: Arc::clone(&cache),
+            db: Arc::clone(&db),
+            wfm: Arc::clone(&wfm),
+            prefilter: Arc::new(Self::create_prefilter()),
             event_conversation: Arc::new(Mutex::new(OnNewConversationEvent::new(
                 Arc::clone(&settings),
                 Arc::clone(&mh),
+                Arc::clone(&cache),
+                Arc::clone(&db),
+                Arc::clone(&wfm),
                 wf_ee_path.clone(),
+                false,
             ))),
             event_trading: Arc::new(Mutex::new(OnTradingEvent::new(
                 Arc::clone(&settings),
                 Arc::clone(&mh),
                 Arc::clone(&cache),
+                Arc::clone(&db),
+                Arc::clone(&wfm),
                 wf_ee_path.clone(),
+                false,
+            ))),
+            event_rules: Arc::new(Mutex::new(OnLogRuleEvent::new(
+                Arc::clone(&settings),
+                Arc::clone(&mh),
+                Arc::clone(&db),
+                false,
+            ))),
+            event_game_session: Arc::new(Mutex::new(OnGameSessionEvent::new(
+                Arc::clone(&settings),
+                Arc::clone(&mh),
+                Arc::clone(&auth),
+                Arc::clone(&wfm),
+                Arc::clone(&live_scraper),
             ))),
         }
     }
 
+    fn create_prefilter() -> PatternSet {
+        let mut patterns = OnGameSessionEvent::patterns();
+        patterns.extend(OnNewConversationEvent::patterns());
+        patterns.extend(OnTradingEvent::patterns());
+        PatternSet::new(patterns).expect("Invalid built in EE.log pattern")
+    }
+
+    // Copies the new settings into the events, called when the settings are saved.
+    pub fn on_settings_change(&self) -> Result<(), AppError> {
+        self.event_conversation.lock()?.refresh_settings()?;
+        self.event_rules.lock()?.refresh_settings()?;
+        Ok(())
+    }
+
     pub fn start_loop(&mut self) {
         logger::info_con(self.component.as_str(), "Starting EE Log Parser");
         let is_running = Arc::clone(&self.is_running);
@@ -68,20 +131,125 @@ impl EELogParser {
         self.is_running.store(true, Ordering::SeqCst);
 
         let handle = thread::spawn(move || {
+            let (tx, rx) = mpsc::channel();
+            let mut watcher = scraper.create_watcher(&scraper.get_path(), tx.clone());
             while is_running.load(Ordering::SeqCst) {
+                // The file is checked on every change, and every few seconds in case a change was missed
+                let timeout = match &watcher {
+                    Ok(_) => Duration::from_secs(5),
+                    Err(_) => Duration::from_secs(1),
+                };
+                match rx.recv_timeout(timeout) {
+                    Ok(_) | Err(RecvTimeoutError::Timeout) => {}
+                    Err(RecvTimeoutError::Disconnected) => thread::sleep(timeout),
+                }
+                // One check reads all new lines, so the queued changes can be dropped
+                while rx.try_recv().is_ok() {}
+
+                // A new path in the settings needs a new watcher
+                if scraper.refresh_path().unwrap_or(false) {
+                    watcher = scraper.create_watcher(&scraper.get_path(), tx.clone());
+                }
+
                 match scraper.check() {
                     Ok(_) => {
                         scraper.cold_start.store(false, Ordering::SeqCst);
                     }
                     Err(_) => {}
                 }
-                thread::sleep(Duration::from_secs(1));
             }
+            drop(watcher);
         });
 
         *self.handle.lock().unwrap() = Some(handle);
     }
 
+    // Watches the Warframe folder instead of the file, the game deletes and recreates EE.log when it starts.
+    fn create_watcher(&self, path: &Path, tx: Sender<()>) -> notify::Result<RecommendedWatcher> {
+        let file_name = path.file_name().map(|n| n.to_os_string());
+        let mut watcher =
+            notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+                if let Ok(event) = res {
+                    if event
+                        .paths
+                        .iter()
+                        .any(|p| p.file_name().map(|n| n.to_os_string()) == file_name)
+                    {
+                        tx.send(()).ok();
+                    }
+                }
+            })?;
+        let folder = path.parent().map(|p| p.to_path_buf()).unwrap_or_default();
+        if let Err(e) = watcher.watch(&folder, RecursiveMode::NonRecursive) {
+            logger::warning_con(
+                self.component.as_str(),
+                format!(
+                    "Could not watch {:?}, checking it every second: {}",
+                    folder, e
+                )
+                .as_str(),
+            );
+            return Err(e);
+        }
+        Ok(watcher)
+    }
+
+    fn get_path(&self) -> PathBuf {
+        self.wf_ee_path.lock().unwrap().clone()
+    }
+
+    // Finds EE.log again with the path from the settings, returns true when the path changed.
+    fn refresh_path(&self) -> Result<bool, AppError> {
+        let custom_path = self.settings.lock()?.ee_log.path.clone();
+        let wf_folder = helper::find_warframe_folder(&custom_path);
+        let path = wf_folder.join("EE.log");
+        let mut wf_ee_path = self.wf_ee_path.lock()?;
+        if *wf_ee_path == path {
+            return Ok(false);
+        }
+        logger::info_con(
+            self.component.as_str(),
+            format!("Reading EE.log from {:?}", path).as_str(),
+        );
+        helper::set_warframe_folder(wf_folder);
+        *wf_ee_path = path.clone();
+        *self.tailer.lock()? = LogTailer::new(path);
+        *self.file_found.lock()? = None;
+        self.cold_start.store(true, Ordering::SeqCst);
+        Ok(true)
+    }
+
+    pub fn get_status(&self) -> Value {
+        let path = self.get_path();
+        json!({
+            "path": path,
+            "found": path.exists(),
+            "running": self.is_running(),
+        })
+    }
+
+    // Tells the frontend when EE.log is found or lost, only when it changes
+    fn set_file_found(&self, found: bool) {
+        {
+            let mut file_found = self.file_found.lock().unwrap();
+            if *file_found == Some(found) {
+                return;
+            }
+            *file_found = Some(found);
+        }
+        if !found {
+            logger::warning_con(
+                self.component.as_str(),
+                format!(
+                    "EE.log not found at {:?}, set its path in the settings",
+                    self.get_path()
+                )
+                .as_str(),
+            );
+        }
+        helper::send_message_to_window("EELogParser:OnStatus", Some(self.get_status()));
+    }
+
     pub fn stop_loop(&self) {
         logger::info_con(self.component.as_str(), "Stopping Whisper Listener");
         self.is_running.store(false, Ordering::SeqCst);
@@ -96,22 +264,30 @@ impl EELogParser {
         let new_lines_result = self.read_new_lines(self.cold_start.load(Ordering::SeqCst));
 
         // Events to check
-        let event_conversation = self.event_conversation.lock()?.clone();
+        let event_conversation = self.event_conversation.lock()?;
         let mut event_trading = self.event_trading.lock()?;
+        let mut event_rules = self.event_rules.lock()?;
+        let mut event_game_session = self.event_game_session.lock()?;
 
         match new_lines_result {
             Ok(new_lines) => {
+                self.set_file_found(true);
                 for line in new_lines {
-                    if event_conversation.check(line.0, &line.1)? {
-                        continue;
-                    }
-                    if event_trading.check(line.0, &line.1)? {
-                        continue;
+                    // Most lines are not used by any built in event, one pass of the prefilter skips them
+                    if (self.prefilter.is_match(&line.1) || event_trading.is_reading_trade())
+                        && !event_game_session.check(line.0, &line.1)?
+                        && !event_conversation.check(line.0, &line.1)?
+                    {
+                        event_trading.check(line.0, &line.1)?;
                     }
+                    // The user rules see every line, also the ones the built in events handled
+                    event_rules.check(line.0, &line.1)?;
                 }
             }
             Err(err) => {
-                helper::send_message_to_window("EELogParser", Some(json!({ "error": "err" })));
+                if err.kind() == std::io::ErrorKind::NotFound {
+                    self.set_file_found(false);
+                }
                 Err(AppError::new(
                     self.component.as_str(),
                     eyre::eyre!(err.to_string()),
@@ -121,38 +297,387 @@ impl EELogParser {
         Ok(())
     }
 
-    fn read_new_lines(&self, is_starting: bool) -> io::Result<Vec<(usize, String)>> {
-        let mut new_lines: Vec<(usize, String)> = Vec::new();
-        let mut file = File::open(&self.wf_ee_path)?;
+    fn read_all_lines(&self, path: &Path) -> Result<Vec<(u64, String)>, AppError> {
+        let mut tailer = LogTailer::new(path.to_path_buf());
+        let mut lines = tailer
+            .read_new_lines()
+            .map_err(|e| AppError::new(self.component.as_str(), eyre::eyre!(e.to_string())))?;
+        if let Some(line) = tailer.take_partial() {
+            lines.push(line);
+        }
+        Ok(lines)
+    }
 
-        let metadata = file.metadata()?;
-        let current_file_size = metadata.len();
+    // Runs the lines through new events that collect what they find instead of notifying.
+    fn replay_lines(
+        &self,
+        path: &Path,
+        lines: &[(u64, String)],
+    ) -> Result<(Vec<PlayerTradeStruct>, Vec<ConversationStruct>), AppError> {
+        let event_conversation = OnNewConversationEvent::new(
+            Arc::clone(&self.settings),
+            Arc::clone(&self.mh),
+            Arc::clone(&self.cache),
+            Arc::clone(&self.db),
+            Arc::clone(&self.wfm),
+            path.to_path_buf(),
+            true,
+        );
+        let mut event_trading = OnTradingEvent::new(
+            Arc::clone(&self.settings),
+            Arc::clone(&self.mh),
+            Arc::clone(&self.cache),
+            Arc::clone(&self.db),
+            Arc::clone(&self.wfm),
+            path.to_path_buf(),
+            true,
+        );
+        let mut event_rules = OnLogRuleEvent::new(
+            Arc::clone(&self.settings),
+            Arc::clone(&self.mh),
+            Arc::clone(&self.db),
+            true,
+        );
+        for line in lines {
+            if (self.prefilter.is_match(&line.1) || event_trading.is_reading_trade())
+                && !event_conversation.check(line.0, &line.1)?
+            {
+                event_trading.check(line.0, &line.1)?;
+            }
+            event_rules.check(line.0, &line.1)?;
+        }
+        Ok((
+            event_trading.take_found_trades(),
+            event_conversation.get_found_conversations()?,
+        ))
+    }
 
+    // Runs a whole log file through new events that collect what they find instead of notifying.
+    pub fn replay(
+        &self,
+        path: PathBuf,
+    ) -> Result<(Vec<PlayerTradeStruct>, Vec<ConversationStruct>), AppError> {
+        let lines = self.read_all_lines(&path)?;
+        let result = self.replay_lines(&path, &lines)?;
+        logger::info_con(
+            self.component.as_str(),
+            format!("Replayed {}", path.to_str().unwrap_or_default()).as_str(),
+        );
+        Ok(result)
+    }
+
+    // Replays a log file and times each step, used to measure the cost per line on a large sample log.
+    pub fn benchmark(&self, path: PathBuf) -> Result<Value, AppError> {
+        let timer = Instant::now();
+        let lines = self.read_all_lines(&path)?;
+        let read_time = timer.elapsed();
+
+        let timer = Instant::now();
+        let matched_lines = lines
+            .iter()
+            .filter(|line| self.prefilter.is_match(&line.1))
+            .count();
+        let prefilter_time = timer.elapsed();
+
+        let timer = Instant::now();
+        let (trades, conversations) = self.replay_lines(&path, &lines)?;
+        let events_time = timer.elapsed();
+
+        let line_count = lines.len().max(1) as u128;
+        let result = json!({
+            "path": path,
+            "lines": lines.len(),
+            "bytes": lines.iter().map(|line| line.1.len()).sum::<usize>(),
+            "matched_lines": matched_lines,
+            "trades": trades.len(),
+            "conversations": conversations.len(),
+            "read_ms": read_time.as_millis(),
+            "prefilter_ms": prefilter_time.as_millis(),
+            "events_ms": events_time.as_millis(),
+            "prefilter_ns_per_line": prefilter_time.as_nanos() / line_count,
+            "events_ns_per_line": events_time.as_nanos() / line_count,
+        });
+        logger::info_con(
+            self.component.as_str(),
+            format!("Benchmark: {}", result).as_str(),
+        );
+        Ok(result)
+    }
+
+    // Returns the new lines with the byte offset they start at.
+    fn read_new_lines(&self, is_starting: bool) -> std::io::Result<Vec<(u64, String)>> {
+        let mut tailer = self.tailer.lock().unwrap();
         if is_starting {
-            *self.last_file_size.lock().unwrap() = current_file_size;
-            return Ok(new_lines);
+            tailer.seek_to_end()?;
+            return Ok(vec![]);
+        }
+        tailer.read_new_lines()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::price_scraper::PriceScraper;
+    use crate::structs::{Item, RivenTypeInfo, TradeClassification};
+    use crate::wf_ee_log_parser::events::on_new_conversation::WhisperStruct;
+    use sqlx::sqlite::SqlitePoolOptions;
+    use std::collections::HashMap;
+
+    // The game language is read from Launcher.log in the Warframe folder, which is set for the whole app.
+    // Tests that create a parser hold this lock so they do not change the folder under each other.
+    static WARFRAME_FOLDER: Mutex<()> = Mutex::new(());
+
+    fn item(item_name: &str, url_name: &str, tags: &[&str]) -> Item {
+        Item {
+            item_name: item_name.to_string(),
+            id: format!("id_{}", url_name),
+            url_name: url_name.to_string(),
+            thumb: "".to_string(),
+            wikia_url: None,
+            trade_tax: None,
+            mr_requirement: None,
+            set_items: None,
+            tags: Some(tags.iter().map(|t| t.to_string()).collect()),
+            mod_max_rank: None,
+            subtypes: None,
+            ducats: None,
+            quantity_for_set: None,
         }
+    }
 
-        let mut last_file_size = self.last_file_size.lock().unwrap();
-        let mut last_line_index = self.last_line_index.lock().unwrap();
-        if *last_file_size > current_file_size || current_file_size < *last_file_size {
-            *last_file_size = 0;
-            *last_line_index = 0;
+    fn riven_type(item_name: &str, url_name: &str) -> RivenTypeInfo {
+        RivenTypeInfo {
+            thumb: "".to_string(),
+            url_name: url_name.to_string(),
+            mastery_level: 0,
+            group: "primary".to_string(),
+            id: format!("id_{}", url_name),
+            icon: "".to_string(),
+            item_name: item_name.to_string(),
+            icon_format: None,
+            riven_type: Some("rifle".to_string()),
         }
+    }
 
-        // Now we can call seek on the file because we have Seek in our scope
-        file.seek(SeekFrom::Start(*last_file_size))?;
+    // Creates a parser with a few cached items, the game language is written to a Launcher.log of its own.
+    fn create_parser(language: &str) -> EELogParser {
+        let folder = std::env::temp_dir().join(format!("quantframe-ee-log-{}", language));
+        std::fs::create_dir_all(&folder).unwrap();
+        std::fs::write(
+            folder.join("Launcher.log"),
+            format!(
+                "Launching Warframe.x64.exe -cluster:public -language:{}\n",
+                language
+            ),
+        )
+        .unwrap();
 
-        let reader = BufReader::new(file);
+        let mut settings = SettingsState::default();
+        settings.ee_log.path = folder.to_str().unwrap().to_string();
+        let settings = Arc::new(Mutex::new(settings));
+        let mh = Arc::new(Mutex::new(MonitorHandler::headless()));
+        let auth = Arc::new(Mutex::new(AuthState::default()));
+        let wfm = Arc::new(Mutex::new(WFMClient::new(
+            Arc::clone(&auth),
+            Arc::clone(&settings),
+        )));
 
-        for (_, line) in reader.lines().enumerate() {
-            *last_line_index += 1;
-            if let Ok(line) = line {
-                new_lines.push((last_line_index.clone(), line)); // Adding line index
-            }
+        let cache = CacheClient::new(Arc::clone(&wfm));
+        {
+            let mut cache_data = cache.cache_data.lock().unwrap();
+            cache_data.item.items = vec![
+                item(
+                    "Ash Prime Systems Blueprint",
+                    "ash_prime_systems_blueprint",
+                    &["prime", "blueprint", "component"],
+                ),
+                item(
+                    "Soma Prime Barrel",
+                    "soma_prime_barrel",
+                    &["prime", "component"],
+                ),
+                item(
+                    "Arcane Energize",
+                    "arcane_energize",
+                    &["arcane_enhancement", "legendary"],
+                ),
+            ];
+            cache_data.item.localized_names.insert(
+                "fr".to_string(),
+                HashMap::from([(
+                    "Canon de Soma Prime".to_string(),
+                    "soma_prime_barrel".to_string(),
+                )]),
+            );
+            cache_data.riven.items = vec![riven_type("Lanka", "lanka")];
         }
+        let cache = Arc::new(Mutex::new(cache));
+
+        // A replay does not use the database, a lazy pool only connects when it is used
+        let connection = SqlitePoolOptions::new()
+            .idle_timeout(None)
+            .max_lifetime(None)
+            .connect_lazy("sqlite::memory:")
+            .unwrap();
+        let db = Arc::new(Mutex::new(DBClient {
+            log_file: "db.log".to_string(),
+            connection: Arc::new(Mutex::new(connection)),
+            cache: Arc::clone(&cache),
+            wfm: Arc::clone(&wfm),
+        }));
+        let price_scraper = Arc::new(Mutex::new(PriceScraper::new(
+            Arc::clone(&wfm),
+            Arc::clone(&auth),
+            Arc::clone(&db),
+            Arc::clone(&settings),
+        )));
+        let live_scraper = Arc::new(Mutex::new(LiveScraperClient::new(
+            Arc::clone(&settings),
+            price_scraper,
+            Arc::clone(&wfm),
+            Arc::clone(&auth),
+            Arc::clone(&db),
+            Arc::clone(&mh),
+        )));
+        EELogParser::new(settings, mh, cache, db, auth, wfm, live_scraper)
+    }
+
+    fn fixture(name: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("ee_log")
+            .join(name)
+    }
+
+    fn replay_fixture(
+        language: &str,
+        name: &str,
+    ) -> (Vec<PlayerTradeStruct>, Vec<ConversationStruct>) {
+        let _folder = WARFRAME_FOLDER.lock().unwrap_or_else(|e| e.into_inner());
+        let parser = create_parser(language);
+        let path = fixture(name);
+        let lines = parser.read_all_lines(&path).unwrap();
+        parser.replay_lines(&path, &lines).unwrap()
+    }
+
+    #[test]
+    fn replays_an_english_trade() {
+        let (trades, conversations) = replay_fixture("en", "english_trade.log");
+
+        // The second trade failed, so it is not found
+        assert_eq!(trades.len(), 1);
+        let trade = &trades[0];
+        assert_eq!(trade.user_name, "BuyerOne");
+        assert_eq!(trade.trade_type, TradeClassification::Sale);
+        assert_eq!(trade.total_platinum, 15);
+        assert_eq!(trade.get_platinum(), (15, 0));
+        assert_eq!(
+            trade.offerings[0].wfm_url_name.as_deref(),
+            Some("ash_prime_systems_blueprint")
+        );
+        assert!(trade.crated_at.starts_with("2024-01-18T15:23:31"));
+
+        assert_eq!(conversations.len(), 1);
+        assert_eq!(conversations[0].user_name, "BuyerOne");
+        assert_eq!(conversations[0].message, None);
+    }
+
+    // The fixture is written from the built in French messages, not captured from the game,
+    // so it only shows the parser follows the messages of the game language.
+    #[test]
+    fn replays_a_french_trade() {
+        let (trades, _) = replay_fixture("fr", "french_trade.log");
+
+        assert_eq!(trades.len(), 1);
+        let trade = &trades[0];
+        assert_eq!(trade.user_name, "VendeurDeux");
+        assert_eq!(trade.trade_type, TradeClassification::Purchase);
+        assert_eq!(trade.get_platinum(), (0, 20));
+        assert_eq!(
+            trade.receiving[0].wfm_url_name.as_deref(),
+            Some("soma_prime_barrel")
+        );
+        // The snippet has no "Current time:" line, so the time of the trade is not known
+        assert_eq!(trade.crated_at, "");
+    }
+
+    #[test]
+    fn replays_a_riven_trade() {
+        let (trades, _) = replay_fixture("en", "riven_trade.log");
+
+        assert_eq!(trades.len(), 1);
+        let trade = &trades[0];
+        assert_eq!(trade.user_name, "RivenBuyer");
+        assert_eq!(trade.trade_type, TradeClassification::Sale);
+        assert_eq!(trade.total_platinum, 350);
+        let item = &trade.offerings[0];
+        assert_eq!(item.rank, 8);
+        assert_eq!(item.wfm_url_name.as_deref(), Some("lanka"));
+        let riven = item.riven.as_ref().unwrap();
+        assert_eq!(riven.weapon_url.as_deref(), Some("lanka"));
+        assert_eq!(riven.mod_name, "Vexi-critacan");
+        assert!(!riven.veiled);
+        assert!(trade.crated_at.starts_with("2024-03-02T21:10:10"));
+    }
+
+    #[test]
+    fn replays_a_whisper() {
+        let (trades, conversations) = replay_fixture("en", "whisper.log");
+
+        assert!(trades.is_empty());
+        // Only the whispers made from the warframe.market template are found
+        assert_eq!(conversations.len(), 2);
+        let conversation = &conversations[0];
+        assert_eq!(conversation.user_name, "BuyerOne");
+        let message = conversation.message.as_deref().unwrap();
+        let whisper = WhisperStruct::parse(&conversation.user_name, message).unwrap();
+        assert_eq!(whisper.action, "buy");
+        assert_eq!(whisper.item_name, "Arcane Energize");
+        assert_eq!(whisper.rank, Some(3));
+        assert_eq!(whisper.price, 40);
+        assert!(!whisper.riven);
+
+        // The platform icon is removed from the name, the item name is kept as it is written
+        let conversation = &conversations[1];
+        assert_eq!(conversation.user_name, "SellerTwo");
+        let message = conversation.message.as_deref().unwrap();
+        let whisper = WhisperStruct::parse(&conversation.user_name, message).unwrap();
+        assert_eq!(whisper.action, "sell");
+        assert_eq!(whisper.item_name, "Systèmes d'Ash Prime (Schéma)");
+        assert_eq!(whisper.price, 12);
+    }
+
+    // Run with `cargo test benchmark_sample_log -- --ignored --nocapture`, EE_LOG_SAMPLE can point to a real EE.log.
+    // Without it the snippets are repeated between lines the parser skips, like most of a real log.
+    #[test]
+    #[ignore]
+    fn benchmark_sample_log() {
+        let path = match std::env::var("EE_LOG_SAMPLE") {
+            Ok(path) => PathBuf::from(path),
+            Err(_) => {
+                let mut sample = String::new();
+                for _ in 0..2000 {
+                    for name in ["english_trade.log", "riven_trade.log", "whisper.log"] {
+                        sample.push_str(&std::fs::read_to_string(fixture(name)).unwrap());
+                        for i in 0..50 {
+                            sample.push_str(&format!(
+                                "1500.{:03} Sys [Info]: Loaded /Lotus/Levels/Proc/Tile{}.level\n",
+                                i, i
+                            ));
+                        }
+                    }
+                }
+                let path = std::env::temp_dir().join("quantframe-ee-log-sample.log");
+                std::fs::write(&path, sample).unwrap();
+                path
+            }
+        };
 
-        *last_file_size = current_file_size;
-        Ok(new_lines)
+        let _folder = WARFRAME_FOLDER.lock().unwrap_or_else(|e| e.into_inner());
+        let parser = create_parser("en");
+        let result = parser.benchmark(path).unwrap();
+        println!("{}", serde_json::to_string_pretty(&result).unwrap());
+        assert!(result["lines"].as_u64().unwrap() > 0);
     }
 }
@@ -1,26 +1,59 @@
 use crate::error::AppError;
 use eyre::eyre;
-use regex::Regex;
-
-pub fn match_pattern(
-    input: &str,
-    regexs: Vec<String>,
-) -> Result<(bool, Vec<Option<String>>), regex::Error> {
-    // Loop through the regexs and try to match them
-    for regex in regexs {
-        let re: Regex = Regex::new(&regex)?;
-        if let Some(captures) = re.captures(input) {
-            let mut result: Vec<Option<String>> = vec![];
-            for i in 1..captures.len() {
-                let group = captures.get(i).map(|m| m.as_str().to_string());
-                let group: Option<String> =
-                    group.map(|s| s.chars().filter(|c| c.is_ascii()).collect());
-                result.push(group);
-            }
-            return Ok((true, result));
+use regex::{Regex, RegexSet};
+
+// Patterns compiled once when the event is created.
+// The RegexSet tells in one pass if any of them match, the single regexes are only run to get the captures.
+#[derive(Clone, Debug)]
+pub struct PatternSet {
+    set: RegexSet,
+    regexes: Vec<Regex>,
+}
+
+impl PatternSet {
+    pub fn new(patterns: Vec<String>) -> Result<Self, regex::Error> {
+        let set = RegexSet::new(&patterns)?;
+        let regexes = patterns
+            .iter()
+            .map(|pattern| Regex::new(pattern))
+            .collect::<Result<Vec<Regex>, regex::Error>>()?;
+        Ok(PatternSet { set, regexes })
+    }
+
+    pub fn is_match(&self, input: &str) -> bool {
+        self.set.is_match(input)
+    }
+
+    // Returns the captures of the first pattern that matches as they are in the line.
+    pub fn match_pattern(&self, input: &str) -> (bool, Vec<Option<String>>) {
+        let index = match self.set.matches(input).into_iter().next() {
+            Some(index) => index,
+            None => return (false, vec![]),
+        };
+        let captures = match self.regexes[index].captures(input) {
+            Some(captures) => captures,
+            None => return (false, vec![]),
+        };
+        let mut result: Vec<Option<String>> = vec![];
+        for i in 1..captures.len() {
+            result.push(captures.get(i).map(|m| m.as_str().to_string()));
         }
+        (true, result)
     }
-    Ok((false, vec![]))
+}
+
+// The game shows the platform of a player with an icon from the private use area after their name, like "\u{e000}".
+pub fn clean_player_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| !('\u{e000}'..='\u{f8ff}').contains(c))
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+// The pattern of the "Current time:" line at the top of EE.log, see parse_log_start.
+pub fn log_start_pattern() -> String {
+    regex::escape("Current time: ")
 }
 
 pub fn get_range_of_lines(
@@ -49,3 +82,27 @@ pub fn get_range_of_lines(
 
     Ok(lines)
 }
+
+// Reads the time the game started from the "Current time:" line at the top of EE.log.
+pub fn parse_log_start(input: &str) -> Option<chrono::NaiveDateTime> {
+    let start = input.find("Current time: ")? + "Current time: ".len();
+    let end = input[start..].find(" [UTC").map(|i| start + i)?;
+    let time = input[start..end]
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ");
+    chrono::NaiveDateTime::parse_from_str(&time, "%a %b %e %H:%M:%S %Y").ok()
+}
+
+// Every line starts with the seconds since the game started, added to the start time this is the time of the line.
+// Returns None when the start time is not known.
+pub fn get_line_time(log_start: Option<chrono::NaiveDateTime>, input: &str) -> Option<String> {
+    let seconds = input
+        .split_whitespace()
+        .next()
+        .and_then(|s| s.parse::<f64>().ok())?;
+    let time = log_start? + chrono::Duration::milliseconds((seconds * 1000.0) as i64);
+    chrono::TimeZone::from_local_datetime(&chrono::Local, &time)
+        .single()
+        .map(|time| time.to_rfc3339())
+}
@@ -1,3 +1,5 @@
 pub mod helper;
+pub mod on_game_session;
+pub mod on_log_rule;
 pub mod on_new_conversation;
 pub mod on_new_trading;
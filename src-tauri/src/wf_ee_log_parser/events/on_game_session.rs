@@ -0,0 +1,296 @@
+use std::sync::{Arc, Mutex};
+
+use once_cell::sync::Lazy;
+use serde_json::json;
+
+use crate::{
+    auth::AuthState,
+    error::{self, AppError},
+    handler::MonitorHandler,
+    helper,
+    live_scraper::client::LiveScraperClient,
+    logger,
+    settings::SettingsState,
+    wfm_client::client::WFMClient,
+};
+
+use super::helper::{self as events_helper, PatternSet};
+
+enum Events {
+    Login,
+    Exit,
+}
+impl Events {
+    fn as_str_list(&self) -> Vec<String> {
+        match self {
+            Events::Login => vec![r"Logged in (?P<name>[^\s]+) \(".to_string()],
+            Events::Exit => vec![r"Main Shutdown Initiated".to_string()],
+        }
+    }
+}
+
+static LOGIN_PATTERNS: Lazy<PatternSet> =
+    Lazy::new(|| PatternSet::new(Events::Login.as_str_list()).expect("Invalid login pattern"));
+static EXIT_PATTERNS: Lazy<PatternSet> =
+    Lazy::new(|| PatternSet::new(Events::Exit.as_str_list()).expect("Invalid exit pattern"));
+
+// Follows the game being started, logged in and closed.
+// The WFM status follows the game, and the live scraper and orders can be paused while the game is closed.
+pub struct OnGameSessionEvent {
+    settings: Arc<Mutex<SettingsState>>,
+    helper: Arc<Mutex<MonitorHandler>>,
+    auth: Arc<Mutex<AuthState>>,
+    wfm: Arc<Mutex<WFMClient>>,
+    live_scraper: Arc<Mutex<LiveScraperClient>>,
+    in_game: bool,
+    // Set when closing the game paused the live scraper, so the next login starts it again
+    paused_live_scraper: bool,
+}
+
+impl std::fmt::Debug for OnGameSessionEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OnGameSessionEvent")
+            .field("in_game", &self.in_game)
+            .field("paused_live_scraper", &self.paused_live_scraper)
+            .finish_non_exhaustive()
+    }
+}
+
+impl OnGameSessionEvent {
+    pub fn new(
+        settings: Arc<Mutex<SettingsState>>,
+        helper: Arc<Mutex<MonitorHandler>>,
+        auth: Arc<Mutex<AuthState>>,
+        wfm: Arc<Mutex<WFMClient>>,
+        live_scraper: Arc<Mutex<LiveScraperClient>>,
+    ) -> Self {
+        Self {
+            settings,
+            helper,
+            auth,
+            wfm,
+            live_scraper,
+            in_game: false,
+            paused_live_scraper: false,
+        }
+    }
+
+    // The patterns the lines must match before check is called
+    pub fn patterns() -> Vec<String> {
+        let mut patterns = vec![events_helper::log_start_pattern()];
+        patterns.extend(Events::Login.as_str_list());
+        patterns.extend(Events::Exit.as_str_list());
+        patterns
+    }
+
+    pub fn check(&mut self, _offset: u64, input: &str) -> Result<bool, AppError> {
+        // The first lines of a new log are written when the game starts.
+        // The trading event reads the start time from the same line, so it is not handled here
+        if events_helper::parse_log_start(input).is_some() {
+            helper::send_message_to_window(
+                "Client:Game:Session",
+                Some(json!({ "status": "launched" })),
+            );
+            return Ok(false);
+        }
+
+        let (found, captures) = LOGIN_PATTERNS.match_pattern(input);
+        if found {
+            let ingame_name = events_helper::clean_player_name(
+                &captures.first().cloned().flatten().unwrap_or_default(),
+            );
+            self.on_login(&ingame_name)?;
+            return Ok(true);
+        }
+
+        let (found, _) = EXIT_PATTERNS.match_pattern(input);
+        if found {
+            self.on_exit()?;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    fn on_login(&mut self, ingame_name: &str) -> Result<(), AppError> {
+        self.in_game = true;
+        let settings = self.settings.lock()?.clone().ee_log.game_session;
+        let auth_name = self.auth.lock()?.ingame_name.clone();
+        logger::info_con(
+            "OnGameSessionEvent",
+            format!("Logged in as {}", ingame_name).as_str(),
+        );
+
+        // Orders posted for another account can not be traded from this one
+        let name_mismatch = !auth_name.is_empty()
+            && !ingame_name.is_empty()
+            && !auth_name.eq_ignore_ascii_case(ingame_name);
+        if name_mismatch {
+            let content = format!(
+                "The game is logged in as {}, but warframe.market as {}",
+                ingame_name, auth_name
+            );
+            logger::warning_con("OnGameSessionEvent", content.as_str());
+            self.helper.lock()?.show_notification(
+                "Account Mismatch",
+                &content,
+                Some("assets/icons/icon.png"),
+                Some("Default"),
+            );
+        }
+
+        if settings.sync_status {
+            self.set_status("ingame")?;
+        }
+
+        let mut live_scraper = self.live_scraper.lock()?;
+        if self.paused_live_scraper {
+            self.paused_live_scraper = false;
+            if !live_scraper.is_running() {
+                live_scraper.start_loop()?;
+            }
+        }
+        let live_scraper_running = live_scraper.is_running();
+        drop(live_scraper);
+        self.show_hidden_orders();
+
+        helper::send_message_to_window(
+            "Client:Game:Session",
+            Some(json!({
+                "status": "ingame",
+                "ingame_name": ingame_name,
+                "name_mismatch": name_mismatch,
+                "live_scraper": live_scraper_running,
+            })),
+        );
+        Ok(())
+    }
+
+    fn on_exit(&mut self) -> Result<(), AppError> {
+        self.in_game = false;
+        let settings = self.settings.lock()?.clone().ee_log.game_session;
+        logger::info_con("OnGameSessionEvent", "The game was closed");
+
+        if settings.sync_status {
+            self.set_status("invisible")?;
+        }
+
+        let live_scraper = self.live_scraper.lock()?;
+        if settings.pause_live_scraper && live_scraper.is_running() {
+            live_scraper.stop_loop();
+            self.paused_live_scraper = true;
+        }
+        let live_scraper_running = live_scraper.is_running();
+        drop(live_scraper);
+        if settings.hide_orders {
+            self.hide_orders();
+        }
+
+        helper::send_message_to_window(
+            "Client:Game:Session",
+            Some(json!({
+                "status": "closed",
+                "live_scraper": live_scraper_running,
+            })),
+        );
+        Ok(())
+    }
+
+    // Saves the status and sends the user to the window, the socket context of the frontend
+    // sends a changed status to warframe.market over its websocket
+    fn set_status(&self, status: &str) -> Result<(), AppError> {
+        let mut auth = self.auth.lock()?;
+        auth.status = Some(status.to_string());
+        auth.save_to_file()?;
+        auth.send_to_window();
+        Ok(())
+    }
+
+    // Hides the visible orders, their ids are kept by the live scraper so they are shown again on the next login,
+    // also after a restart.
+    fn hide_orders(&self) {
+        let wfm = self.wfm.lock().unwrap().clone();
+        let live_scraper = self.live_scraper.lock().unwrap().clone();
+        tauri::async_runtime::spawn(async move {
+            let orders = match wfm.orders().get_my_orders().await {
+                Ok(orders) => orders,
+                Err(e) => {
+                    error::create_log_file("game_session.log".to_string(), &e);
+                    return;
+                }
+            };
+            let mut all_orders = orders.sell_orders;
+            all_orders.extend(orders.buy_orders);
+            let visible_orders = all_orders
+                .into_iter()
+                .filter(|o| o.visible)
+                .collect::<Vec<_>>();
+
+            // Mark the orders first, so the live scraper leaves them alone from now on
+            let mut hidden_ids = live_scraper.hidden_orders.lock().unwrap().clone();
+            hidden_ids.extend(visible_orders.iter().map(|o| o.id.clone()));
+            if let Err(e) = live_scraper.set_hidden_orders(hidden_ids.clone()) {
+                error::create_log_file("game_session.log".to_string(), &e);
+            }
+            for order in visible_orders {
+                if let Err(e) = wfm
+                    .orders()
+                    .update(
+                        &order.id,
+                        order.platinum as i32,
+                        order.quantity as i32,
+                        false,
+                    )
+                    .await
+                {
+                    error::create_log_file("game_session.log".to_string(), &e);
+                    hidden_ids.retain(|id| id != &order.id);
+                }
+            }
+            if let Err(e) = live_scraper.set_hidden_orders(hidden_ids) {
+                error::create_log_file("game_session.log".to_string(), &e);
+            }
+        });
+    }
+
+    // Shows the orders hidden when the game closed, with their current price and quantity.
+    fn show_hidden_orders(&self) {
+        let live_scraper = self.live_scraper.lock().unwrap().clone();
+        let hidden_ids = live_scraper.hidden_orders.lock().unwrap().clone();
+        if hidden_ids.is_empty() {
+            return;
+        }
+        let wfm = self.wfm.lock().unwrap().clone();
+        tauri::async_runtime::spawn(async move {
+            // Orders removed in the meantime are skipped
+            let orders = match wfm.orders().get_my_orders().await {
+                Ok(orders) => orders,
+                Err(e) => {
+                    error::create_log_file("game_session.log".to_string(), &e);
+                    return;
+                }
+            };
+            let mut all_orders = orders.sell_orders;
+            all_orders.extend(orders.buy_orders);
+            for order in all_orders
+                .into_iter()
+                .filter(|o| !o.visible && hidden_ids.contains(&o.id))
+            {
+                if let Err(e) = wfm
+                    .orders()
+                    .update(
+                        &order.id,
+                        order.platinum as i32,
+                        order.quantity as i32,
+                        true,
+                    )
+                    .await
+                {
+                    error::create_log_file("game_session.log".to_string(), &e);
+                }
+            }
+            if let Err(e) = live_scraper.set_hidden_orders(vec![]) {
+                error::create_log_file("game_session.log".to_string(), &e);
+            }
+        });
+    }
+}
@@ -0,0 +1,193 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+use regex::{Regex, RegexSet};
+use serde_json::json;
+
+use crate::{
+    database::client::DBClient,
+    enums::LogRuleAction,
+    error::{self, AppError},
+    handler::MonitorHandler,
+    helper, logger,
+    settings::{LogRule, SettingsState},
+};
+
+// Runs the log rules the user defined in the settings.
+#[derive(Debug)]
+pub struct OnLogRuleEvent {
+    settings: Arc<Mutex<SettingsState>>,
+    helper: Arc<Mutex<MonitorHandler>>,
+    db: Arc<Mutex<DBClient>>,
+    // The enabled rules with a valid pattern and their compiled pattern, refreshed when the settings are saved
+    rules: Vec<(LogRule, Regex)>,
+    // All rule patterns in one set, so a line is only matched once for all rules
+    rule_set: RegexSet,
+    // When replaying a log the rules are matched but their actions are not run
+    replay: bool,
+    // Rule name -> the last time it fired
+    last_triggered: HashMap<String, Instant>,
+}
+
+impl OnLogRuleEvent {
+    pub fn new(
+        settings: Arc<Mutex<SettingsState>>,
+        helper: Arc<Mutex<MonitorHandler>>,
+        db: Arc<Mutex<DBClient>>,
+        replay: bool,
+    ) -> Self {
+        let rules = Self::compile_rules(&settings.lock().unwrap().ee_log.rules);
+        let rule_set = Self::create_rule_set(&rules);
+        Self {
+            settings,
+            helper,
+            db,
+            rules,
+            rule_set,
+            replay,
+            last_triggered: HashMap::new(),
+        }
+    }
+
+    // Compiles the enabled rules, an invalid pattern is reported and its rule left out.
+    fn compile_rules(rules: &[LogRule]) -> Vec<(LogRule, Regex)> {
+        let mut compiled = vec![];
+        for rule in rules.iter().filter(|r| r.enabled) {
+            match Regex::new(&rule.pattern) {
+                Ok(regex) => compiled.push((rule.clone(), regex)),
+                Err(e) => logger::warning_con(
+                    "OnLogRuleEvent",
+                    format!("Invalid log rule pattern {}: {}", rule.pattern, e).as_str(),
+                ),
+            }
+        }
+        compiled
+    }
+
+    fn create_rule_set(rules: &[(LogRule, Regex)]) -> RegexSet {
+        // Every pattern was compiled on its own already, so the set can not fail
+        RegexSet::new(rules.iter().map(|(rule, _)| rule.pattern.as_str()))
+            .unwrap_or_else(|_| RegexSet::empty())
+    }
+
+    pub fn refresh_settings(&mut self) -> Result<(), AppError> {
+        let rules = self.settings.lock()?.ee_log.rules.clone();
+        self.rules = Self::compile_rules(&rules);
+        self.rule_set = Self::create_rule_set(&self.rules);
+        Ok(())
+    }
+
+    pub fn check(&mut self, _offset: u64, input: &str) -> Result<bool, AppError> {
+        if self.rules.is_empty() {
+            return Ok(false);
+        }
+        let matches = self.rule_set.matches(input);
+        if !matches.matched_any() {
+            return Ok(false);
+        }
+        for index in matches.into_iter() {
+            let (rule, regex) = &self.rules[index];
+            let captures = match regex.captures(input) {
+                Some(captures) => captures,
+                None => continue,
+            };
+            if self.replay {
+                continue;
+            }
+
+            if let Some(last_triggered) = self.last_triggered.get(&rule.name) {
+                if last_triggered.elapsed().as_secs() < rule.cooldown.max(0) as u64 {
+                    continue;
+                }
+            }
+            self.last_triggered
+                .insert(rule.name.clone(), Instant::now());
+
+            let mut values: HashMap<String, String> = HashMap::new();
+            for name in regex.capture_names().flatten() {
+                if let Some(value) = captures.name(name) {
+                    values.insert(name.to_string(), value.as_str().to_string());
+                }
+            }
+            self.run_action(rule, input, values)?;
+        }
+        Ok(true)
+    }
+
+    // Replaces <name> with the capture of that name, <RULE_NAME> and <LINE> are always there.
+    fn fill_template(
+        template: &str,
+        rule: &LogRule,
+        input: &str,
+        values: &HashMap<String, String>,
+    ) -> String {
+        let mut text = template
+            .replace("<RULE_NAME>", &rule.name)
+            .replace("<LINE>", input);
+        for (name, value) in values {
+            text = text.replace(&format!("<{}>", name), value);
+        }
+        text
+    }
+
+    fn run_action(
+        &self,
+        rule: &LogRule,
+        input: &str,
+        values: HashMap<String, String>,
+    ) -> Result<(), AppError> {
+        let title = Self::fill_template(&rule.title, rule, input, &values);
+        let content = Self::fill_template(&rule.content, rule, input, &values);
+        match &rule.action {
+            LogRuleAction::SystemNotify => {
+                self.helper.lock()?.show_notification(
+                    &title,
+                    &content,
+                    Some("assets/icons/icon.png"),
+                    Some("Default"),
+                );
+            }
+            LogRuleAction::Discord => {
+                helper::send_message_to_discord(
+                    rule.webhook.clone().unwrap_or("".to_string()),
+                    title,
+                    content,
+                    rule.user_ids.clone(),
+                );
+            }
+            LogRuleAction::Frontend => {
+                helper::send_message_to_window(
+                    "EELogParser:OnRule",
+                    Some(json!({
+                        "rule": rule.name,
+                        "title": title,
+                        "content": content,
+                        "captures": values,
+                        "line": input,
+                    })),
+                );
+            }
+            LogRuleAction::Database => {
+                // The parser runs on its own thread, the row is written on the async runtime
+                let db = self.db.lock()?.clone();
+                let name = rule.name.clone();
+                let line = input.to_string();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = db.log_event().create(&name, &line, json!(values)).await {
+                        error::create_log_file("ee_log_rules.log".to_string(), &e);
+                    }
+                });
+            }
+            LogRuleAction::Unknown(action) => {
+                logger::warning_con(
+                    "OnLogRuleEvent",
+                    format!("Unknown action {} in log rule {}", action, rule.name).as_str(),
+                );
+            }
+        }
+        Ok(())
+    }
+}
@@ -3,11 +3,26 @@ use std::{
     sync::{Arc, Mutex},
 };
 
-use crate::{error::AppError, handler::MonitorHandler, settings::SettingsState};
-use eyre::eyre;
+use crate::{
+    cache::client::CacheClient,
+    database::client::DBClient,
+    enums::OrderType,
+    error::{self, AppError},
+    handler::MonitorHandler,
+    helper,
+    settings::{Notification, SettingsState},
+    wfm_client::client::WFMClient,
+};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use super::helper::{clean_player_name, PatternSet};
 
 enum Events {
     Conversation,
+    Whisper,
 }
 impl Events {
     fn as_str_list(&self) -> Vec<String> {
@@ -15,48 +30,169 @@ impl Events {
             Events::Conversation => vec![
                 r"Script \[Info\]: ChatRedux\.lua: ChatRedux::AddTab: Adding tab with channel name: F(?<name>.+) to index.+".to_string(),
             ],
+            // A whisper line with the text warframe.market fills in for the buyer or seller
+            Events::Whisper => vec![
+                r"(?<name>[^\s:\]]+): (?<message>Hi! I want to (?:buy|sell): .+)$".to_string(),
+            ],
         }
     }
 }
 
+static CONVERSATION_PATTERNS: Lazy<PatternSet> = Lazy::new(|| {
+    PatternSet::new(Events::Conversation.as_str_list()).expect("Invalid conversation pattern")
+});
+static WHISPER_PATTERNS: Lazy<PatternSet> =
+    Lazy::new(|| PatternSet::new(Events::Whisper.as_str_list()).expect("Invalid whisper pattern"));
+// "Hi! I want to buy: "Item Name (rank 3)" for 25 platinum. (warframe.market)"
+static WFM_TEMPLATE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"Hi! I want to (?<action>buy|sell): "?(?<item>.+?)(?: \(rank (?<rank>\d+)\))?"?(?<riven> riven)? for (?<price>\d+) platinum"#)
+        .expect("Invalid whisper template")
+});
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversationStruct {
+    pub user_name: String,
+    // Byte offset of the line in EE.log
+    pub offset: u64,
+    // The whisper text, None for the line that opens the chat tab
+    pub message: Option<String>,
+}
+
+// A whisper made from a warframe.market template.
+// The action is what the other player wants, "buy" is for our sell listings and "sell" for our buy orders.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WhisperStruct {
+    pub user_name: String,
+    pub message: String,
+    pub action: String,
+    pub item_name: String,
+    pub rank: Option<i64>,
+    pub riven: bool,
+    pub price: i64,
+}
+
+// The listing a whisper is about, the frontend highlights it by its type and id.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WhisperListingStruct {
+    // stock_item, stock_riven or order
+    pub listing_type: String,
+    pub id: String,
+    pub name: String,
+    pub listed_price: Option<i64>,
+    // The owned quantity, None for buy orders
+    pub owned: Option<i64>,
+    pub order_id: Option<String>,
+}
+
+impl WhisperStruct {
+    pub fn parse(user_name: &str, message: &str) -> Option<Self> {
+        let captures = WFM_TEMPLATE.captures(message)?;
+        Some(WhisperStruct {
+            user_name: user_name.to_string(),
+            message: message.to_string(),
+            action: captures.name("action")?.as_str().to_string(),
+            item_name: captures.name("item")?.as_str().trim().to_string(),
+            rank: captures.name("rank").and_then(|r| r.as_str().parse().ok()),
+            riven: captures.name("riven").is_some(),
+            price: captures.name("price")?.as_str().parse().ok()?,
+        })
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct OnNewConversationEvent {
     settings: Arc<Mutex<SettingsState>>,
     helper: Arc<Mutex<MonitorHandler>>,
+    cache: Arc<Mutex<CacheClient>>,
+    db: Arc<Mutex<DBClient>>,
+    wfm: Arc<Mutex<WFMClient>>,
+    // Copy of the notification settings, refreshed when the settings are saved
+    notification: Notification,
+    whisper_notification: Notification,
+    // When replaying a log the conversations are collected instead of notified
+    replay: bool,
+    found_conversations: Arc<Mutex<Vec<ConversationStruct>>>,
 }
 
 impl OnNewConversationEvent {
     pub fn new(
         settings: Arc<Mutex<SettingsState>>,
         helper: Arc<Mutex<MonitorHandler>>,
+        cache: Arc<Mutex<CacheClient>>,
+        db: Arc<Mutex<DBClient>>,
+        wfm: Arc<Mutex<WFMClient>>,
         _: PathBuf,
+        replay: bool,
     ) -> Self {
-        Self { settings, helper }
+        let notifications = settings.lock().unwrap().notifications.clone();
+        Self {
+            settings,
+            helper,
+            cache,
+            db,
+            wfm,
+            notification: notifications.on_new_conversation,
+            whisper_notification: notifications.on_whisper,
+            replay,
+            found_conversations: Arc::new(Mutex::new(vec![])),
+        }
+    }
+
+    // The patterns the lines must match before check is called
+    pub fn patterns() -> Vec<String> {
+        let mut patterns = Events::Conversation.as_str_list();
+        patterns.extend(Events::Whisper.as_str_list());
+        patterns
+    }
+
+    pub fn refresh_settings(&mut self) -> Result<(), AppError> {
+        let notifications = self.settings.lock()?.notifications.clone();
+        self.notification = notifications.on_new_conversation;
+        self.whisper_notification = notifications.on_whisper;
+        Ok(())
     }
 
-    pub fn check(&self, _: usize, input: &str) -> Result<bool, AppError> {
-        let settings = self
-            .settings
-            .lock()?
-            .clone()
-            .notifications
-            .on_new_conversation;
-        let helper = self.helper.lock()?;
+    pub fn get_found_conversations(&self) -> Result<Vec<ConversationStruct>, AppError> {
+        Ok(self.found_conversations.lock()?.clone())
+    }
+
+    pub fn check(&self, offset: u64, input: &str) -> Result<bool, AppError> {
+        let (found, captures) = WHISPER_PATTERNS.match_pattern(input);
+        if found {
+            let username =
+                clean_player_name(&captures.first().cloned().flatten().unwrap_or_default());
+            let message = captures.get(1).cloned().flatten().unwrap_or_default();
+            if self.replay {
+                self.found_conversations.lock()?.push(ConversationStruct {
+                    user_name: username,
+                    offset,
+                    message: Some(message),
+                });
+            } else if let Some(whisper) = WhisperStruct::parse(&username, &message) {
+                self.on_whisper(whisper)?;
+            }
+            return Ok(true);
+        }
 
-        if !settings.system_notify && !settings.discord_notify {
+        let settings = &self.notification;
+        if !self.replay && !settings.system_notify && !settings.discord_notify {
             return Ok(false);
         }
-        let (found, captures) = crate::wf_ee_log_parser::events::helper::match_pattern(
-            input,
-            Events::Conversation.as_str_list(),
-        )
-        .map_err(|e| AppError::new("OnNewConversationEvent", eyre!(e)))?;
-        if found {
-            let username = captures.get(0).unwrap().clone().unwrap();
+        let (found, captures) = CONVERSATION_PATTERNS.match_pattern(input);
+        if found && self.replay {
+            let username =
+                clean_player_name(&captures.first().cloned().flatten().unwrap_or_default());
+            self.found_conversations.lock()?.push(ConversationStruct {
+                user_name: username,
+                offset,
+                message: None,
+            });
+        } else if found {
+            let username = clean_player_name(&captures.first().unwrap().clone().unwrap());
             let content = settings.content.replace("<PLAYER_NAME>", username.as_str());
             // If system notification is enabled, show it
             if settings.system_notify {
-                helper.show_notification(
+                self.helper.lock()?.show_notification(
                     settings.title.as_str(),
                     &content,
                     Some("assets/icons/icon.png"),
@@ -66,13 +202,159 @@ impl OnNewConversationEvent {
             // If discord webhook is enabled, send it
             if settings.discord_notify && settings.webhook.is_some() {
                 crate::helper::send_message_to_discord(
+                    settings.webhook.clone().unwrap_or("".to_string()),
+                    settings.title.clone(),
+                    content,
+                    settings.user_ids.clone(),
+                );
+            }
+        }
+        Ok(found)
+    }
+
+    // Finds the listing the whisper is about and tells the user, the lookups need the async runtime.
+    fn on_whisper(&self, whisper: WhisperStruct) -> Result<(), AppError> {
+        let settings = self.whisper_notification.clone();
+        let helper = Arc::clone(&self.helper);
+        let cache = self.cache.lock()?.clone();
+        let db = self.db.lock()?.clone();
+        let wfm = self.wfm.lock()?.clone();
+        tauri::async_runtime::spawn(async move {
+            let listing = match Self::find_listing(&cache, &db, &wfm, &whisper).await {
+                Ok(listing) => listing,
+                Err(e) => {
+                    error::create_log_file("ee_log_whisper.log".to_string(), &e);
+                    None
+                }
+            };
+            helper::send_message_to_window(
+                "EELogParser:OnWhisper",
+                Some(json!({ "whisper": whisper, "listing": listing })),
+            );
+
+            let listing_text = match &listing {
+                Some(listing) if listing.owned.is_some() => format!(
+                    "Listed for {}p, {} in stock",
+                    listing.listed_price.unwrap_or(0),
+                    listing.owned.unwrap_or(0)
+                ),
+                Some(listing) => format!("Buy order for {}p", listing.listed_price.unwrap_or(0)),
+                None => "No matching listing".to_string(),
+            };
+            let content = settings
+                .content
+                .replace("<PLAYER_NAME>", &whisper.user_name)
+                .replace("<ACTION>", &whisper.action)
+                .replace("<ITEM_NAME>", &whisper.item_name)
+                .replace("<PRICE>", &whisper.price.to_string())
+                .replace("<LISTING>", &listing_text);
+            // If system notification is enabled, show it
+            if settings.system_notify {
+                if let Ok(helper) = helper.lock() {
+                    helper.show_notification(
+                        settings.title.as_str(),
+                        &content,
+                        Some("assets/icons/icon.png"),
+                        Some("Default"),
+                    );
+                }
+            }
+            // If discord webhook is enabled, send it
+            if settings.discord_notify && settings.webhook.is_some() {
+                helper::send_message_to_discord(
                     settings.webhook.unwrap_or("".to_string()),
                     settings.title,
                     content,
                     settings.user_ids.clone(),
                 );
             }
+        });
+        Ok(())
+    }
+
+    // A buy whisper is matched to the riven or item stock and our sell order, a sell whisper to our buy order.
+    async fn find_listing(
+        cache: &CacheClient,
+        db: &DBClient,
+        wfm: &WFMClient,
+        whisper: &WhisperStruct,
+    ) -> Result<Option<WhisperListingStruct>, AppError> {
+        if whisper.riven {
+            if whisper.action != "buy" {
+                return Ok(None);
+            }
+            let riven = db.stock_riven().get_rivens().await?.into_iter().find(|r| {
+                format!("{} {}", r.weapon_name, r.mod_name).eq_ignore_ascii_case(&whisper.item_name)
+            });
+            return Ok(riven.map(|riven| WhisperListingStruct {
+                listing_type: "stock_riven".to_string(),
+                id: riven.id.to_string(),
+                name: format!("{} {}", riven.weapon_name, riven.mod_name),
+                listed_price: riven.listed_price.map(|p| p as i64),
+                owned: Some(1),
+                order_id: riven.order_id,
+            }));
         }
-        Ok(found)
+
+        let item = match cache
+            .items()
+            .get_types()?
+            .into_iter()
+            .find(|i| i.item_name.eq_ignore_ascii_case(&whisper.item_name))
+        {
+            Some(item) => item,
+            None => match cache
+                .items()
+                .find_by_localized_name(&helper::get_warframe_language_code(), &whisper.item_name)?
+            {
+                Some(item) => item,
+                None => return Ok(None),
+            },
+        };
+
+        let order_type = if whisper.action == "buy" {
+            OrderType::Sell
+        } else {
+            OrderType::Buy
+        };
+        let orders = wfm.orders().get_my_orders().await?;
+        let orders = match order_type {
+            OrderType::Sell => orders.sell_orders,
+            _ => orders.buy_orders,
+        };
+        let order = orders.into_iter().find(|o| {
+            o.item.as_ref().is_some_and(|i| i.url_name == item.url_name)
+                && (whisper.rank.is_none() || o.mod_rank == whisper.rank)
+        });
+
+        if order_type == OrderType::Sell {
+            let stock_item = db.stock_item().get_items().await?.into_iter().find(|s| {
+                s.url == item.url_name && whisper.rank.is_none_or(|rank| s.rank as i64 == rank)
+            });
+            if let Some(stock_item) = stock_item {
+                return Ok(Some(WhisperListingStruct {
+                    listing_type: "stock_item".to_string(),
+                    id: stock_item.id.to_string(),
+                    name: stock_item.name,
+                    listed_price: stock_item
+                        .listed_price
+                        .map(|p| p as i64)
+                        .or(order.as_ref().map(|o| o.platinum)),
+                    owned: Some(stock_item.owned as i64),
+                    order_id: order.map(|o| o.id),
+                }));
+            }
+        }
+        Ok(order.map(|order| WhisperListingStruct {
+            listing_type: "order".to_string(),
+            id: order.id.clone(),
+            name: item.item_name.clone(),
+            listed_price: Some(order.platinum),
+            owned: match order_type {
+                OrderType::Sell => Some(order.quantity),
+                _ => None,
+            },
+            order_id: Some(order.id),
+        }))
     }
 }
@@ -1,5 +1,5 @@
 use std::{
-    collections::HashMap,
+    collections::{hash_map::Entry, HashMap},
     path::PathBuf,
     sync::{Arc, Mutex},
 };
@@ -12,39 +12,145 @@ use crate::{
     helper, logger,
     settings::SettingsState,
     structs::TradeClassification,
-    structs::WarframeLanguage,
+    structs::{Item, RivenTypeInfo, WarframeLanguage},
+    wfm_client::client::WFMClient,
 };
 use eyre::eyre;
+use once_cell::sync::OnceCell;
 use serde::{Deserialize, Serialize};
+
+use super::helper as events_helper;
 use serde_json::{json, Value};
 
-#[derive(Debug)]
-struct TradeLogMessages {
-    detect_line: &'static str,
-    detect_trade_confirmation_line: &'static str,
-    detect_trade_failed_line: &'static str,
-    will_receive_line_first_part: &'static str,
-    will_receive_line_second_part: &'static str,
-    platinum_name: &'static str,
+// The dialog texts of a trade in one language.
+// They are kept in "trade_messages.json" so languages can be added or corrected without an update.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeLogMessages {
+    detect_line: String,
+    detect_trade_confirmation_line: String,
+    detect_trade_failed_line: String,
+    will_receive_line_first_part: String,
+    will_receive_line_second_part: String,
+    platinum_name: String,
+    // Set for the built in languages that were not checked against a log of the game,
+    // the user sets it to false after correcting them
+    #[serde(default)]
+    unverified: bool,
+}
+impl TradeLogMessages {
+    fn new(
+        detect_line: &str,
+        detect_trade_confirmation_line: &str,
+        detect_trade_failed_line: &str,
+        will_receive_line_first_part: &str,
+        will_receive_line_second_part: &str,
+        platinum_name: &str,
+    ) -> Self {
+        TradeLogMessages {
+            detect_line: format!("description={}", detect_line),
+            detect_trade_confirmation_line: format!(
+                "description={}, leftItem=/Menu/Confirm_Item_Ok",
+                detect_trade_confirmation_line
+            ),
+            detect_trade_failed_line: format!(
+                "description={}, leftItem=/Menu/Confirm_Item_Ok",
+                detect_trade_failed_line
+            ),
+            will_receive_line_first_part: will_receive_line_first_part.to_string(),
+            will_receive_line_second_part: will_receive_line_second_part.to_string(),
+            platinum_name: platinum_name.to_string(),
+            unverified: false,
+        }
+    }
+    // The built in messages for every language Warframe reports in the launcher log
+    fn get_default(language: &WarframeLanguage) -> Option<Self> {
+        match language {
+            WarframeLanguage::English => Some(TradeLogMessages::new(
+                "Are you sure you want to accept this trade? You are offering",
+                "The trade was successful!",
+                "The trade failed.",
+                "and will receive from ",
+                " the following:",
+                "Platinum",
+            )),
+            WarframeLanguage::French => Some(TradeLogMessages::new(
+                "Êtes-vous sûr de vouloir accepter cet échange ? Vous offrez",
+                "L'échange a réussi !",
+                "L'échange a échoué.",
+                "et recevrez de la part de ",
+                " les objets suivants :",
+                "Platine",
+            )),
+            WarframeLanguage::Spanish => Some(TradeLogMessages::new(
+                "¿Seguro que quieres aceptar este intercambio? Ofreces",
+                "¡El intercambio se ha realizado con éxito!",
+                "El intercambio ha fallado.",
+                "y recibirás de ",
+                " lo siguiente:",
+                "Platino",
+            )),
+            WarframeLanguage::German => Some(TradeLogMessages::new(
+                "Bist du sicher, dass du diesen Handel annehmen willst? Du bietest",
+                "Der Handel war erfolgreich!",
+                "Der Handel ist fehlgeschlagen.",
+                "und erhältst von ",
+                " Folgendes:",
+                "Platin",
+            )),
+            WarframeLanguage::Russian => Some(TradeLogMessages::new(
+                "Вы уверены, что хотите принять этот обмен? Вы предлагаете",
+                "Обмен прошел успешно!",
+                "Обмен не удался.",
+                "и получите от ",
+                " следующее:",
+                "Платина",
+            )),
+            WarframeLanguage::Unknown => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlayerTradeStruct {
-    crated_at: String,
-    user_name: String,
-    trade_type: TradeClassification,
-    total_platinum: i32,
-    offerings: Vec<TradeItemStruct>,
-    receiving: Vec<TradeItemStruct>,
+    pub crated_at: String,
+    pub user_name: String,
+    pub trade_type: TradeClassification,
+    pub total_platinum: i32,
+    pub offerings: Vec<TradeItemStruct>,
+    pub receiving: Vec<TradeItemStruct>,
+}
+impl PlayerTradeStruct {
+    // The platinum received from and paid to the player, used for the trade partner totals.
+    pub fn get_platinum(&self) -> (i64, i64) {
+        let platinum = |items: &Vec<TradeItemStruct>| {
+            items
+                .iter()
+                .filter(|item| item.name == "plat")
+                .map(|item| item.quantity as i64)
+                .sum::<i64>()
+        };
+        (platinum(&self.receiving), platinum(&self.offerings))
+    }
 }
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TradeItemStruct {
-    name: String,
-    wfm_id: Option<String>,
-    wfm_url_name: Option<String>,
-    display_name: String,
-    quantity: i32,
-    rank: i32,
+    pub name: String,
+    pub wfm_id: Option<String>,
+    pub wfm_url_name: Option<String>,
+    pub display_name: String,
+    pub quantity: i32,
+    pub rank: i32,
+    // Set for a riven mod, a veiled riven has no weapon
+    pub riven: Option<TradeRivenStruct>,
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeRivenStruct {
+    pub weapon_url: Option<String>,
+    pub weapon_name: String,
+    pub mod_name: String,
+    pub veiled: bool,
+    // The stock riven that was sold in this trade
+    pub stock_id: Option<i64>,
 }
 
 #[derive(Debug)]
@@ -53,12 +159,25 @@ pub struct OnTradingEvent {
     settings: Arc<Mutex<SettingsState>>,
     chche: Arc<Mutex<CacheClient>>,
     helper: Arc<Mutex<MonitorHandler>>,
+    db: Arc<Mutex<DBClient>>,
+    wfm: Arc<Mutex<WFMClient>>,
     // Current trade
-    trade_log_messages_by_language: HashMap<WarframeLanguage, TradeLogMessages>,
+    // Language code -> trade messages
+    trade_log_messages_by_language: HashMap<String, TradeLogMessages>,
+    // The game language and its trade messages, resolved on first use and again when the game restarts
+    language: OnceCell<String>,
+    trade_log_messages: OnceCell<TradeLogMessages>,
+    // Languages the item names have been requested for, so they are only requested once
+    requested_languages: Vec<String>,
     current_trade_logs: Vec<String>,
     getting_trade_message_multiline: bool,
     waiting_for_trade_message_confirmation: bool,
     current_trade: Arc<Mutex<PlayerTradeStruct>>,
+    // The time the game started, read from the top of the log
+    log_start: Option<chrono::NaiveDateTime>,
+    // When replaying a log the trades are collected instead of notified
+    replay: bool,
+    found_trades: Vec<PlayerTradeStruct>,
 }
 
 impl OnTradingEvent {
@@ -66,41 +185,61 @@ impl OnTradingEvent {
         settings: Arc<Mutex<SettingsState>>,
         helper: Arc<Mutex<MonitorHandler>>,
         chche: Arc<Mutex<CacheClient>>,
+        db: Arc<Mutex<DBClient>>,
+        wfm: Arc<Mutex<WFMClient>>,
         wf_ee_path: PathBuf,
+        replay: bool,
     ) -> Self {
         Self {
             settings,
             helper,
             chche,
+            db,
+            wfm,
             wf_ee_path,
-            trade_log_messages_by_language: HashMap::from([(
-                WarframeLanguage::English,
-                TradeLogMessages {
-                    detect_line:
-                        "description=Are you sure you want to accept this trade? You are offering",
-                    detect_trade_confirmation_line:
-                        "description=The trade was successful!, leftItem=/Menu/Confirm_Item_Ok",
-                    detect_trade_failed_line:
-                        "description=The trade failed., leftItem=/Menu/Confirm_Item_Ok",
-                    will_receive_line_first_part: "and will receive from ",
-                    will_receive_line_second_part: " the following:",
-                    platinum_name: "Platinum",
-                },
-            )]),
+            trade_log_messages_by_language: Self::load_trade_log_messages(),
+            language: OnceCell::new(),
+            trade_log_messages: OnceCell::new(),
+            requested_languages: Vec::new(),
             current_trade_logs: Vec::new(),
             getting_trade_message_multiline: false,
             waiting_for_trade_message_confirmation: false,
             current_trade: Arc::new(Mutex::new(PlayerTradeStruct {
-                crated_at: chrono::Local::now().to_string(),
+                crated_at: chrono::Local::now().to_rfc3339(),
                 total_platinum: -1,
                 user_name: "".to_string(),
                 trade_type: TradeClassification::Unknown,
                 offerings: Vec::new(),
                 receiving: Vec::new(),
             })),
+            log_start: None,
+            replay,
+            found_trades: Vec::new(),
         }
     }
-    pub fn check(&mut self, _index: usize, input: &str) -> Result<bool, AppError> {
+    // The patterns the lines must match before check is called, see also is_reading_trade
+    pub fn patterns() -> Vec<String> {
+        vec![
+            events_helper::log_start_pattern(),
+            regex::escape("[Info]: Dialog.lua: Dialog::CreateOkCancel(description="),
+            regex::escape("[Info]: Dialog.lua: Dialog::CreateOk(description="),
+        ]
+    }
+    // The lines of a trade dialog after the first one do not match any pattern, they all go to check
+    pub fn is_reading_trade(&self) -> bool {
+        self.getting_trade_message_multiline
+    }
+    pub fn take_found_trades(&mut self) -> Vec<PlayerTradeStruct> {
+        std::mem::take(&mut self.found_trades)
+    }
+    pub fn check(&mut self, _offset: u64, input: &str) -> Result<bool, AppError> {
+        if let Some(log_start) = events_helper::parse_log_start(input) {
+            self.log_start = Some(log_start);
+            // The language can be changed between game sessions
+            self.language = OnceCell::new();
+            self.trade_log_messages = OnceCell::new();
+            return Ok(false);
+        }
         while self.getting_trade_message_multiline {
             if input.contains("[Info]") || input.contains("[Error]") || input.contains("[Warning]")
             {
@@ -141,8 +280,121 @@ impl OnTradingEvent {
         Ok(false)
     }
 
+    // The trade messages of the languages that are built in.
+    // Only English is taken from a real EE.log, the other languages are translated by hand and marked unverified.
+    fn default_trade_log_messages() -> HashMap<String, TradeLogMessages> {
+        ["en", "fr", "es", "de", "ru"]
+            .iter()
+            .filter_map(|code| {
+                TradeLogMessages::get_default(&WarframeLanguage::from_str(code)).map(|mut m| {
+                    m.unverified = *code != "en";
+                    (code.to_string(), m)
+                })
+            })
+            .collect()
+    }
+
+    // Reads the trade messages from "trade_messages.json", the built in languages are added when missing.
+    // A file that can not be read or parsed is left as it is, so the edits of the user are not lost.
+    fn load_trade_log_messages() -> HashMap<String, TradeLogMessages> {
+        let path = helper::get_app_roaming_path().join("trade_messages.json");
+        let defaults = Self::default_trade_log_messages();
+        let mut messages: HashMap<String, TradeLogMessages> = match std::fs::read(&path) {
+            Ok(content) => match serde_json::from_slice(&content) {
+                Ok(messages) => messages,
+                Err(e) => {
+                    logger::warning_con(
+                        "OnTradingEvent",
+                        format!(
+                            "Could not parse the trade messages, using the built in ones: {}",
+                            e
+                        )
+                        .as_str(),
+                    );
+                    return defaults;
+                }
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => {
+                logger::warning_con(
+                    "OnTradingEvent",
+                    format!(
+                        "Could not read the trade messages, using the built in ones: {}",
+                        e
+                    )
+                    .as_str(),
+                );
+                return defaults;
+            }
+        };
+
+        let mut changed = false;
+        for (language, default) in defaults {
+            if let Entry::Vacant(entry) = messages.entry(language) {
+                entry.insert(default);
+                changed = true;
+            }
+        }
+        if changed {
+            if let Err(e) = std::fs::write(&path, json!(messages).to_string()) {
+                logger::warning_con(
+                    "OnTradingEvent",
+                    format!("Could not save the trade messages: {}", e).as_str(),
+                );
+            }
+        }
+        messages
+    }
+
+    // The game language, Launcher.log is only read once per game session.
+    fn get_language(&self) -> &str {
+        self.language
+            .get_or_init(helper::get_warframe_language_code)
+    }
+
+    // Returns the trade messages of the game language, English when the language is not known.
+    fn get_trade_log_messages(&self) -> TradeLogMessages {
+        self.trade_log_messages
+            .get_or_init(|| {
+                let language = self.get_language();
+                if let Some(messages) = self.trade_log_messages_by_language.get(language) {
+                    if messages.unverified {
+                        logger::warning_con(
+                            "OnTradingEvent",
+                            format!(
+                                "The trade messages for language {} are not checked against a game log yet, correct them in trade_messages.json if trades are missed",
+                                language
+                            )
+                            .as_str(),
+                        );
+                    }
+                    return messages.clone();
+                }
+                logger::warning_con(
+                    "OnTradingEvent",
+                    format!(
+                        "No trade messages for language {}, add them to trade_messages.json",
+                        language
+                    )
+                    .as_str(),
+                );
+                self.trade_log_messages_by_language
+                    .get("en")
+                    .cloned()
+                    .or(TradeLogMessages::get_default(&WarframeLanguage::English))
+                    .unwrap()
+            })
+            .clone()
+    }
     fn start_trade_log(&mut self, msg: &str) {
         self.reset_trade();
+        // A live trade happens now, a replayed trade without a log time is left without a time
+        self.current_trade.lock().unwrap().crated_at =
+            match events_helper::get_line_time(self.log_start, msg) {
+                Some(time) => time,
+                None if self.replay => "".to_string(),
+                None => chrono::Local::now().to_rfc3339(),
+            };
         self.received_trade_log_message(msg);
     }
     fn received_trade_log_message(&mut self, msg: &str) {
@@ -153,9 +405,8 @@ impl OnTradingEvent {
         let trade_struct_mutex = Arc::clone(&self.current_trade);
         let mut trade_struct = trade_struct_mutex.lock()?;
 
-        let lang = helper::get_warframe_language();
-
-        let trade_log_messages = self.trade_log_messages_by_language.get(&lang).unwrap();
+        let trade_log_messages = self.get_trade_log_messages();
+        self.request_localized_names()?;
 
         let mut logs = self.current_trade_logs.clone();
 
@@ -168,16 +419,16 @@ impl OnTradingEvent {
         let mut flag = true;
         // Loop through the trade logs
         for (_index, log) in logs.iter().enumerate() {
-            if log == "\n" || log == "" || log.contains(trade_log_messages.detect_line) {
+            if log == "\n" || log.is_empty() || log.contains(&trade_log_messages.detect_line) {
                 continue;
             }
             // Find the user name
-            if log.contains(trade_log_messages.will_receive_line_first_part)
-                && log.contains(trade_log_messages.will_receive_line_second_part)
+            if log.contains(&trade_log_messages.will_receive_line_first_part)
+                && log.contains(&trade_log_messages.will_receive_line_second_part)
             {
                 trade_struct.user_name = log
-                    .replace(trade_log_messages.will_receive_line_first_part, "")
-                    .replace(trade_log_messages.will_receive_line_second_part, "")
+                    .replace(&trade_log_messages.will_receive_line_first_part, "")
+                    .replace(&trade_log_messages.will_receive_line_second_part, "")
                     .replace("\u{e000}", "")
                     .trim()
                     .to_string();
@@ -224,6 +475,7 @@ impl OnTradingEvent {
                             quantity: num,
                             display_name: item_name.clone(),
                             rank: 0,
+                            riven: None,
                         });
                     }
                 } else if let Some(traded_object) = trade_struct
@@ -240,6 +492,7 @@ impl OnTradingEvent {
                         quantity: num,
                         display_name: item_name.clone(),
                         rank: 0,
+                        riven: None,
                     });
                 }
             }
@@ -287,14 +540,70 @@ impl OnTradingEvent {
         Ok(())
     }
 
+    // Finds an item by its English name, or by its name in the game language.
+    fn find_item(&self, name: &str) -> Result<Option<Item>, AppError> {
+        let cache = self.chche.lock()?.clone();
+        let item_cache = cache.items().get_types()?;
+        if let Some(item) = item_cache.iter().find(|p| p.item_name == name) {
+            return Ok(Some(item.clone()));
+        }
+        let language = self.get_language();
+        if language == "en" {
+            return Ok(None);
+        }
+        cache.items().find_by_localized_name(language, name)
+    }
+
+    // Finds the weapon of a riven by its name, the warframe.market url is the name in lower case with underscores.
+    fn find_riven_weapon(&self, weapon_name: &str) -> Result<Option<RivenTypeInfo>, AppError> {
+        let cache = self.chche.lock()?.clone();
+        let url_name = weapon_name
+            .to_lowercase()
+            .replace('&', "and")
+            .replace(['\'', '-'], "")
+            .replace(' ', "_");
+        if let Some(weapon) = cache.riven().find_type(&url_name)? {
+            return Ok(Some(weapon));
+        }
+        Ok(cache
+            .riven()
+            .get_types()?
+            .into_iter()
+            .find(|w| w.item_name.eq_ignore_ascii_case(weapon_name)))
+    }
+
+    // Fetches the item names of the game language in the background when the cache does not have them.
+    fn request_localized_names(&mut self) -> Result<(), AppError> {
+        let language = self.get_language().to_string();
+        if language == "en" || self.requested_languages.contains(&language) {
+            return Ok(());
+        }
+        self.requested_languages.push(language.clone());
+        let cache = self.chche.lock()?.clone();
+        if cache
+            .cache_data
+            .lock()?
+            .item
+            .localized_names
+            .contains_key(&language)
+        {
+            return Ok(());
+        }
+        tauri::async_runtime::spawn(async move {
+            match cache.items().refresh_localized_names(&language).await {
+                Ok(_) => {
+                    if let Err(e) = cache.save_to_file() {
+                        error::create_log_file("cache.log".to_string(), &e);
+                    }
+                }
+                Err(e) => error::create_log_file("cache.log".to_string(), &e),
+            }
+        });
+        Ok(())
+    }
+
     fn convert_itemname_to_id(&self, item: &mut TradeItemStruct) -> Result<bool, AppError> {
         item.rank = -1;
-        let item_cache = self
-            .chche
-            .lock()?
-            .items()
-            .get_types()
-            .expect("No item cache found");
         // Find the item
 
         if item.name.contains("(") && item.name.ends_with(")") {
@@ -313,16 +622,34 @@ impl OnTradingEvent {
                 // Check if the item is a riven mod
                 if item_details.contains("(RIVEN RANK ") {
                     if item_details.contains(" Riven Mod") {
+                        item.riven = Some(TradeRivenStruct {
+                            weapon_url: None,
+                            weapon_name: "".to_string(),
+                            mod_name: name_part.clone(),
+                            veiled: true,
+                            stock_id: None,
+                        });
                         item.display_name = name_part + " (Veiled)";
                     } else {
                         let str3 = name_part[..name_part.rfind(" ").unwrap()].to_string();
                         let str4 = name_part[name_part.rfind(" ").unwrap() + 1..].to_string();
                         item.name = format!("/AF_Special/Riven/{}/{}", str3, str4);
+                        item.display_name = name_part.clone();
+                        let weapon = self.find_riven_weapon(&str3)?;
+                        item.wfm_id = weapon.as_ref().map(|w| w.id.clone());
+                        item.wfm_url_name = weapon.as_ref().map(|w| w.url_name.clone());
+                        item.riven = Some(TradeRivenStruct {
+                            weapon_url: weapon.map(|w| w.url_name),
+                            weapon_name: str3,
+                            mod_name: str4,
+                            veiled: false,
+                            stock_id: None,
+                        });
                     }
 
                     return Ok(true);
                 }
-                let ch_item = item_cache.iter().find(|p| p.item_name == name_part);
+                let ch_item = self.find_item(&name_part)?;
                 if ch_item.is_some() {
                     let ch_item = ch_item.unwrap();
                     item.wfm_id = Some(ch_item.id.clone());
@@ -335,6 +662,12 @@ impl OnTradingEvent {
                 return Ok(true);
             }
         }
+        if let Some(ch_item) = self.find_item(&item.name)? {
+            item.wfm_id = Some(ch_item.id.clone());
+            item.wfm_url_name = Some(ch_item.url_name.clone());
+            item.display_name = ch_item.item_name.clone();
+            return Ok(true);
+        }
         if item.name.chars().count() != item.name.len() {
             let arcane_name_part = item.name[..item.name.rfind(' ').unwrap_or(0)].to_string();
             item.display_name = arcane_name_part;
@@ -348,6 +681,12 @@ impl OnTradingEvent {
         let mh = self.helper.lock()?.clone();
         let trade = self.current_trade.lock()?.clone();
 
+        if self.replay {
+            self.found_trades.push(trade);
+            self.reset_trade();
+            return Ok(());
+        }
+
         // Send a notification to the user
         mh.show_notification(
             "Trade Accepted",
@@ -359,6 +698,11 @@ impl OnTradingEvent {
         // Send the trade to the main window
         helper::send_message_to_window("Client:Trade:Received", Some(json!(trade.clone())));
 
+        self.record_trade_partner(&trade)?;
+        if trade.trade_type == TradeClassification::Sale {
+            self.sell_traded_riven(&trade)?;
+        }
+
         match self.read_json_file(file_path) {
             Ok(data) => {
                 // Modify the data
@@ -383,6 +727,120 @@ impl OnTradingEvent {
         Ok(())
     }
 
+    // Adds the trade to the totals of the player in the trade partner directory.
+    fn record_trade_partner(&self, trade: &PlayerTradeStruct) -> Result<(), AppError> {
+        if trade.user_name.is_empty() {
+            return Ok(());
+        }
+        let (platinum_in, platinum_out) = trade.get_platinum();
+        let user_name = trade.user_name.clone();
+        let seen = trade.crated_at.clone();
+        let db = self.db.lock()?.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = db
+                .trade_partner()
+                .record_trade(&user_name, platinum_in, platinum_out, &seen)
+                .await
+            {
+                error::create_log_file("trading.log".to_string(), &e);
+            }
+        });
+        Ok(())
+    }
+
+    // Finds the sold riven in the stock by its weapon and name, then removes it from the stock and closes its auction.
+    // The matching stock rivens are sent as candidates, so the user can pick one when there is no single match.
+    fn sell_traded_riven(&self, trade: &PlayerTradeStruct) -> Result<(), AppError> {
+        let riven = match trade
+            .offerings
+            .iter()
+            .find_map(|item| item.riven.clone().filter(|r| !r.veiled))
+        {
+            Some(riven) => riven,
+            None => return Ok(()),
+        };
+        let auto_sell = self.settings.lock()?.ee_log.sell_traded_rivens;
+        let db = self.db.lock()?.clone();
+        let wfm = self.wfm.lock()?.clone();
+        let mh = self.helper.lock()?.clone();
+        let mut trade = trade.clone();
+        tauri::async_runtime::spawn(async move {
+            let rivens = match db.stock_riven().get_rivens().await {
+                Ok(rivens) => rivens,
+                Err(e) => {
+                    error::create_log_file("trading.log".to_string(), &e);
+                    return;
+                }
+            };
+            let matches = rivens
+                .into_iter()
+                .filter(|stock| {
+                    riven.weapon_url.as_ref().map_or(
+                        stock.weapon_name.eq_ignore_ascii_case(&riven.weapon_name),
+                        |url| *url == stock.weapon_url,
+                    ) && stock.mod_name.eq_ignore_ascii_case(&riven.mod_name)
+                })
+                .collect::<Vec<_>>();
+            // Two rivens with the same weapon and name can not be told apart, the user picks one
+            if matches.len() != 1 {
+                helper::send_message_to_window(
+                    "Client:Trade:Riven",
+                    Some(json!({
+                        "trade": trade,
+                        "stock_riven": null,
+                        "candidates": matches,
+                        "sold": false
+                    })),
+                );
+                return;
+            }
+            let stock = matches[0].clone();
+            for item in trade.offerings.iter_mut() {
+                if let Some(item_riven) = item.riven.as_mut() {
+                    item_riven.stock_id = Some(stock.id);
+                }
+            }
+
+            let mut sold = false;
+            if auto_sell {
+                match db
+                    .stock_riven()
+                    .sell_riven(stock.id, trade.total_platinum)
+                    .await
+                {
+                    Ok(_) => sold = true,
+                    Err(e) => error::create_log_file("trading.log".to_string(), &e),
+                }
+                if let (true, Some(order_id)) = (sold, stock.order_id.as_ref()) {
+                    if let Err(e) = wfm.auction().delete(order_id).await {
+                        error::create_log_file("trading.log".to_string(), &e);
+                    }
+                }
+            }
+            if sold {
+                mh.show_notification(
+                    "Riven Sold",
+                    &format!(
+                        "{} {} sold to {} for {} platinum",
+                        stock.weapon_name, stock.mod_name, trade.user_name, trade.total_platinum
+                    ),
+                    Some("assets/icons/icon.png"),
+                    Some("Default"),
+                );
+            }
+            helper::send_message_to_window(
+                "Client:Trade:Riven",
+                Some(json!({
+                    "trade": trade,
+                    "stock_riven": stock,
+                    "candidates": matches,
+                    "sold": sold
+                })),
+            );
+        });
+        Ok(())
+    }
+
     fn trade_failed(&mut self) {
         self.reset_trade();
     }
@@ -400,36 +858,30 @@ impl OnTradingEvent {
     }
 
     fn is_beginninig_of_tradelog(&self, msg: &str) -> Result<bool, AppError> {
-        let lang = helper::get_warframe_language();
-        // Find trade log messages
-        let trade_log_messages = self.trade_log_messages_by_language.get(&lang).unwrap();
+        let trade_log_messages = self.get_trade_log_messages();
 
         // Check if the message is the beginning of a trade log
-        if msg.contains(trade_log_messages.detect_line) {
+        if msg.contains(&trade_log_messages.detect_line) {
             return Ok(true);
         }
         Ok(false)
     }
 
     fn is_trade_confirmation(&self, msg: &str) -> Result<bool, AppError> {
-        let lang = helper::get_warframe_language();
-        // Find trade log messages
-        let trade_log_messages = self.trade_log_messages_by_language.get(&lang).unwrap();
+        let trade_log_messages = self.get_trade_log_messages();
 
         // Check if the message is the beginning of a trade log
-        if msg.contains(trade_log_messages.detect_trade_confirmation_line) {
+        if msg.contains(&trade_log_messages.detect_trade_confirmation_line) {
             return Ok(true);
         }
         Ok(false)
     }
 
     fn is_trade_failed(&self, msg: &str) -> Result<bool, AppError> {
-        let lang = helper::get_warframe_language();
-        // Find trade log messages
-        let trade_log_messages = self.trade_log_messages_by_language.get(&lang).unwrap();
+        let trade_log_messages = self.get_trade_log_messages();
 
         // Check if the message is the beginning of a trade log
-        if msg.contains(trade_log_messages.detect_trade_failed_line) {
+        if msg.contains(&trade_log_messages.detect_trade_failed_line) {
             return Ok(true);
         }
         Ok(false)
@@ -0,0 +1,288 @@
+use std::fs::{File, Metadata};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::PathBuf;
+
+// Identifies a file on disk, so a recreated file is not mistaken for the old one.
+// Unix uses the inode, Windows uses the creation time.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct FileIdentity {
+    #[cfg(unix)]
+    inode: u64,
+    #[cfg(not(unix))]
+    created: Option<std::time::SystemTime>,
+}
+
+impl FileIdentity {
+    fn from_metadata(metadata: &Metadata) -> Self {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            FileIdentity {
+                inode: metadata.ino(),
+            }
+        }
+        #[cfg(not(unix))]
+        {
+            FileIdentity {
+                created: metadata.created().ok(),
+            }
+        }
+    }
+}
+
+// Bytes kept from the start of the file, the first line of EE.log holds the time the game started
+const HEAD_LEN: u64 = 256;
+
+// Reads the lines added to a file since the last read.
+// Lines are returned with the byte offset they start at, a line without its newline is kept until it is finished.
+#[derive(Clone, Debug)]
+pub struct LogTailer {
+    path: PathBuf,
+    // Byte offset of the next byte to read
+    offset: u64,
+    identity: Option<FileIdentity>,
+    // The first bytes of the file, a file truncated and written past the offset again starts differently
+    head: Vec<u8>,
+    // The unfinished last line and the offset it starts at
+    partial: Vec<u8>,
+    partial_offset: u64,
+}
+
+impl LogTailer {
+    pub fn new(path: PathBuf) -> Self {
+        LogTailer {
+            path,
+            offset: 0,
+            identity: None,
+            head: Vec::new(),
+            partial: Vec::new(),
+            partial_offset: 0,
+        }
+    }
+
+    // Skips everything in the file, only lines written from now on are read.
+    pub fn seek_to_end(&mut self) -> io::Result<()> {
+        let mut file = File::open(&self.path)?;
+        let metadata = file.metadata()?;
+        self.identity = Some(FileIdentity::from_metadata(&metadata));
+        self.reset(metadata.len());
+        self.head = Self::read_head(&mut file, HEAD_LEN)?;
+        Ok(())
+    }
+
+    fn reset(&mut self, offset: u64) {
+        self.offset = offset;
+        self.head.clear();
+        self.partial.clear();
+        self.partial_offset = offset;
+    }
+
+    fn read_head(file: &mut File, len: u64) -> io::Result<Vec<u8>> {
+        let mut head = Vec::new();
+        file.seek(SeekFrom::Start(0))?;
+        file.take(len).read_to_end(&mut head)?;
+        Ok(head)
+    }
+
+    // Returns the unfinished last line, used when the whole file is read and nothing more is written.
+    pub fn take_partial(&mut self) -> Option<(u64, String)> {
+        if self.partial.is_empty() {
+            return None;
+        }
+        let line = String::from_utf8_lossy(&self.partial).to_string();
+        self.partial.clear();
+        Some((self.partial_offset, line))
+    }
+
+    pub fn read_new_lines(&mut self) -> io::Result<Vec<(u64, String)>> {
+        let mut file = File::open(&self.path)?;
+        let metadata = file.metadata()?;
+        let identity = FileIdentity::from_metadata(&metadata);
+
+        // The game recreates the file on start and may truncate it, both start over from the beginning
+        if self.identity.as_ref() != Some(&identity) {
+            if self.identity.is_some() {
+                self.reset(0);
+            }
+            self.identity = Some(identity);
+        }
+        if metadata.len() < self.offset
+            || Self::read_head(&mut file, self.head.len() as u64)? != self.head
+        {
+            self.reset(0);
+        }
+        if (self.head.len() as u64) < HEAD_LEN.min(metadata.len()) {
+            self.head = Self::read_head(&mut file, HEAD_LEN)?;
+        }
+        if metadata.len() == self.offset {
+            return Ok(vec![]);
+        }
+
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut buffer = Vec::new();
+        file.read_to_end(&mut buffer)?;
+
+        let mut lines = Vec::new();
+        let mut line_start = self.partial_offset;
+        let mut line = std::mem::take(&mut self.partial);
+        if line.is_empty() {
+            line_start = self.offset;
+        }
+        let mut position = self.offset;
+        for byte in buffer {
+            position += 1;
+            if byte == b'\n' {
+                if line.last() == Some(&b'\r') {
+                    line.pop();
+                }
+                lines.push((line_start, String::from_utf8_lossy(&line).to_string()));
+                line.clear();
+                line_start = position;
+            } else {
+                line.push(byte);
+            }
+        }
+        self.offset = position;
+        self.partial = line;
+        self.partial_offset = line_start;
+        Ok(lines)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    // A log file of its own in the temp folder, removed again when the test is done
+    struct TempLog(PathBuf);
+
+    impl TempLog {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!("quantframe-tailer-{}.log", name));
+            std::fs::write(&path, "").unwrap();
+            TempLog(path)
+        }
+
+        fn append(&self, content: &str) {
+            let mut file = std::fs::OpenOptions::new()
+                .append(true)
+                .open(&self.0)
+                .unwrap();
+            file.write_all(content.as_bytes()).unwrap();
+        }
+    }
+
+    impl Drop for TempLog {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    fn lines(tailer: &mut LogTailer) -> Vec<(u64, String)> {
+        tailer.read_new_lines().unwrap()
+    }
+
+    #[test]
+    fn reads_appended_lines_with_their_offsets() {
+        let log = TempLog::new("append");
+        let mut tailer = LogTailer::new(log.0.clone());
+        log.append("first\nsecond\r\n");
+        assert_eq!(
+            lines(&mut tailer),
+            vec![(0, "first".to_string()), (6, "second".to_string())]
+        );
+        assert!(lines(&mut tailer).is_empty());
+
+        log.append("third\n");
+        assert_eq!(lines(&mut tailer), vec![(14, "third".to_string())]);
+    }
+
+    #[test]
+    fn keeps_a_partial_line_until_it_is_finished() {
+        let log = TempLog::new("partial");
+        let mut tailer = LogTailer::new(log.0.clone());
+        log.append("first\nsec");
+        assert_eq!(lines(&mut tailer), vec![(0, "first".to_string())]);
+
+        log.append("ond\nthi");
+        assert_eq!(lines(&mut tailer), vec![(6, "second".to_string())]);
+        assert_eq!(tailer.take_partial(), Some((13, "thi".to_string())));
+    }
+
+    #[test]
+    fn starts_over_when_the_file_is_truncated() {
+        let log = TempLog::new("truncate");
+        let mut tailer = LogTailer::new(log.0.clone());
+        log.append("first line\nsecond line\n");
+        assert_eq!(lines(&mut tailer).len(), 2);
+
+        std::fs::OpenOptions::new()
+            .write(true)
+            .truncate(true)
+            .open(&log.0)
+            .unwrap();
+        log.append("new\n");
+        assert_eq!(lines(&mut tailer), vec![(0, "new".to_string())]);
+    }
+
+    #[test]
+    fn starts_over_when_the_file_is_rewritten_past_the_offset() {
+        let log = TempLog::new("rewrite");
+        let mut tailer = LogTailer::new(log.0.clone());
+        log.append("0.000 Sys [Diag]: Current time: 1\n");
+        assert_eq!(lines(&mut tailer).len(), 1);
+
+        // Truncated and written again between two reads, the file is longer than the offset
+        std::fs::OpenOptions::new()
+            .write(true)
+            .truncate(true)
+            .open(&log.0)
+            .unwrap();
+        log.append("0.000 Sys [Diag]: Current time: 2\n1.000 Sys [Info]: Logged in\n");
+        assert_eq!(
+            lines(&mut tailer),
+            vec![
+                (0, "0.000 Sys [Diag]: Current time: 2".to_string()),
+                (34, "1.000 Sys [Info]: Logged in".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn starts_over_when_the_file_is_recreated() {
+        let log = TempLog::new("recreate");
+        let mut tailer = LogTailer::new(log.0.clone());
+        log.append("old first line\n");
+        assert_eq!(lines(&mut tailer).len(), 1);
+
+        // A new file moved to the same path has a new inode, even when it starts the same
+        let new_path = log.0.with_extension("new");
+        std::fs::write(&new_path, "old first line\nnext\n").unwrap();
+        std::fs::rename(&new_path, &log.0).unwrap();
+        assert_eq!(
+            lines(&mut tailer),
+            vec![(0, "old first line".to_string()), (15, "next".to_string())]
+        );
+    }
+
+    #[test]
+    fn offsets_do_not_drift_over_many_reads() {
+        let log = TempLog::new("drift");
+        let mut tailer = LogTailer::new(log.0.clone());
+        let mut expected = 0;
+        for i in 0..50 {
+            let line = format!("{} Sys [Info]: line {}", i, "x".repeat(i));
+            // Every other line is written in two parts
+            let (start, end) = line.split_at(line.len() / 2);
+            log.append(start);
+            let mut read = lines(&mut tailer);
+            log.append(&format!("{}\r\n", end));
+            read.extend(lines(&mut tailer));
+            assert_eq!(read, vec![(expected, line.clone())]);
+            expected += line.len() as u64 + 2;
+        }
+        let content = std::fs::read(&log.0).unwrap();
+        assert_eq!(content.len() as u64, expected);
+    }
+}
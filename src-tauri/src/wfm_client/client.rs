@@ -99,6 +99,7 @@ impl WFMClient {
         url: &str,
         payload_key: Option<&str>,
         body: Option<Value>,
+        language: Option<&str>,
     ) -> Result<ApiResult<T>, AppError> {
         let auth = self.auth.lock()?.clone();
         let mut rate_limiter = self.limiter.lock().await;
@@ -123,7 +124,10 @@ impl WFMClient {
                 "User-Agent",
                 format!("Quantframe {}", packageinfo.version.to_string()),
             )
-            .header("Language", auth.region);
+            .header(
+                "Language",
+                language.map(|l| l.to_string()).unwrap_or(auth.region),
+            );
 
         let request = match body.clone() {
             Some(content) => request.json(&content),
@@ -236,7 +240,20 @@ impl WFMClient {
         payload_key: Option<&str>,
     ) -> Result<ApiResult<T>, AppError> {
         let payload: ApiResult<T> = self
-            .send_request(Method::GET, url, payload_key, None)
+            .send_request(Method::GET, url, payload_key, None, None)
+            .await?;
+        Ok(payload)
+    }
+
+    // Same as get, but the response is in the given language instead of the region of the user
+    pub async fn get_in_language<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        payload_key: Option<&str>,
+        language: &str,
+    ) -> Result<ApiResult<T>, AppError> {
+        let payload: ApiResult<T> = self
+            .send_request(Method::GET, url, payload_key, None, Some(language))
             .await?;
         Ok(payload)
     }
@@ -248,7 +265,7 @@ impl WFMClient {
         body: Value,
     ) -> Result<ApiResult<T>, AppError> {
         let payload: ApiResult<T> = self
-            .send_request(Method::POST, url, payload_key, Some(body))
+            .send_request(Method::POST, url, payload_key, Some(body), None)
             .await?;
         Ok(payload)
     }
@@ -259,7 +276,7 @@ impl WFMClient {
         payload_key: Option<&str>,
     ) -> Result<ApiResult<T>, AppError> {
         let payload: ApiResult<T> = self
-            .send_request(Method::DELETE, url, payload_key, None)
+            .send_request(Method::DELETE, url, payload_key, None, None)
             .await?;
         Ok(payload)
     }
@@ -271,7 +288,7 @@ impl WFMClient {
         body: Option<Value>,
     ) -> Result<ApiResult<T>, AppError> {
         let payload: ApiResult<T> = self
-            .send_request(Method::PUT, url, payload_key, body)
+            .send_request(Method::PUT, url, payload_key, body, None)
             .await?;
         Ok(payload)
     }
@@ -7,7 +7,8 @@ use crate::{
     error::{ApiResult, AppError},
     helper, logger,
     structs::{
-        Auction, AuctionItem, AuctionOwner, RivenAttribute, RivenAttributeInfo, RivenTypeInfo,
+        Auction, AuctionItem, AuctionOwner, AuctionWeaponInfo, RivenAttribute, RivenAttributeInfo,
+        RivenTypeInfo,
     },
     wfm_client::client::WFMClient,
 };
@@ -74,6 +75,37 @@ impl<'a> AuctionModule<'a> {
             }
         };
     }
+    pub async fn get_all_weapon_types(
+        &self,
+        auction_type: &str,
+    ) -> Result<Vec<AuctionWeaponInfo>, AppError> {
+        let url = format!("{}/weapons", auction_type);
+        match self
+            .client
+            .get::<Vec<AuctionWeaponInfo>>(&url, Some("weapons"))
+            .await
+        {
+            Ok(ApiResult::Success(mut payload, _headers)) => {
+                for weapon in payload.iter_mut() {
+                    weapon.auction_type = Some(auction_type.to_string());
+                }
+                self.client.debug(
+                    &self.debug_id,
+                    "Auction:GetAllWeaponTypes",
+                    format!("Found {} {} weapons", payload.len(), auction_type).as_str(),
+                    None,
+                );
+                Ok(payload)
+            }
+            Ok(ApiResult::Error(error, _headers)) => Err(self.client.create_api_error(
+                "Auction:GetAllWeaponTypes",
+                error,
+                eyre!("There was an error getting all {} weapons", auction_type),
+                crate::enums::LogLevel::Error,
+            )),
+            Err(err) => Err(err),
+        }
+    }
 
     // User methods (sea-query)
     pub async fn get_user_auctions(
@@ -152,6 +184,17 @@ impl<'a> AuctionModule<'a> {
                 "mastery_level": item.mastery_level,
             });
             body["item"] = item_riven;
+        } else if auction_type == "lich" || auction_type == "sister" {
+            let item_weapon = json!({
+                "type": auction_type,
+                "weapon_url_name": item.weapon_url_name,
+                "element": item.element,
+                "damage": item.damage,
+                "having_ephemera": item.having_ephemera,
+                "quirk": item.quirk,
+                "name": item.name,
+            });
+            body["item"] = item_weapon;
         } else if auction_type == "item" {
             logger::warning_con(
                 "WarframeMarket:Auction:Create",
@@ -332,6 +375,83 @@ impl<'a> AuctionModule<'a> {
             }
         };
     }
+    #[allow(clippy::too_many_arguments)]
+    pub async fn search_weapon(
+        &self,
+        auction_type: &str,
+        weapon_url_name: &str,
+        element: Option<&str>,
+        having_ephemera: Option<bool>,
+        damage_min: Option<i64>,
+        damage_max: Option<i64>,
+        quirk: Option<&str>,
+        buyout_policy: Option<&str>,
+        sort_by: Option<&str>,
+    ) -> Result<Vec<Auction<AuctionOwner>>, AppError> {
+        let base_url = format!("auctions/search?type={}", auction_type);
+
+        let mut query_params = Vec::new();
+        query_params.push(format!("weapon_url_name={}", weapon_url_name));
+
+        if let Some(e) = element {
+            query_params.push(format!("element={}", e));
+        }
+        if let Some(ephemera) = having_ephemera {
+            query_params.push(format!("ephemera={}", ephemera));
+        }
+        if let Some(damage) = damage_min {
+            query_params.push(format!("damage_min={}", damage));
+        }
+        if let Some(damage) = damage_max {
+            query_params.push(format!("damage_max={}", damage));
+        }
+        if let Some(q) = quirk {
+            query_params.push(format!("quirk={}", q));
+        }
+        if let Some(policy) = buyout_policy {
+            query_params.push(format!("buyout_policy={}", policy));
+        }
+        if let Some(sort) = sort_by {
+            query_params.push(format!("sort_by={}", sort));
+        }
+
+        let full_query = query_params.join("&");
+        let url = format!("{}&{}", base_url, full_query);
+
+        match self
+            .client
+            .get::<Vec<Auction<AuctionOwner>>>(&url, Some("auctions"))
+            .await
+        {
+            Ok(ApiResult::Success(payload, _headers)) => {
+                self.client.debug(
+                    &self.debug_id,
+                    "Auction:SearchWeapon",
+                    format!(
+                        "Found {} auctions using query: {}",
+                        &payload.len(),
+                        full_query
+                    )
+                    .as_str(),
+                    None,
+                );
+                Ok(payload)
+            }
+            Ok(ApiResult::Error(error, _headers)) => {
+                let log_level = match error.status_code {
+                    400 => crate::enums::LogLevel::Warning,
+                    _ => crate::enums::LogLevel::Error,
+                };
+                Err(self.client.create_api_error(
+                    "Auction:SearchWeapon",
+                    error,
+                    eyre!("There was an error searching for auctions."),
+                    log_level,
+                ))
+            }
+            Err(err) => Err(err),
+        }
+    }
     pub async fn delete(&self, auction_id: &str) -> Result<Option<String>, AppError> {
         let url = format!("auctions/entry/{}/close", auction_id);
 
@@ -408,3 +528,35 @@ pub async fn auction_search(
         .await?;
     Ok(live_auctions)
 }
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn auction_search_weapon(
+    auction_type: &str,
+    weapon_url_name: &str,
+    element: Option<&str>,
+    having_ephemera: Option<bool>,
+    damage_min: Option<i64>,
+    damage_max: Option<i64>,
+    quirk: Option<&str>,
+    buyout_policy: Option<&str>,
+    sort_by: Option<&str>,
+    wfm: tauri::State<'_, Arc<Mutex<WFMClient>>>,
+) -> Result<Vec<Auction<AuctionOwner>>, AppError> {
+    let wfm = wfm.lock()?.clone();
+    let live_auctions = wfm
+        .auction()
+        .search_weapon(
+            auction_type,
+            weapon_url_name,
+            element,
+            having_ephemera,
+            damage_min,
+            damage_max,
+            quirk,
+            buyout_policy,
+            sort_by,
+        )
+        .await?;
+    Ok(live_auctions)
+}
@@ -71,7 +71,7 @@ impl<'a> AuthModule<'a> {
         match self
             .client
             .orders()
-            .create("56783f24cbfa8f0432dd89a2", "buy", 1, 1, false, None)
+            .create("56783f24cbfa8f0432dd89a2", "buy", 1, 1, false, None, None)
             .await
         {
             Ok(order) => {
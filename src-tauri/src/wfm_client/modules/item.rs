@@ -1,6 +1,6 @@
 use crate::{
     error::{ApiResult, AppError},
-    structs::{Item, ItemDetails},
+    structs::{Item, ItemDetails, ItemStatistics},
     wfm_client::client::WFMClient,
 };
 
@@ -35,6 +35,30 @@ impl<'a> ItemModule<'a> {
             }
         };
     }
+    pub async fn get_all_items_in_language(&self, language: &str) -> Result<Vec<Item>, AppError> {
+        match self
+            .client
+            .get_in_language::<Vec<Item>>("items", Some("items"), language)
+            .await
+        {
+            Ok(ApiResult::Success(payload, _headers)) => {
+                self.client.debug(
+                    &self.debug_id,
+                    "Item:GetAllItemsInLanguage",
+                    format!("{} {} items were fetched.", payload.len(), language).as_str(),
+                    None,
+                );
+                Ok(payload)
+            }
+            Ok(ApiResult::Error(error, _headers)) => Err(self.client.create_api_error(
+                "Item:GetAllItemsInLanguage",
+                error,
+                eyre!("There was an error fetching {} items", language),
+                crate::enums::LogLevel::Error,
+            )),
+            Err(err) => Err(err),
+        }
+    }
     pub async fn get_item(&self, item: String) -> Result<ItemDetails, AppError> {
         let url = format!("items/{}", item);
         match self.client.get(&url, Some("item")).await {
@@ -60,4 +84,25 @@ impl<'a> ItemModule<'a> {
             }
         }
     }
+    pub async fn get_item_statistics(&self, item: &str) -> Result<ItemStatistics, AppError> {
+        let url = format!("items/{}/statistics", item);
+        match self.client.get(&url, None).await {
+            Ok(ApiResult::Success(payload, _headers)) => {
+                self.client.debug(
+                    &self.debug_id,
+                    "Item:GetItemStatistics",
+                    format!("Gettting statistics for item: {}", item).as_str(),
+                    None,
+                );
+                Ok(payload)
+            }
+            Ok(ApiResult::Error(error, _headers)) => Err(self.client.create_api_error(
+                "Item:GetItemStatistics",
+                error,
+                eyre!("There was an error fetching statistics for item {}", item),
+                crate::enums::LogLevel::Error,
+            )),
+            Err(err) => Err(err),
+        }
+    }
 }
@@ -56,6 +56,7 @@ impl<'a> OrderModule<'a> {
         Ok(orders)
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub async fn create(
         &self,
         item_id: &str,
@@ -64,6 +65,7 @@ impl<'a> OrderModule<'a> {
         quantity: i64,
         visible: bool,
         rank: Option<f64>,
+        subtype: Option<&str>,
     ) -> Result<Order, AppError> {
         // Construct any JSON body
         let mut body = json!({
@@ -77,6 +79,10 @@ impl<'a> OrderModule<'a> {
         if let Some(rank) = rank {
             body["rank"] = json!(rank);
         }
+        // Add subtype to body if it exists, e.g. relic refinement or fish size
+        if let Some(subtype) = subtype {
+            body["subtype"] = json!(subtype);
+        }
 
         match self
             .client
@@ -88,12 +94,13 @@ impl<'a> OrderModule<'a> {
                     &self.debug_id,
                     "Order:Create",
                     format!(
-                        "Order created type: {} item: {}, platinum: {}, quantity: {}, rank: {}",
+                        "Order created type: {} item: {}, platinum: {}, quantity: {}, rank: {}, subtype: {}",
                         order_type,
                         item_id,
                         platinum,
                         quantity,
-                        rank.unwrap_or(-1.0)
+                        rank.unwrap_or(-1.0),
+                        subtype.unwrap_or("none")
                     )
                     .as_str(),
                     None,
@@ -295,6 +302,16 @@ impl<'a> OrderModule<'a> {
 
     // Methods
     pub async fn get_ordres_by_item(&self, item: &str) -> Result<DataFrame, AppError> {
+        self.get_ordres_by_item_and_rank(item, None, None).await
+    }
+    // Returns the ingame orders for an item at the given mod rank, or at the highest rank found when None.
+    // Only the orders of the given subtype are returned, None only returns the orders without a subtype.
+    pub async fn get_ordres_by_item_and_rank(
+        &self,
+        item: &str,
+        mod_rank: Option<i64>,
+        subtype: Option<&str>,
+    ) -> Result<DataFrame, AppError> {
         let url = format!("items/{}/orders", item);
 
         let orders = match self.client.get::<Vec<Order>>(&url, Some("orders")).await {
@@ -323,17 +340,24 @@ impl<'a> OrderModule<'a> {
         if orders.len() == 0 {
             return Ok(DataFrame::new_no_checks(vec![]));
         }
-        let mod_rank = orders
-            .iter()
-            .max_by(|a, b| a.mod_rank.cmp(&b.mod_rank))
-            .unwrap()
-            .mod_rank;
+        let mod_rank = match mod_rank {
+            Some(mod_rank) => Some(mod_rank),
+            None => {
+                orders
+                    .iter()
+                    .max_by(|a, b| a.mod_rank.cmp(&b.mod_rank))
+                    .unwrap()
+                    .mod_rank
+            }
+        };
 
         let orders: Vec<Order> = orders
             .into_iter()
             .filter(|order| {
                 if let Some(user) = &order.user {
-                    user.status == "ingame" && order.mod_rank == mod_rank
+                    user.status == "ingame"
+                        && order.mod_rank == mod_rank
+                        && order.subtype.as_deref() == subtype
                 } else {
                     false
                 }
@@ -355,6 +379,11 @@ impl<'a> OrderModule<'a> {
                     .map(|item| item.url_name)
                     .unwrap_or("".to_string())],
             ),
+            Series::new(
+                "sub_type",
+                vec![order.subtype.clone().unwrap_or("".to_string())],
+            ),
+            Series::new("mod_rank", vec![order.mod_rank]),
             Series::new("platinum", vec![order.platinum.clone()]),
             Series::new("platform", vec![order.platform.clone()]),
             Series::new("order_type", vec![order.order_type.as_str()]),
@@ -409,6 +438,20 @@ impl<'a> OrderModule<'a> {
                     })
                     .collect::<Vec<_>>(),
             ),
+            Series::new(
+                "sub_type",
+                orders
+                    .iter()
+                    .map(|order| order.subtype.clone().unwrap_or("".to_string()))
+                    .collect::<Vec<_>>(),
+            ),
+            Series::new(
+                "mod_rank",
+                orders
+                    .iter()
+                    .map(|order| order.mod_rank)
+                    .collect::<Vec<_>>(),
+            ),
             Series::new(
                 "platinum",
                 orders
@@ -83,6 +83,7 @@ export const SocketContextProvider = ({ children }: SocketContextProviderProps)
   }, []);
 
 
+  // Send the status on every (re)connect and whenever it changes, the game session events of the EE.log change it too
   useEffect(() => {
     if (!socket) return;
     socket.send(JSON.stringify({
@@ -91,7 +92,7 @@ export const SocketContextProvider = ({ children }: SocketContextProviderProps)
     }));
 
     return () => { };
-  }, [socket]);
+  }, [socket, user?.status]);
 
 
   return (